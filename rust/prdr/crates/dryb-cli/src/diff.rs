@@ -0,0 +1,128 @@
+use proto_dryb::Value;
+
+use crate::text::inline;
+
+/// Lists the paths at which two decoded values differ, as `path: old -> new` lines.
+pub fn diff(left: &Value, right: &Value) -> Vec<String> {
+    let mut out = Vec::new();
+    walk("", left, right, &mut out);
+    out
+}
+
+fn walk(path: &str, left: &Value, right: &Value, out: &mut Vec<String>) {
+    match (left, right) {
+        (Value::Struct(left), Value::Struct(right))
+        | (Value::Tagged { fields: left, .. }, Value::Tagged { fields: right, .. }) => {
+            fields(path, left, right, out)
+        }
+        (
+            Value::Versioned {
+                version: left_version,
+                fields: left,
+                ..
+            },
+            Value::Versioned {
+                version: right_version,
+                fields: right,
+                ..
+            },
+        ) => {
+            if left_version != right_version {
+                out.push(format!(
+                    "{}: version {} -> version {}",
+                    label(path),
+                    left_version,
+                    right_version
+                ));
+            }
+            fields(path, left, right, out);
+        }
+        (
+            Value::Variant {
+                name: left_name,
+                fields: left,
+                ..
+            },
+            Value::Variant {
+                name: right_name,
+                fields: right,
+                ..
+            },
+        ) if left_name == right_name => fields(path, left, right, out),
+        (Value::Option(Some(left)), Value::Option(Some(right))) => walk(path, left, right, out),
+        (Value::Seq(left), Value::Seq(right)) => {
+            if left.len() != right.len() {
+                out.push(format!(
+                    "{}: {} items -> {} items",
+                    label(path),
+                    left.len(),
+                    right.len()
+                ));
+            }
+            for (i, (l, r)) in left.iter().zip(right).enumerate() {
+                walk(&format!("{}[{}]", path, i), l, r, out);
+            }
+        }
+        (Value::Map(left), Value::Map(right)) => {
+            for (key, l) in left {
+                let entry_path = format!("{}[{}]", path, inline(key));
+                match right.iter().find(|(k, _)| k == key) {
+                    Some((_, r)) => walk(&entry_path, l, r, out),
+                    None => out.push(format!("{}: {} -> <absent>", entry_path, inline(l))),
+                }
+            }
+            for (key, r) in right {
+                if !left.iter().any(|(k, _)| k == key) {
+                    let entry_path = format!("{}[{}]", path, inline(key));
+                    out.push(format!("{}: <absent> -> {}", entry_path, inline(r)));
+                }
+            }
+        }
+        _ if same(left, right) => {}
+        _ => out.push(format!(
+            "{}: {} -> {}",
+            label(path),
+            inline(left),
+            inline(right)
+        )),
+    }
+}
+
+/// Compares floats by their bits, so a NaN matches itself and `-0.0` differs from `0.0`.
+fn same(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::F32(left), Value::F32(right)) => left.to_bits() == right.to_bits(),
+        (Value::F64(left), Value::F64(right)) => left.to_bits() == right.to_bits(),
+        _ => left == right,
+    }
+}
+
+fn fields(path: &str, left: &[(String, Value)], right: &[(String, Value)], out: &mut Vec<String>) {
+    let join = |name: &str| {
+        if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", path, name)
+        }
+    };
+
+    for (name, l) in left {
+        match right.iter().find(|(n, _)| n == name) {
+            Some((_, r)) => walk(&join(name), l, r, out),
+            None => out.push(format!("{}: {} -> <absent>", join(name), inline(l))),
+        }
+    }
+    for (name, r) in right {
+        if !left.iter().any(|(n, _)| n == name) {
+            out.push(format!("{}: <absent> -> {}", join(name), inline(r)));
+        }
+    }
+}
+
+fn label(path: &str) -> &str {
+    if path.is_empty() {
+        "value"
+    } else {
+        path
+    }
+}
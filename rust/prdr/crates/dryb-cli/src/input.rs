@@ -0,0 +1,46 @@
+use std::fs;
+use std::io::{self, Read};
+
+use crate::Error;
+
+/// Reads the bytes named by an input argument: `-` for stdin, `hex:<digits>` for an
+/// inline hex string, and anything else as a file path.
+pub fn read(spec: &str) -> Result<Vec<u8>, Error> {
+    if spec == "-" {
+        let mut bytes = Vec::new();
+        io::stdin().read_to_end(&mut bytes)?;
+        return Ok(bytes);
+    }
+    if let Some(digits) = spec.strip_prefix("hex:") {
+        return parse_hex(digits);
+    }
+
+    fs::read(spec).map_err(|e| format!("{}: {}", spec, e).into())
+}
+
+/// Parses hex digits, ignoring whitespace so that `xxd -p` output can be pasted in.
+pub fn parse_hex(digits: &str) -> Result<Vec<u8>, Error> {
+    let digits = digits
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+    if digits.len() % 2 != 0 {
+        return Err("hex input has an odd number of digits".into());
+    }
+
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let byte = pair.iter().collect::<String>();
+            // `from_str_radix` alone would also take a sign, as in `+f`.
+            if !pair.iter().all(char::is_ascii_hexdigit) {
+                return Err(format!("invalid hex byte `{}`", byte).into());
+            }
+            Ok(u8::from_str_radix(&byte, 16)?)
+        })
+        .collect()
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
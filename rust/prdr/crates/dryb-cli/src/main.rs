@@ -0,0 +1,210 @@
+mod diff;
+mod input;
+mod text;
+
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use proto_dryb::dump::{dump, Style};
+use proto_dryb::json;
+use proto_dryb::schema::{self, Type};
+use proto_dryb::{Endianness, Value};
+
+type Error = Box<dyn std::error::Error>;
+
+/// Inspects and converts proto-dryb payloads using an exported schema file.
+///
+/// INPUT arguments name a file, `-` for stdin, or `hex:<digits>` for inline bytes.
+#[derive(Parser)]
+#[command(name = "dryb", version)]
+struct Cli {
+    /// Schema file written by `proto_dryb::schema::export`, conventionally named `.drys`.
+    /// `.dryb` sources have to be compiled and exported first.
+    #[arg(short, long, global = true, default_value = "schema.drys")]
+    schema: String,
+
+    /// Byte order the payload was written with.
+    #[arg(short, long, global = true, value_enum, default_value_t = Endian::Little)]
+    endian: Endian,
+
+    /// How JSON output writes `[u8; N]` and `Vec<u8>`. Encoding also accepts arrays.
+    #[arg(long, global = true, value_enum, default_value_t = Bytes::Array)]
+    bytes: Bytes,
+
+    /// Writes `u64` and `i64` as JSON strings.
+    #[arg(long, global = true)]
+    int64_strings: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Decodes a payload and prints it.
+    Decode {
+        input: String,
+
+        #[arg(short, long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+    /// Encodes a JSON document into a payload.
+    Encode {
+        /// JSON file, or `-` for stdin.
+        json: String,
+
+        /// Writes the payload here instead of to stdout.
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Prints the payload as hex instead of raw bytes.
+        #[arg(long)]
+        hex: bool,
+    },
+    /// Prints an annotated hex dump of a payload.
+    Dump {
+        input: String,
+
+        #[arg(long, value_enum, default_value_t = Color::Auto)]
+        color: Color,
+    },
+    /// Lists the fields that differ between two payloads. Exits with 1 if any do.
+    Diff { left: String, right: String },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Endian {
+    Little,
+    Big,
+    Native,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Bytes {
+    Array,
+    Hex,
+    Base64,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+impl From<Bytes> for json::Bytes {
+    fn from(bytes: Bytes) -> Self {
+        match bytes {
+            Bytes::Array => json::Bytes::Array,
+            Bytes::Hex => json::Bytes::Hex,
+            Bytes::Base64 => json::Bytes::Base64,
+        }
+    }
+}
+
+impl From<Endian> for Endianness {
+    fn from(endian: Endian) -> Self {
+        match endian {
+            Endian::Little => Endianness::Little,
+            Endian::Big => Endianness::Big,
+            Endian::Native => Endianness::Native,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode, Error> {
+    let bytes = fs::read(&cli.schema).map_err(|e| format!("{}: {}", cli.schema, e))?;
+    let ty = schema::import(&bytes).map_err(|e| format!("{}: {}", cli.schema, e))?;
+    let endian = cli.endian.into();
+    let options = json::Options {
+        bytes: cli.bytes.into(),
+        int64: if cli.int64_strings {
+            json::Int64::String
+        } else {
+            json::Int64::Number
+        },
+    };
+
+    match cli.command {
+        Command::Decode { input, format } => {
+            let value = decode(&ty, &input, endian)?;
+            match format {
+                Format::Text => println!("{}", text::pretty(&ty, &value)),
+                Format::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&json::value_to_json(&ty, &value, &options))?
+                ),
+            }
+        }
+        Command::Encode { json, output, hex } => {
+            let document = String::from_utf8(input::read(&json)?)?;
+            let document = serde_json::from_str(&document)?;
+            let payload = json::encode(&ty, &document, endian, &options)?;
+
+            let payload = if hex {
+                format!("{}\n", input::to_hex(&payload)).into_bytes()
+            } else {
+                payload
+            };
+            match output {
+                Some(path) => fs::write(&path, payload).map_err(|e| format!("{}: {}", path, e))?,
+                None => io::stdout().write_all(&payload)?,
+            }
+        }
+        Command::Dump { input, color } => {
+            let style = match color {
+                Color::Always => Style::Ansi,
+                Color::Auto if io::stdout().is_terminal() => Style::Ansi,
+                _ => Style::Plain,
+            };
+            println!("{}", dump(&ty, &input::read(&input)?, endian, style));
+        }
+        Command::Diff { left, right } => {
+            let left = decode(&ty, &left, endian)?;
+            let right = decode(&ty, &right, endian)?;
+            let differences = diff::diff(&left, &right);
+            for line in &differences {
+                println!("{}", line);
+            }
+            if !differences.is_empty() {
+                return Ok(ExitCode::from(1));
+            }
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn decode(ty: &Type, input: &str, endian: Endianness) -> Result<Value, Error> {
+    let bytes = input::read(input)?;
+    let (value, size) = Value::decode(ty, &bytes, endian)
+        .map_err(|e| format!("{}: {} (run `dryb dump` to see where)", input, e))?;
+    if size < bytes.len() {
+        eprintln!(
+            "warning: {}: {} trailing bytes after the payload",
+            input,
+            bytes.len() - size
+        );
+    }
+    Ok(value)
+}
@@ -0,0 +1,158 @@
+use std::fmt::Write;
+
+use proto_dryb::schema::{Field, Type, VariantKind};
+use proto_dryb::Value;
+
+const INDENT: &str = "    ";
+
+/// Formats a decoded value like `{:#?}` would format the Rust type it came from.
+pub fn pretty(ty: &Type, value: &Value) -> String {
+    let mut out = String::new();
+    write_pretty(&mut out, ty, value, 0);
+    out
+}
+
+/// Formats a value on a single line, without type names.
+pub fn inline(value: &Value) -> String {
+    match value {
+        Value::U8(v) => v.to_string(),
+        Value::I8(v) => v.to_string(),
+        Value::U16(v) => v.to_string(),
+        Value::I16(v) => v.to_string(),
+        Value::U32(v) => v.to_string(),
+        Value::I32(v) => v.to_string(),
+        Value::U64(v) => v.to_string(),
+        Value::I64(v) => v.to_string(),
+        Value::F32(v) => format!("{:?}", v),
+        Value::F64(v) => format!("{:?}", v),
+        Value::Bool(v) => v.to_string(),
+        Value::String(v) => format!("{:?}", v),
+        Value::Option(None) => "None".to_string(),
+        Value::Option(Some(v)) => format!("Some({})", inline(v)),
+        Value::Seq(items) => format!("[{}]", join(items.iter().map(inline))),
+        Value::Map(entries) => format!(
+            "{{{}}}",
+            join(
+                entries
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", inline(k), inline(v)))
+            )
+        ),
+        Value::Struct(fields) | Value::Versioned { fields, .. } | Value::Tagged { fields, .. } => {
+            format!("{{ {} }}", join(named(fields)))
+        }
+        Value::Variant { name, fields, .. } if fields.is_empty() => name.clone(),
+        Value::Variant { name, fields, .. } if is_tuple(fields) => {
+            format!("{}({})", name, join(fields.iter().map(|(_, v)| inline(v))))
+        }
+        Value::Variant { name, fields, .. } => format!("{} {{ {} }}", name, join(named(fields))),
+    }
+}
+
+fn join(items: impl Iterator<Item = String>) -> String {
+    items.collect::<Vec<_>>().join(", ")
+}
+
+fn named(fields: &[(String, Value)]) -> impl Iterator<Item = String> + '_ {
+    fields
+        .iter()
+        .map(|(name, v)| format!("{}: {}", name, inline(v)))
+}
+
+/// Tuple variant fields are named by their position.
+fn is_tuple(fields: &[(String, Value)]) -> bool {
+    fields
+        .first()
+        .is_some_and(|(name, _)| name.parse::<usize>().is_ok())
+}
+
+fn is_scalar(value: &Value) -> bool {
+    match value {
+        Value::Option(Some(v)) => is_scalar(v),
+        Value::Seq(items) => items.is_empty(),
+        Value::Map(entries) => entries.is_empty(),
+        Value::Struct(fields) | Value::Versioned { fields, .. } | Value::Tagged { fields, .. } => {
+            fields.is_empty()
+        }
+        Value::Variant { fields, .. } => fields.iter().all(|(_, v)| is_scalar(v)),
+        _ => true,
+    }
+}
+
+fn write_pretty(out: &mut String, ty: &Type, value: &Value, depth: usize) {
+    if is_scalar(value) {
+        out.push_str(&inline(value));
+        return;
+    }
+
+    let indent = INDENT.repeat(depth + 1);
+    match (ty, value) {
+        (Type::Option(item), Value::Option(Some(v))) => {
+            out.push_str("Some(");
+            write_pretty(out, item, v, depth);
+            out.push(')');
+        }
+        (Type::Vec { item, .. } | Type::Array { item, .. }, Value::Seq(items)) => {
+            out.push_str("[\n");
+            for v in items {
+                out.push_str(&indent);
+                write_pretty(out, item, v, depth + 1);
+                out.push_str(",\n");
+            }
+            let _ = write!(out, "{}]", INDENT.repeat(depth));
+        }
+        (Type::Map { key, value, .. }, Value::Map(entries)) => {
+            out.push_str("{\n");
+            for (k, v) in entries {
+                out.push_str(&indent);
+                write_pretty(out, key, k, depth + 1);
+                out.push_str(": ");
+                write_pretty(out, value, v, depth + 1);
+                out.push_str(",\n");
+            }
+            let _ = write!(out, "{}}}", INDENT.repeat(depth));
+        }
+        (
+            Type::Struct(s),
+            Value::Struct(fields) | Value::Versioned { fields, .. } | Value::Tagged { fields, .. },
+        ) => {
+            let _ = write!(out, "{} ", s.name);
+            write_fields(out, &s.fields, fields, depth);
+        }
+        (Type::Enum(e), Value::Variant { name, fields, .. }) => {
+            let variant = e.variants.iter().find(|v| v.name == *name);
+            let _ = write!(out, "{}::{}", e.name, name);
+            match variant {
+                Some(variant) if variant.kind == VariantKind::Tuple => {
+                    out.push_str("(\n");
+                    for (field, (_, v)) in variant.fields.iter().zip(fields) {
+                        out.push_str(&indent);
+                        write_pretty(out, &field.ty, v, depth + 1);
+                        out.push_str(",\n");
+                    }
+                    let _ = write!(out, "{})", INDENT.repeat(depth));
+                }
+                Some(variant) => {
+                    out.push(' ');
+                    write_fields(out, &variant.fields, fields, depth);
+                }
+                None => out.push_str(&inline(value)),
+            }
+        }
+        _ => out.push_str(&inline(value)),
+    }
+}
+
+fn write_fields(out: &mut String, schema: &[Field], fields: &[(String, Value)], depth: usize) {
+    let indent = INDENT.repeat(depth + 1);
+    out.push_str("{\n");
+    for (name, v) in fields {
+        let _ = write!(out, "{}{}: ", indent, name);
+        match schema.iter().find(|f| f.name == *name) {
+            Some(field) => write_pretty(out, &field.ty, v, depth + 1),
+            None => out.push_str(&inline(v)),
+        }
+        out.push_str(",\n");
+    }
+    let _ = write!(out, "{}}}", INDENT.repeat(depth));
+}
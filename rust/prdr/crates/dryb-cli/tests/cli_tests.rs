@@ -0,0 +1,221 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use proto_dryb::{
+    schema, Deserialize, DeserializeError, Endianness, Schema, Serialize, SerializeError,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Status {
+    Ok,
+    Moved(String),
+    Failed { code: i32 },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Packet {
+    id: u32,
+    statuses: Vec<Status>,
+    checksum: Option<u8>,
+}
+
+struct Fixture {
+    dir: PathBuf,
+}
+
+impl Fixture {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("dryb-cli-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("schema.drys"), schema::export(&Packet::schema())).unwrap();
+        Fixture { dir }
+    }
+
+    fn write(&self, name: &str, packet: &Packet) -> String {
+        let mut buffer = [0u8; 256];
+        let size = packet.serialize(&mut buffer, Endianness::Big).unwrap();
+        let path = self.dir.join(name);
+        fs::write(&path, &buffer[..size]).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_dryb"))
+            .arg("--schema")
+            .arg(self.dir.join("schema.drys"))
+            .args(["--endian", "big"])
+            .args(args)
+            .output()
+            .unwrap()
+    }
+
+    fn path(&self, name: &str) -> String {
+        self.dir.join(name).to_str().unwrap().to_string()
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn packet() -> Packet {
+    Packet {
+        id: 7,
+        statuses: vec![Status::Ok, Status::Moved("/a".to_string())],
+        checksum: None,
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_decode_text_and_json() {
+    let fixture = Fixture::new("decode");
+    let input = fixture.write("packet.bin", &packet());
+
+    let text = stdout(&fixture.run(&["decode", &input]));
+    assert_eq!(
+        text,
+        "\
+Packet {
+    id: 7,
+    statuses: [
+        Ok,
+        Moved(\"/a\"),
+    ],
+    checksum: None,
+}
+"
+    );
+
+    let json = stdout(&fixture.run(&["decode", &input, "--format", "json"]));
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "id": 7,
+            "statuses": ["Ok", {"Moved": "/a"}],
+            "checksum": null,
+        })
+    );
+}
+
+#[test]
+fn test_encode_from_json() {
+    let fixture = Fixture::new("encode");
+    let json = fixture.path("packet.json");
+    fs::write(
+        &json,
+        r#"{"id": 9, "statuses": [{"Failed": {"code": -1}}], "checksum": 3}"#,
+    )
+    .unwrap();
+
+    let output = fixture.path("packet.bin");
+    stdout(&fixture.run(&["encode", &json, "--output", &output]));
+    let bytes = fs::read(&output).unwrap();
+    let (decoded, _) = Packet::deserialize(&bytes, Endianness::Big).unwrap();
+    assert_eq!(
+        decoded,
+        Packet {
+            id: 9,
+            statuses: vec![Status::Failed { code: -1 }],
+            checksum: Some(3),
+        }
+    );
+
+    let hex = stdout(&fixture.run(&["encode", &json, "--hex"]));
+    assert_eq!(hex.trim(), "000000090000000102ffffffff0103");
+
+    fs::write(&json, r#"{"id": 9, "statuses": [{"Gone": 1}]}"#).unwrap();
+    let output = fixture.run(&["encode", &json]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("has no variant `Gone`"));
+}
+
+#[test]
+fn test_dump_hex_input() {
+    let fixture = Fixture::new("dump");
+    let output = stdout(&fixture.run(&["dump", "hex:00000007 00000000 01 05", "--color", "never"]));
+    assert!(output.contains("000000      4    id: 7"), "{}", output);
+    assert!(output.contains("checksum: 5"), "{}", output);
+
+    let output = fixture.run(&["decode", "hex:000000"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = fixture.run(&["decode", "hex:00000007 00000000 +1 05"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid hex byte `+1`"));
+}
+
+#[test]
+fn test_diff() {
+    let fixture = Fixture::new("diff");
+    let left = fixture.write("left.bin", &packet());
+    let right = fixture.write(
+        "right.bin",
+        &Packet {
+            id: 8,
+            statuses: vec![Status::Ok, Status::Moved("/b".to_string()), Status::Ok],
+            checksum: None,
+        },
+    );
+
+    assert_eq!(stdout(&fixture.run(&["diff", &left, &left])), "");
+
+    let output = fixture.run(&["diff", &left, &right]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "\
+id: 7 -> 8
+statuses: 2 items -> 3 items
+statuses[1].0: \"/a\" -> \"/b\"
+"
+    );
+}
+
+#[test]
+fn test_diff_compares_floats_by_bits() {
+    let fixture = Fixture::new("diff-floats");
+    let schema = fixture.path("floats.drys");
+    fs::write(&schema, schema::export(&Vec::<f64>::schema())).unwrap();
+    let diff = |left: &str, right: &str| {
+        Command::new(env!("CARGO_BIN_EXE_dryb"))
+            .args(["--schema", &schema, "--endian", "big", "diff", left, right])
+            .output()
+            .unwrap()
+    };
+
+    let nan = "hex:00000001 7ff8000000000000";
+    assert_eq!(stdout(&diff(nan, nan)), "");
+
+    let output = diff(
+        "hex:00000001 0000000000000000",
+        "hex:00000001 8000000000000000",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "[0]: 0.0 -> -0.0\n"
+    );
+}
+
+#[test]
+fn test_missing_schema() {
+    let output = Command::new(env!("CARGO_BIN_EXE_dryb"))
+        .args(["--schema", "/nonexistent/schema.drys", "decode", "-"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("error: /nonexistent/schema.drys"));
+}
@@ -0,0 +1,113 @@
+use std::fmt;
+
+use proto_dryb::schema::Primitive;
+use proto_dryb::Endianness;
+
+/// A 1-based location in a `.dryb` source file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct File {
+    pub items: Vec<Item>,
+}
+
+impl File {
+    pub fn item(&self, name: &str) -> Option<&Item> {
+        self.items.iter().find(|item| item.name() == name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Item {
+    Struct(Struct),
+    Enum(Enum),
+}
+
+impl Item {
+    pub fn name(&self) -> &str {
+        match self {
+            Item::Struct(s) => &s.name,
+            Item::Enum(e) => &e.name,
+        }
+    }
+
+    pub fn position(&self) -> Position {
+        match self {
+            Item::Struct(s) => s.position,
+            Item::Enum(e) => e.position,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Struct {
+    pub name: String,
+    pub position: Position,
+    pub docs: Vec<String>,
+    pub endian: Option<Endianness>,
+    pub version: Option<u16>,
+    pub tagged: bool,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Enum {
+    pub name: String,
+    pub position: Position,
+    pub docs: Vec<String>,
+    pub endian: Option<Endianness>,
+    pub length_prefixed: bool,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Variant {
+    pub name: String,
+    pub position: Position,
+    pub docs: Vec<String>,
+    /// The tag written after `=`, if any.
+    pub tag: Option<u8>,
+    pub other: bool,
+    pub kind: VariantKind,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VariantKind {
+    Unit,
+    Tuple,
+    Struct,
+}
+
+/// A struct field or variant field. Tuple variant fields are named by their position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub position: Position,
+    pub docs: Vec<String>,
+    pub ty: Type,
+    pub endian: Option<Endianness>,
+    pub since: Option<u16>,
+    pub id: Option<u16>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    Primitive(Primitive),
+    String,
+    Option(Box<Type>),
+    Vec(Box<Type>),
+    Array(Box<Type>, u32),
+    Map(Box<Type>, Box<Type>),
+    Named(String, Position),
+}
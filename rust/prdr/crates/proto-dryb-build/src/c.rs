@@ -0,0 +1,530 @@
+use std::fmt::{self, Write};
+
+use proto_dryb::schema::{
+    Enum, Field, Primitive, Struct, StructEncoding, Type, Variant, VariantKind,
+};
+use proto_dryb::Endianness;
+
+const C_KEYWORDS: &[&str] = &[
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
+    "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long", "register",
+    "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch", "typedef",
+    "union", "unsigned", "void", "volatile", "while",
+];
+
+const HELPERS: &str = "\
+#ifndef DRYB_BYTE_ORDER_HELPERS
+#define DRYB_BYTE_ORDER_HELPERS
+
+#if defined(__BYTE_ORDER__) && __BYTE_ORDER__ == __ORDER_BIG_ENDIAN__
+#define DRYB_HOST_BIG_ENDIAN 1
+#else
+#define DRYB_HOST_BIG_ENDIAN 0
+#endif
+
+/* Each helper reverses the bytes of a value unless `big_endian` matches the host. */
+static inline uint16_t dryb_swap_u16(uint16_t v, int big_endian) {
+    if (big_endian == DRYB_HOST_BIG_ENDIAN) return v;
+    return (uint16_t)((v >> 8) | (v << 8));
+}
+
+static inline uint32_t dryb_swap_u32(uint32_t v, int big_endian) {
+    if (big_endian == DRYB_HOST_BIG_ENDIAN) return v;
+    return ((v >> 24) & 0xffu) | ((v >> 8) & 0xff00u) | ((v << 8) & 0xff0000u) | (v << 24);
+}
+
+static inline uint64_t dryb_swap_u64(uint64_t v, int big_endian) {
+    if (big_endian == DRYB_HOST_BIG_ENDIAN) return v;
+    return ((uint64_t)dryb_swap_u32((uint32_t)v, !DRYB_HOST_BIG_ENDIAN) << 32)
+        | dryb_swap_u32((uint32_t)(v >> 32), !DRYB_HOST_BIG_ENDIAN);
+}
+
+static inline float dryb_swap_f32(float v, int big_endian) {
+    uint32_t bits;
+    memcpy(&bits, &v, sizeof bits);
+    bits = dryb_swap_u32(bits, big_endian);
+    memcpy(&v, &bits, sizeof v);
+    return v;
+}
+
+static inline double dryb_swap_f64(double v, int big_endian) {
+    uint64_t bits;
+    memcpy(&bits, &v, sizeof bits);
+    bits = dryb_swap_u64(bits, big_endian);
+    memcpy(&v, &bits, sizeof v);
+    return v;
+}
+
+#endif /* DRYB_BYTE_ORDER_HELPERS */
+";
+
+/// A type that has no fixed-size C equivalent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for HeaderError {}
+
+/// Generates a C header declaring packed structs for `types` and the structs and enums they
+/// contain. Every type must encode to a fixed number of bytes. Multi-byte fields hold wire
+/// order; `<Type>_to_host` and `<Type>_to_wire` convert a message in place, assuming it was
+/// written with `endian` unless the schema says otherwise.
+pub fn generate_c_header(
+    name: &str,
+    endian: Endianness,
+    types: &[Type],
+) -> Result<String, HeaderError> {
+    let mut defs = Vec::new();
+    for ty in types {
+        if !matches!(ty, Type::Struct(_) | Type::Enum(_)) {
+            return Err(HeaderError {
+                path: type_name(ty),
+                message: "only structs and enums get a C definition".to_string(),
+            });
+        }
+        collect(ty, &type_name(ty), &mut defs)?;
+    }
+
+    let guard = screaming(name)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>()
+        + "_H";
+    let mut out = String::new();
+    let _ = writeln!(out, "/* Generated by proto-dryb-build. Do not edit. */\n");
+    let _ = writeln!(out, "#ifndef {}\n#define {}\n", guard, guard);
+    out.push_str("#include <stddef.h>\n#include <stdint.h>\n#include <string.h>\n\n");
+    out.push_str(HELPERS);
+
+    out.push_str("\n#pragma pack(push, 1)\n");
+    for def in &defs {
+        out.push('\n');
+        match def {
+            Type::Struct(s) => write_struct(&mut out, s),
+            Type::Enum(e) => write_enum(&mut out, e),
+            _ => unreachable!("only named types are collected"),
+        }
+    }
+    out.push_str("\n#pragma pack(pop)\n");
+
+    let wire = big_endian_expr(endian);
+    for def in &defs {
+        let name = type_name(def);
+        let size = size(def);
+        let _ = write!(
+            out,
+            "\n#define {}_SIZE {}\n_Static_assert(sizeof({}) == {}, \"{} must match its dryb encoding\");\n",
+            screaming(&name),
+            size,
+            name,
+            size,
+            name
+        );
+        if let Type::Struct(Struct {
+            encoding: StructEncoding::Versioned(version),
+            ..
+        }) = def
+        {
+            let _ = writeln!(out, "#define {}_VERSION {}", screaming(&name), version);
+        }
+        write_swap(&mut out, def);
+        let _ = write!(
+            out,
+            "\nstatic inline void {0}_to_host({0} *m) {{ {0}_swap(m, {1}); }}\n\
+             static inline void {0}_to_wire({0} *m) {{ {0}_swap(m, {1}); }}\n",
+            name, wire
+        );
+    }
+
+    let _ = write!(out, "\n#endif /* {} */\n", guard);
+    Ok(out)
+}
+
+/// Pushes the named types reachable from `ty` onto `defs`, dependencies first.
+fn collect<'a>(ty: &'a Type, path: &str, defs: &mut Vec<&'a Type>) -> Result<(), HeaderError> {
+    let variable = |what: &str| {
+        Err(HeaderError {
+            path: path.to_string(),
+            message: format!("{} has no fixed size", what),
+        })
+    };
+
+    match ty {
+        Type::Primitive(_) => return Ok(()),
+        Type::Array { item, .. } => return collect(item, &format!("{}[]", path), defs),
+        Type::Option(_) => return variable("an option"),
+        Type::Vec { .. } => return variable("a vec"),
+        Type::String => return variable("a string"),
+        Type::Map { .. } => return variable("a map"),
+        Type::Struct(s) => {
+            if s.encoding == StructEncoding::Tagged {
+                return variable("a tagged struct");
+            }
+            if s.fields.is_empty() {
+                return Err(HeaderError {
+                    path: path.to_string(),
+                    message: "C does not allow empty structs".to_string(),
+                });
+            }
+            for field in &s.fields {
+                collect(&field.ty, &format!("{}.{}", path, field.name), defs)?;
+            }
+        }
+        Type::Enum(e) => {
+            let mut sizes = Vec::new();
+            for variant in &e.variants {
+                for field in payload(variant) {
+                    let field_path = format!("{}::{}.{}", path, variant.name, field.name);
+                    collect(&field.ty, &field_path, defs)?;
+                }
+                sizes.push((&variant.name, payload_size(variant)));
+            }
+            if let Some((other, other_size)) = sizes.iter().find(|(_, s)| *s != sizes[0].1) {
+                return Err(HeaderError {
+                    path: path.to_string(),
+                    message: format!(
+                        "variants encode to different sizes ({} is {} bytes, {} is {} bytes)",
+                        sizes[0].0, sizes[0].1, other, other_size
+                    ),
+                });
+            }
+        }
+    }
+
+    let name = type_name(ty);
+    match defs.iter().find(|d| type_name(d) == name) {
+        Some(existing) if *existing != ty => Err(HeaderError {
+            path: path.to_string(),
+            message: format!("another type is also named `{}`", name),
+        }),
+        Some(_) => Ok(()),
+        None => {
+            defs.push(ty);
+            Ok(())
+        }
+    }
+}
+
+/// Fields encoded after a variant's tag. A catch-all's captured tag isn't encoded again.
+fn payload(variant: &Variant) -> &[Field] {
+    if variant.captures_tag() {
+        &[]
+    } else {
+        &variant.fields
+    }
+}
+
+fn payload_size(variant: &Variant) -> usize {
+    payload(variant).iter().map(|f| size(&f.ty)).sum()
+}
+
+/// Wire size of a type `collect` accepted.
+fn size(ty: &Type) -> usize {
+    match ty {
+        Type::Primitive(primitive) => primitive.width(),
+        Type::Array { len, item } => *len as usize * size(item),
+        Type::Struct(s) => {
+            let header = match s.encoding {
+                StructEncoding::Versioned(_) => 6,
+                _ => 0,
+            };
+            header + s.fields.iter().map(|f| size(&f.ty)).sum::<usize>()
+        }
+        Type::Enum(e) => {
+            let prefix = if e.length_prefixed { 5 } else { 1 };
+            prefix + e.variants.first().map_or(0, payload_size)
+        }
+        _ => unreachable!("variable size types are rejected by collect"),
+    }
+}
+
+/// Unit-only enums without a length prefix are a bare tag byte.
+fn is_bare_tag(e: &Enum) -> bool {
+    !e.length_prefixed && e.variants.iter().all(|v| payload(v).is_empty())
+}
+
+fn write_struct(out: &mut String, s: &Struct) {
+    let _ = writeln!(out, "typedef struct {} {{", s.name);
+    if let StructEncoding::Versioned(_) = s.encoding {
+        out.push_str("    uint16_t dryb_version;\n    uint32_t dryb_length;\n");
+    }
+    for field in &s.fields {
+        let _ = writeln!(out, "    {};", declaration(&field.ty, &ident(&field.name)));
+    }
+    let _ = writeln!(out, "}} {};", s.name);
+}
+
+fn write_enum(out: &mut String, e: &Enum) {
+    let prefix = screaming(&e.name);
+    out.push_str("enum {\n");
+    for variant in e.variants.iter().filter(|v| !v.captures_tag()) {
+        let _ = writeln!(
+            out,
+            "    {}_{} = {},",
+            prefix,
+            screaming(&variant.name),
+            variant.tag
+        );
+    }
+    out.push_str("};\n");
+
+    if is_bare_tag(e) {
+        let _ = writeln!(out, "typedef uint8_t {};", e.name);
+        return;
+    }
+
+    let _ = writeln!(out, "typedef struct {} {{", e.name);
+    out.push_str("    uint8_t tag;\n");
+    if e.length_prefixed {
+        out.push_str("    uint32_t length;\n");
+    }
+    if payload_size_of(e) > 0 {
+        out.push_str("    union {\n");
+        for variant in &e.variants {
+            if payload(variant).is_empty() {
+                continue;
+            }
+            out.push_str("        struct {\n");
+            for field in payload(variant) {
+                let _ = writeln!(
+                    out,
+                    "            {};",
+                    declaration(&field.ty, &field_ident(variant.kind, &field.name))
+                );
+            }
+            let _ = writeln!(out, "        }} {};", ident(&snake(&variant.name)));
+        }
+        out.push_str("    } body;\n");
+    }
+    let _ = writeln!(out, "}} {};", e.name);
+}
+
+fn payload_size_of(e: &Enum) -> usize {
+    e.variants.first().map_or(0, payload_size)
+}
+
+fn write_swap(out: &mut String, ty: &Type) {
+    let name = type_name(ty);
+    let _ = writeln!(
+        out,
+        "\nstatic inline void {0}_swap({0} *m, int big_endian) {{",
+        name
+    );
+
+    match ty {
+        Type::Struct(s) => {
+            if let Some(endian) = s.endian {
+                let _ = writeln!(out, "    big_endian = {};", big_endian_expr(endian));
+            }
+            if let StructEncoding::Versioned(_) = s.encoding {
+                out.push_str("    m->dryb_version = dryb_swap_u16(m->dryb_version, big_endian);\n");
+                out.push_str("    m->dryb_length = dryb_swap_u32(m->dryb_length, big_endian);\n");
+            }
+            for field in &s.fields {
+                let lvalue = format!("m->{}", ident(&field.name));
+                swap(out, &field.ty, &lvalue, &field_endian(field), 1);
+            }
+        }
+        Type::Enum(e) if is_bare_tag(e) => out.push_str("    (void)m;\n    (void)big_endian;\n"),
+        Type::Enum(e) => {
+            if let Some(endian) = e.endian {
+                let _ = writeln!(out, "    big_endian = {};", big_endian_expr(endian));
+            }
+            if e.length_prefixed {
+                out.push_str("    m->length = dryb_swap_u32(m->length, big_endian);\n");
+            }
+            if payload_size_of(e) > 0 {
+                out.push_str("    switch (m->tag) {\n");
+                for variant in &e.variants {
+                    if payload(variant).is_empty() {
+                        continue;
+                    }
+                    let _ = writeln!(
+                        out,
+                        "    case {}_{}:",
+                        screaming(&e.name),
+                        screaming(&variant.name)
+                    );
+                    for field in payload(variant) {
+                        let lvalue = format!(
+                            "m->body.{}.{}",
+                            ident(&snake(&variant.name)),
+                            field_ident(variant.kind, &field.name)
+                        );
+                        swap(out, &field.ty, &lvalue, &field_endian(field), 2);
+                    }
+                    out.push_str("        break;\n");
+                }
+                out.push_str("    default:\n        break;\n    }\n");
+            } else {
+                out.push_str("    (void)m;\n");
+            }
+        }
+        _ => unreachable!("only named types are collected"),
+    }
+
+    out.push_str("}\n");
+}
+
+fn field_endian(field: &Field) -> String {
+    match field.endian {
+        Some(endian) => big_endian_expr(endian).to_string(),
+        None => "big_endian".to_string(),
+    }
+}
+
+fn swap(out: &mut String, ty: &Type, lvalue: &str, endian: &str, depth: usize) {
+    let indent = "    ".repeat(depth);
+    match ty {
+        Type::Primitive(primitive) => {
+            let (helper, cast) = match primitive {
+                Primitive::U8 | Primitive::I8 | Primitive::Bool => return,
+                Primitive::U16 => ("u16", None),
+                Primitive::I16 => ("u16", Some(("int16_t", "uint16_t"))),
+                Primitive::U32 => ("u32", None),
+                Primitive::I32 => ("u32", Some(("int32_t", "uint32_t"))),
+                Primitive::U64 => ("u64", None),
+                Primitive::I64 => ("u64", Some(("int64_t", "uint64_t"))),
+                Primitive::F32 => ("f32", None),
+                Primitive::F64 => ("f64", None),
+            };
+            let _ = match cast {
+                Some((signed, unsigned)) => writeln!(
+                    out,
+                    "{0}{1} = ({2})dryb_swap_{3}(({4}){1}, {5});",
+                    indent, lvalue, signed, helper, unsigned, endian
+                ),
+                None => writeln!(
+                    out,
+                    "{0}{1} = dryb_swap_{2}({1}, {3});",
+                    indent, lvalue, helper, endian
+                ),
+            };
+        }
+        Type::Array { len, item } => {
+            if !needs_swap(item) {
+                return;
+            }
+            let index = format!("i{}", depth);
+            let _ = writeln!(
+                out,
+                "{0}for (size_t {1} = 0; {1} < {2}; {1}++) {{",
+                indent, index, len
+            );
+            swap(
+                out,
+                item,
+                &format!("{}[{}]", lvalue, index),
+                endian,
+                depth + 1,
+            );
+            let _ = writeln!(out, "{}}}", indent);
+        }
+        Type::Struct(s) => {
+            let _ = writeln!(out, "{}{}_swap(&{}, {});", indent, s.name, lvalue, endian);
+        }
+        Type::Enum(e) if is_bare_tag(e) => {}
+        Type::Enum(e) => {
+            let _ = writeln!(out, "{}{}_swap(&{}, {});", indent, e.name, lvalue, endian);
+        }
+        _ => unreachable!("variable size types are rejected by collect"),
+    }
+}
+
+fn needs_swap(ty: &Type) -> bool {
+    match ty {
+        Type::Primitive(primitive) => primitive.width() > 1,
+        Type::Array { item, .. } => needs_swap(item),
+        Type::Enum(e) => !is_bare_tag(e),
+        _ => true,
+    }
+}
+
+fn declaration(ty: &Type, name: &str) -> String {
+    match ty {
+        Type::Primitive(primitive) => {
+            let c_type = match primitive {
+                Primitive::U8 | Primitive::Bool => "uint8_t",
+                Primitive::I8 => "int8_t",
+                Primitive::U16 => "uint16_t",
+                Primitive::I16 => "int16_t",
+                Primitive::U32 => "uint32_t",
+                Primitive::I32 => "int32_t",
+                Primitive::U64 => "uint64_t",
+                Primitive::I64 => "int64_t",
+                Primitive::F32 => "float",
+                Primitive::F64 => "double",
+            };
+            format!("{} {}", c_type, name)
+        }
+        Type::Array { len, item } => declaration(item, &format!("{}[{}]", name, len)),
+        Type::Struct(s) => format!("{} {}", s.name, name),
+        Type::Enum(e) => format!("{} {}", e.name, name),
+        _ => unreachable!("variable size types are rejected by collect"),
+    }
+}
+
+fn big_endian_expr(endian: Endianness) -> &'static str {
+    match endian {
+        Endianness::Little => "0",
+        Endianness::Big => "1",
+        Endianness::Native => "DRYB_HOST_BIG_ENDIAN",
+    }
+}
+
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Primitive(primitive) => primitive.name().to_string(),
+        Type::Struct(s) => s.name.clone(),
+        Type::Enum(e) => e.name.clone(),
+        Type::Option(_) => "Option".to_string(),
+        Type::Vec { .. } => "Vec".to_string(),
+        Type::String => "String".to_string(),
+        Type::Array { .. } => "array".to_string(),
+        Type::Map { .. } => "map".to_string(),
+    }
+}
+
+/// Tuple variant fields are named by position, which isn't a C identifier.
+fn field_ident(kind: VariantKind, name: &str) -> String {
+    match kind {
+        VariantKind::Tuple => format!("_{}", name),
+        _ => ident(name),
+    }
+}
+
+fn ident(name: &str) -> String {
+    if C_KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// `CamelCase` to `snake_case`.
+fn snake(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn screaming(name: &str) -> String {
+    snake(name).to_ascii_uppercase()
+}
@@ -0,0 +1,106 @@
+use crate::ast::Position;
+use crate::Error;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Token {
+    Ident(String),
+    Int(u64),
+    Punct(char),
+    /// The text of a `///` comment.
+    Doc(String),
+    Eof,
+}
+
+impl Token {
+    pub fn describe(&self) -> String {
+        match self {
+            Token::Ident(name) => format!("`{}`", name),
+            Token::Int(value) => format!("`{}`", value),
+            Token::Punct(c) => format!("`{}`", c),
+            Token::Doc(_) => "a doc comment".to_string(),
+            Token::Eof => "end of file".to_string(),
+        }
+    }
+}
+
+pub(crate) fn tokenize(source: &str) -> Result<Vec<(Token, Position)>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    let mut position = Position { line: 1, column: 1 };
+
+    macro_rules! bump {
+        () => {{
+            let c = chars.next();
+            if c == Some('\n') {
+                position.line += 1;
+                position.column = 1;
+            } else if c.is_some() {
+                position.column += 1;
+            }
+            c
+        }};
+    }
+
+    while let Some(&c) = chars.peek() {
+        let start = position;
+
+        if c.is_whitespace() {
+            bump!();
+        } else if c == '/' {
+            bump!();
+            if bump!() != Some('/') {
+                return Err(Error::new(start, "expected `//` to start a comment"));
+            }
+            let mut text = String::new();
+            while let Some(&c) = chars.peek() {
+                if c == '\n' {
+                    break;
+                }
+                text.push(c);
+                bump!();
+            }
+            if let Some(doc) = text.strip_prefix('/') {
+                if !doc.starts_with('/') {
+                    let doc = doc.strip_prefix(' ').unwrap_or(doc);
+                    tokens.push((Token::Doc(doc.to_string()), start));
+                }
+            }
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut name = String::new();
+            while let Some(&c) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                name.push(c);
+                bump!();
+            }
+            tokens.push((Token::Ident(name), start));
+        } else if c.is_ascii_digit() {
+            let mut digits = String::new();
+            while let Some(&c) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                if c != '_' {
+                    digits.push(c);
+                }
+                bump!();
+            }
+            let value = match digits.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => digits.parse(),
+            };
+            let value =
+                value.map_err(|_| Error::new(start, format!("invalid integer `{}`", digits)))?;
+            tokens.push((Token::Int(value), start));
+        } else if "@{}()[]<>:;,=".contains(c) {
+            bump!();
+            tokens.push((Token::Punct(c), start));
+        } else {
+            return Err(Error::new(start, format!("unexpected character `{}`", c)));
+        }
+    }
+
+    tokens.push((Token::Eof, position));
+    Ok(tokens)
+}
@@ -0,0 +1,132 @@
+//! Compiles `.dryb` schema files into Rust types that use the proto-dryb derives.
+//!
+//! ```text
+//! /// Sent by the sensor every second.
+//! @endian(big)
+//! @version(2)
+//! struct Reading {
+//!     sensor: u16,
+//!     samples: [f32; 4],
+//!     status: Status,
+//!     @since(2) label: option<string>,
+//! }
+//!
+//! @length_prefixed
+//! enum Status {
+//!     Idle = 0x10,
+//!     Busy { jobs: vec<u32> },
+//!     Fault(u16, string),
+//!     @other Unknown(u8),
+//! }
+//! ```
+//!
+//! Fields are `name: type`, with types `u8` to `i64`, `f32`, `f64`, `bool`, `string`,
+//! `option<T>`, `vec<T>`, `map<K, V>`, `[T; N]` or the name of another struct or enum.
+//! `@endian`, `@version`, `@tagged`, `@length_prefixed`, `@since`, `@id` and `@other` map to
+//! the `#[dryb(...)]` attributes of the same name, and `= N` to `#[dryb(tag = N)]`.
+//!
+//! The only length-prefix choice is `@length_prefixed` on an enum, which puts a `u32` body
+//! length after the tag. `string`, `vec` and `map` always start with a `u32` count, as the Rust
+//! types they become do.
+//!
+//! From a build script:
+//!
+//! ```no_run
+//! let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("messages.rs");
+//! proto_dryb_build::compile("messages.dryb", &out).unwrap();
+//! ```
+//!
+//! and `include!(concat!(env!("OUT_DIR"), "/messages.rs"));` in a module of its own.
+
+pub mod ast;
+mod c;
+mod lexer;
+mod parser;
+mod rust;
+mod validate;
+
+use std::path::{Path, PathBuf};
+use std::{error, fmt, fs, io};
+
+use ast::{File, Position};
+pub use c::{generate_c_header, HeaderError};
+
+/// A syntax or validation error in a `.dryb` source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub position: Position,
+    pub message: String,
+}
+
+impl Error {
+    pub(crate) fn new(position: Position, message: impl Into<String>) -> Self {
+        Error {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.position, self.message)
+    }
+}
+
+impl error::Error for Error {}
+
+#[derive(Debug)]
+pub enum BuildError {
+    Io { path: PathBuf, error: io::Error },
+    Schema { path: PathBuf, error: Error },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            BuildError::Schema { path, error } => write!(f, "{}:{}", path.display(), error),
+        }
+    }
+}
+
+impl error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            BuildError::Io { error, .. } => Some(error),
+            BuildError::Schema { error, .. } => Some(error),
+        }
+    }
+}
+
+/// Parses and validates a `.dryb` source.
+pub fn parse(source: &str) -> Result<File, Error> {
+    let file = parser::parse_file(source)?;
+    validate::validate(&file)?;
+    Ok(file)
+}
+
+/// Generates Rust source for a parsed file.
+pub fn generate_rust(file: &File) -> String {
+    rust::generate(file)
+}
+
+/// Compiles `input` into Rust source at `output`, and asks Cargo to rerun the build script
+/// when `input` changes.
+pub fn compile(input: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<(), BuildError> {
+    let (input, output) = (input.as_ref(), output.as_ref());
+    println!("cargo:rerun-if-changed={}", input.display());
+
+    let source = fs::read_to_string(input).map_err(|error| BuildError::Io {
+        path: input.to_path_buf(),
+        error,
+    })?;
+    let file = parse(&source).map_err(|error| BuildError::Schema {
+        path: input.to_path_buf(),
+        error,
+    })?;
+    fs::write(output, generate_rust(&file)).map_err(|error| BuildError::Io {
+        path: output.to_path_buf(),
+        error,
+    })
+}
@@ -0,0 +1,437 @@
+use proto_dryb::schema::Primitive;
+use proto_dryb::Endianness;
+
+use crate::ast::{Enum, Field, File, Item, Position, Struct, Type, Variant, VariantKind};
+use crate::lexer::{tokenize, Token};
+use crate::Error;
+
+pub(crate) fn parse_file(source: &str) -> Result<File, Error> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        index: 0,
+    };
+
+    let mut items = Vec::new();
+    while parser.peek() != &Token::Eof {
+        items.push(parser.item()?);
+    }
+
+    Ok(File { items })
+}
+
+/// An `@name` or `@name(argument)` annotation, checked by the declaration it precedes.
+struct Attr {
+    name: String,
+    position: Position,
+    argument: Option<(Token, Position)>,
+}
+
+impl Attr {
+    fn flag(&self) -> Result<bool, Error> {
+        match &self.argument {
+            None => Ok(true),
+            Some((_, position)) => Err(Error::new(
+                *position,
+                format!("`@{}` takes no argument", self.name),
+            )),
+        }
+    }
+
+    fn int<T: TryFrom<u64>>(&self, type_name: &str) -> Result<T, Error> {
+        match &self.argument {
+            Some((Token::Int(value), position)) => T::try_from(*value).map_err(|_| {
+                Error::new(
+                    *position,
+                    format!("`@{}` must fit in a {}", self.name, type_name),
+                )
+            }),
+            Some((_, position)) => Err(Error::new(
+                *position,
+                format!("`@{}` expects an integer", self.name),
+            )),
+            None => Err(Error::new(
+                self.position,
+                format!("`@{}` expects an integer argument", self.name),
+            )),
+        }
+    }
+
+    fn endian(&self) -> Result<Endianness, Error> {
+        match &self.argument {
+            Some((Token::Ident(name), position)) => match name.as_str() {
+                "little" => Ok(Endianness::Little),
+                "big" => Ok(Endianness::Big),
+                "native" => Ok(Endianness::Native),
+                _ => Err(Error::new(
+                    *position,
+                    "expected `little`, `big` or `native`",
+                )),
+            },
+            Some((_, position)) => Err(Error::new(
+                *position,
+                "expected `little`, `big` or `native`",
+            )),
+            None => Err(Error::new(
+                self.position,
+                "`@endian` expects `little`, `big` or `native`",
+            )),
+        }
+    }
+
+    fn misplaced(&self, what: &str) -> Error {
+        match self.name.as_str() {
+            "length_prefixed" if what == "a struct" => {
+                Error::new(self.position, "`@length_prefixed` only applies to enums")
+            }
+            "version" | "tagged" if what == "an enum" => Error::new(
+                self.position,
+                format!("`@{}` only applies to structs", self.name),
+            ),
+            _ => Error::new(
+                self.position,
+                format!("unknown attribute `@{}` on {}", self.name, what),
+            ),
+        }
+    }
+}
+
+/// Rejects an attribute given twice on the same declaration.
+fn once<T>(slot: &mut Option<T>, attr: &Attr, value: T) -> Result<(), Error> {
+    if slot.is_some() {
+        return Err(Error::new(
+            attr.position,
+            format!("duplicate `@{}`", attr.name),
+        ));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+struct Parser {
+    tokens: Vec<(Token, Position)>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index].0
+    }
+
+    fn position(&self) -> Position {
+        self.tokens[self.index].1
+    }
+
+    fn next(&mut self) -> (Token, Position) {
+        let token = self.tokens[self.index].clone();
+        if token.0 != Token::Eof {
+            self.index += 1;
+        }
+        token
+    }
+
+    fn unexpected(&self, expected: &str) -> Error {
+        Error::new(
+            self.position(),
+            format!("expected {}, found {}", expected, self.peek().describe()),
+        )
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == &Token::Punct(c) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), Error> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", c)))
+        }
+    }
+
+    fn ident(&mut self, what: &str) -> Result<(String, Position), Error> {
+        match self.peek() {
+            Token::Ident(_) => match self.next() {
+                (Token::Ident(name), position) => Ok((name, position)),
+                _ => unreachable!(),
+            },
+            _ => Err(self.unexpected(what)),
+        }
+    }
+
+    /// Doc comments and attributes preceding a declaration, in any order.
+    fn prelude(&mut self) -> Result<(Vec<String>, Vec<Attr>), Error> {
+        let mut docs = Vec::new();
+        let mut attrs = Vec::new();
+
+        loop {
+            match self.peek() {
+                Token::Doc(_) => {
+                    if let (Token::Doc(doc), _) = self.next() {
+                        docs.push(doc);
+                    }
+                }
+                Token::Punct('@') => {
+                    self.next();
+                    let (name, position) = self.ident("an attribute name")?;
+                    let argument = if self.eat('(') {
+                        let argument = match self.peek() {
+                            Token::Ident(_) | Token::Int(_) => self.next(),
+                            _ => return Err(self.unexpected("an attribute argument")),
+                        };
+                        self.expect(')')?;
+                        Some(argument)
+                    } else {
+                        None
+                    };
+                    attrs.push(Attr {
+                        name,
+                        position,
+                        argument,
+                    });
+                }
+                _ => return Ok((docs, attrs)),
+            }
+        }
+    }
+
+    fn item(&mut self) -> Result<Item, Error> {
+        let (docs, attrs) = self.prelude()?;
+        let (keyword, position) = self.ident("`struct` or `enum`")?;
+        match keyword.as_str() {
+            "struct" => self.structure(docs, attrs).map(Item::Struct),
+            "enum" => self.enumeration(docs, attrs).map(Item::Enum),
+            _ => Err(Error::new(
+                position,
+                format!("expected `struct` or `enum`, found `{}`", keyword),
+            )),
+        }
+    }
+
+    fn structure(&mut self, docs: Vec<String>, attrs: Vec<Attr>) -> Result<Struct, Error> {
+        let (name, position) = self.ident("a struct name")?;
+        let mut endian = None;
+        let mut version = None;
+        let mut tagged = None;
+        for attr in &attrs {
+            match attr.name.as_str() {
+                "endian" => once(&mut endian, attr, attr.endian()?)?,
+                "version" => once(&mut version, attr, attr.int("u16")?)?,
+                "tagged" => once(&mut tagged, attr, attr.flag()?)?,
+                _ => return Err(attr.misplaced("a struct")),
+            }
+        }
+
+        self.expect('{')?;
+        let fields = self.fields()?;
+
+        Ok(Struct {
+            name,
+            position,
+            docs,
+            endian,
+            version,
+            tagged: tagged.unwrap_or(false),
+            fields,
+        })
+    }
+
+    fn enumeration(&mut self, docs: Vec<String>, attrs: Vec<Attr>) -> Result<Enum, Error> {
+        let (name, position) = self.ident("an enum name")?;
+        let mut endian = None;
+        let mut length_prefixed = None;
+        for attr in &attrs {
+            match attr.name.as_str() {
+                "endian" => once(&mut endian, attr, attr.endian()?)?,
+                "length_prefixed" => once(&mut length_prefixed, attr, attr.flag()?)?,
+                _ => return Err(attr.misplaced("an enum")),
+            }
+        }
+
+        self.expect('{')?;
+        let mut variants = Vec::new();
+        while !self.eat('}') {
+            variants.push(self.variant()?);
+            if !self.eat(',') {
+                self.expect('}')?;
+                break;
+            }
+        }
+
+        Ok(Enum {
+            name,
+            position,
+            docs,
+            endian,
+            length_prefixed: length_prefixed.unwrap_or(false),
+            variants,
+        })
+    }
+
+    fn variant(&mut self) -> Result<Variant, Error> {
+        let (docs, attrs) = self.prelude()?;
+        let (name, position) = self.ident("a variant name")?;
+        let mut other = None;
+        for attr in &attrs {
+            match attr.name.as_str() {
+                "other" => once(&mut other, attr, attr.flag()?)?,
+                _ => return Err(attr.misplaced("a variant")),
+            }
+        }
+
+        let (kind, fields) = if self.eat('(') {
+            (VariantKind::Tuple, self.tuple_fields()?)
+        } else if self.eat('{') {
+            (VariantKind::Struct, self.fields()?)
+        } else {
+            (VariantKind::Unit, Vec::new())
+        };
+
+        let tag = if self.eat('=') {
+            match self.next() {
+                (Token::Int(tag), position) => Some(
+                    u8::try_from(tag)
+                        .map_err(|_| Error::new(position, "variant tags must fit in a u8"))?,
+                ),
+                (token, position) => {
+                    return Err(Error::new(
+                        position,
+                        format!("expected a variant tag, found {}", token.describe()),
+                    ))
+                }
+            }
+        } else {
+            None
+        };
+
+        Ok(Variant {
+            name,
+            position,
+            docs,
+            tag,
+            other: other.unwrap_or(false),
+            kind,
+            fields,
+        })
+    }
+
+    /// Named fields up to and including the closing `}`.
+    fn fields(&mut self) -> Result<Vec<Field>, Error> {
+        let mut fields = Vec::new();
+        while !self.eat('}') {
+            let (docs, attrs) = self.prelude()?;
+            let (name, position) = self.ident("a field name")?;
+            self.expect(':')?;
+            fields.push(self.field(name, position, docs, attrs)?);
+            if !self.eat(',') {
+                self.expect('}')?;
+                break;
+            }
+        }
+        Ok(fields)
+    }
+
+    /// Positional fields up to and including the closing `)`.
+    fn tuple_fields(&mut self) -> Result<Vec<Field>, Error> {
+        let mut fields = Vec::new();
+        while !self.eat(')') {
+            let (docs, attrs) = self.prelude()?;
+            let position = self.position();
+            fields.push(self.field(fields.len().to_string(), position, docs, attrs)?);
+            if !self.eat(',') {
+                self.expect(')')?;
+                break;
+            }
+        }
+        Ok(fields)
+    }
+
+    fn field(
+        &mut self,
+        name: String,
+        position: Position,
+        docs: Vec<String>,
+        attrs: Vec<Attr>,
+    ) -> Result<Field, Error> {
+        let mut endian = None;
+        let mut since = None;
+        let mut id = None;
+        for attr in &attrs {
+            match attr.name.as_str() {
+                "endian" => once(&mut endian, attr, attr.endian()?)?,
+                "since" => once(&mut since, attr, attr.int("u16")?)?,
+                "id" => once(&mut id, attr, attr.int("u16")?)?,
+                _ => return Err(attr.misplaced("a field")),
+            }
+        }
+
+        Ok(Field {
+            name,
+            position,
+            docs,
+            ty: self.ty()?,
+            endian,
+            since,
+            id,
+        })
+    }
+
+    fn ty(&mut self) -> Result<Type, Error> {
+        if self.eat('[') {
+            let item = self.ty()?;
+            self.expect(';')?;
+            let len = match self.next() {
+                (Token::Int(len), position) => u32::try_from(len)
+                    .map_err(|_| Error::new(position, "array length must fit in a u32"))?,
+                (token, position) => {
+                    return Err(Error::new(
+                        position,
+                        format!("expected an array length, found {}", token.describe()),
+                    ))
+                }
+            };
+            self.expect(']')?;
+            return Ok(Type::Array(Box::new(item), len));
+        }
+
+        let (name, position) = self.ident("a type")?;
+        let primitive = match name.as_str() {
+            "u8" => Primitive::U8,
+            "i8" => Primitive::I8,
+            "u16" => Primitive::U16,
+            "i16" => Primitive::I16,
+            "u32" => Primitive::U32,
+            "i32" => Primitive::I32,
+            "u64" => Primitive::U64,
+            "i64" => Primitive::I64,
+            "f32" => Primitive::F32,
+            "f64" => Primitive::F64,
+            "bool" => Primitive::Bool,
+            "string" => return Ok(Type::String),
+            "option" | "vec" => {
+                self.expect('<')?;
+                let item = Box::new(self.ty()?);
+                self.expect('>')?;
+                return Ok(if name == "option" {
+                    Type::Option(item)
+                } else {
+                    Type::Vec(item)
+                });
+            }
+            "map" => {
+                self.expect('<')?;
+                let key = self.ty()?;
+                self.expect(',')?;
+                let value = self.ty()?;
+                self.expect('>')?;
+                return Ok(Type::Map(Box::new(key), Box::new(value)));
+            }
+            _ => return Ok(Type::Named(name, position)),
+        };
+        Ok(Type::Primitive(primitive))
+    }
+}
@@ -0,0 +1,220 @@
+use std::fmt::Write;
+
+use proto_dryb::Endianness;
+
+use crate::ast::{Enum, Field, File, Item, Struct, Type, Variant, VariantKind};
+use crate::validate::has_default;
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+pub(crate) fn generate(file: &File) -> String {
+    let mut out = String::from("// Generated by proto-dryb-build. Do not edit.\n");
+    if !file.items.is_empty() {
+        out.push_str(
+            "\nuse proto_dryb::{Deserialize, DeserializeError, Endianness, Serialize, SerializeError};\n",
+        );
+    }
+
+    for item in &file.items {
+        out.push('\n');
+        match item {
+            Item::Struct(s) => write_struct(&mut out, file, s),
+            Item::Enum(e) => write_enum(&mut out, e),
+        }
+    }
+
+    out
+}
+
+fn write_struct(out: &mut String, file: &File, s: &Struct) {
+    let default = s
+        .fields
+        .iter()
+        .all(|f| has_default(file, &f.ty, &mut Vec::new()));
+
+    write_docs(out, &s.docs, "");
+    write_derive(out, default);
+    let mut attrs = Vec::new();
+    if let Some(endian) = s.endian {
+        attrs.push(format!("endian = \"{}\"", endian_name(endian)));
+    }
+    if let Some(version) = s.version {
+        attrs.push(format!("version = {}", version));
+    }
+    if s.tagged {
+        attrs.push("tagged".to_string());
+    }
+    write_attrs(out, &attrs, "");
+    let _ = writeln!(out, "pub struct {} {{", ident(&s.name));
+    write_fields(out, &s.fields, "    ", "pub ");
+    out.push_str("}\n");
+}
+
+fn write_enum(out: &mut String, e: &Enum) {
+    let unit = |v: &&Variant| v.kind == VariantKind::Unit;
+    let default = e
+        .variants
+        .iter()
+        .filter(unit)
+        .find(|v| !v.other)
+        .or_else(|| e.variants.iter().find(unit));
+
+    write_docs(out, &e.docs, "");
+    write_derive(out, default.is_some());
+    let mut attrs = Vec::new();
+    if let Some(endian) = e.endian {
+        attrs.push(format!("endian = \"{}\"", endian_name(endian)));
+    }
+    if e.length_prefixed {
+        attrs.push("length_prefixed".to_string());
+    }
+    write_attrs(out, &attrs, "");
+    let _ = writeln!(out, "pub enum {} {{", ident(&e.name));
+
+    for variant in &e.variants {
+        write_docs(out, &variant.docs, "    ");
+        if default.is_some_and(|d| d.name == variant.name) {
+            out.push_str("    #[default]\n");
+        }
+        let mut attrs = Vec::new();
+        if let Some(tag) = variant.tag {
+            attrs.push(format!("tag = {}", tag));
+        }
+        if variant.other {
+            attrs.push("other".to_string());
+        }
+        write_attrs(out, &attrs, "    ");
+
+        let _ = write!(out, "    {}", ident(&variant.name));
+        match variant.kind {
+            VariantKind::Unit => {}
+            VariantKind::Tuple if variant.fields.iter().any(|f| !f.docs.is_empty()) => {
+                out.push_str("(\n");
+                write_fields(out, &variant.fields, "        ", "");
+                out.push_str("    )");
+            }
+            VariantKind::Tuple => {
+                let fields = variant
+                    .fields
+                    .iter()
+                    .map(|f| format!("{}{}", inline_attrs(f), rust_type(&f.ty)))
+                    .collect::<Vec<_>>();
+                let _ = write!(out, "({})", fields.join(", "));
+            }
+            VariantKind::Struct => {
+                out.push_str(" {\n");
+                write_fields(out, &variant.fields, "        ", "");
+                out.push_str("    }");
+            }
+        }
+        out.push_str(",\n");
+    }
+    out.push_str("}\n");
+}
+
+fn write_derive(out: &mut String, default: bool) {
+    let default = if default { "Default, " } else { "" };
+    let _ = writeln!(
+        out,
+        "#[derive(Clone, Debug, {}PartialEq, Serialize, Deserialize)]",
+        default
+    );
+}
+
+fn write_docs(out: &mut String, docs: &[String], indent: &str) {
+    for doc in docs {
+        if doc.is_empty() {
+            let _ = writeln!(out, "{}///", indent);
+        } else {
+            let _ = writeln!(out, "{}/// {}", indent, doc);
+        }
+    }
+}
+
+fn write_attrs(out: &mut String, attrs: &[String], indent: &str) {
+    if !attrs.is_empty() {
+        let _ = writeln!(out, "{}#[dryb({})]", indent, attrs.join(", "));
+    }
+}
+
+/// Writes named fields, or positional ones when they are named by index.
+fn write_fields(out: &mut String, fields: &[Field], indent: &str, visibility: &str) {
+    for field in fields {
+        write_docs(out, &field.docs, indent);
+        write_attrs(out, &field_attrs(field), indent);
+        if field.name.parse::<usize>().is_ok() {
+            let _ = writeln!(out, "{}{},", indent, rust_type(&field.ty));
+        } else {
+            let _ = writeln!(
+                out,
+                "{}{}{}: {},",
+                indent,
+                visibility,
+                ident(&field.name),
+                rust_type(&field.ty)
+            );
+        }
+    }
+}
+
+fn field_attrs(field: &Field) -> Vec<String> {
+    let mut attrs = Vec::new();
+    if let Some(endian) = field.endian {
+        attrs.push(format!("endian = \"{}\"", endian_name(endian)));
+    }
+    if let Some(since) = field.since {
+        attrs.push(format!("since = {}", since));
+    }
+    if let Some(id) = field.id {
+        attrs.push(format!("id = {}", id));
+    }
+    attrs
+}
+
+fn inline_attrs(field: &Field) -> String {
+    let attrs = field_attrs(field);
+    if attrs.is_empty() {
+        String::new()
+    } else {
+        format!("#[dryb({})] ", attrs.join(", "))
+    }
+}
+
+fn rust_type(ty: &Type) -> String {
+    match ty {
+        Type::Primitive(primitive) => primitive.name().to_string(),
+        Type::String => "String".to_string(),
+        Type::Option(item) => format!("Option<{}>", rust_type(item)),
+        Type::Vec(item) => format!("Vec<{}>", rust_type(item)),
+        Type::Array(item, len) => format!("[{}; {}]", rust_type(item), len),
+        Type::Map(key, value) => format!(
+            "::std::collections::BTreeMap<{}, {}>",
+            rust_type(key),
+            rust_type(value)
+        ),
+        Type::Named(name, _) => ident(name),
+    }
+}
+
+fn endian_name(endian: Endianness) -> &'static str {
+    match endian {
+        Endianness::Little => "little",
+        Endianness::Big => "big",
+        Endianness::Native => "native",
+    }
+}
+
+/// Schema names that are Rust keywords are written as raw identifiers.
+fn ident(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
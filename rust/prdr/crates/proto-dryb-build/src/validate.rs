@@ -0,0 +1,352 @@
+use proto_dryb::schema::Primitive;
+
+use crate::ast::{Enum, Field, File, Item, Struct, Type, VariantKind};
+use crate::Error;
+
+/// Names the generated code refers to, which a schema type must not shadow.
+const RESERVED_TYPES: &[&str] = &[
+    "Self",
+    "String",
+    "Vec",
+    "Option",
+    "Some",
+    "None",
+    "Default",
+    "Serialize",
+    "Deserialize",
+    "Schema",
+    "Endianness",
+    "SerializeError",
+    "DeserializeError",
+];
+
+/// Keywords that cannot be written as raw identifiers.
+const RESERVED_NAMES: &[&str] = &["_", "self", "Self", "super", "crate"];
+
+/// Checks the rules the derives enforce, so mistakes are reported against the `.dryb` source
+/// instead of the generated code.
+pub(crate) fn validate(file: &File) -> Result<(), Error> {
+    for (index, item) in file.items.iter().enumerate() {
+        if let Some(first) = file.items[..index].iter().find(|i| i.name() == item.name()) {
+            return Err(Error::new(
+                item.position(),
+                format!(
+                    "`{}` is already defined at {}",
+                    item.name(),
+                    first.position()
+                ),
+            ));
+        }
+        if RESERVED_TYPES.contains(&item.name()) {
+            return Err(Error::new(
+                item.position(),
+                format!(
+                    "`{}` clashes with a name the generated code uses",
+                    item.name()
+                ),
+            ));
+        }
+
+        match item {
+            Item::Struct(s) => check_struct(file, s)?,
+            Item::Enum(e) => check_enum(file, e)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn check_struct(file: &File, s: &Struct) -> Result<(), Error> {
+    if s.tagged && s.version.is_some() {
+        return Err(Error::new(
+            s.position,
+            "`@tagged` structs cannot also be versioned",
+        ));
+    }
+    check_names(&s.fields)?;
+
+    let mut previous = 0;
+    let mut ids: Vec<(u16, &Field)> = Vec::new();
+    for field in &s.fields {
+        check_field_type(file, &s.name, field)?;
+
+        match field.id {
+            Some(_) if !s.tagged => {
+                return Err(Error::new(
+                    field.position,
+                    "`@id` requires a `@tagged` struct",
+                ))
+            }
+            None if s.tagged => {
+                return Err(Error::new(
+                    field.position,
+                    format!(
+                        "field `{}` of a `@tagged` struct needs an `@id`",
+                        field.name
+                    ),
+                ))
+            }
+            Some(id) => {
+                if let Some((_, first)) = ids.iter().find(|(i, _)| *i == id) {
+                    return Err(Error::new(
+                        field.position,
+                        format!("`@id({})` is already used by `{}`", id, first.name),
+                    ));
+                }
+                ids.push((id, field));
+            }
+            None => {}
+        }
+
+        if s.tagged && !has_default(file, &field.ty, &mut Vec::new()) {
+            return Err(Error::new(
+                field.position,
+                format!(
+                    "field `{}` of a `@tagged` struct needs a type with a default value",
+                    field.name
+                ),
+            ));
+        }
+
+        let Some(since) = field.since else {
+            if previous > 0 {
+                return Err(Error::new(
+                    field.position,
+                    "fields without `@since` must come before versioned fields",
+                ));
+            }
+            continue;
+        };
+        match s.version {
+            None => {
+                return Err(Error::new(
+                    field.position,
+                    "`@since` requires a `@version` on the struct",
+                ))
+            }
+            Some(version) if since > version => {
+                return Err(Error::new(
+                    field.position,
+                    format!(
+                        "`@since({})` is newer than the struct version {}",
+                        since, version
+                    ),
+                ))
+            }
+            _ if since < previous => {
+                return Err(Error::new(
+                    field.position,
+                    "versioned fields must be declared in ascending `@since` order",
+                ))
+            }
+            _ => previous = since,
+        }
+        if !has_default(file, &field.ty, &mut Vec::new()) {
+            return Err(Error::new(
+                field.position,
+                format!(
+                    "`@since` field `{}` needs a type with a default value",
+                    field.name
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_enum(file: &File, e: &Enum) -> Result<(), Error> {
+    let mut tags: Vec<(u8, &str)> = Vec::new();
+    let mut other = None;
+
+    for (index, variant) in e.variants.iter().enumerate() {
+        if let Some(first) = e.variants[..index].iter().find(|v| v.name == variant.name) {
+            return Err(Error::new(
+                variant.position,
+                format!(
+                    "variant `{}` is already defined at {}",
+                    variant.name, first.position
+                ),
+            ));
+        }
+        if RESERVED_NAMES.contains(&variant.name.as_str()) {
+            return Err(Error::new(
+                variant.position,
+                format!("`{}` cannot be used as a variant name", variant.name),
+            ));
+        }
+
+        let tag = match (variant.tag, tags.last()) {
+            (Some(tag), _) => tag,
+            (None, None) => 0,
+            (None, Some((previous, _))) => previous.checked_add(1).ok_or_else(|| {
+                Error::new(
+                    variant.position,
+                    format!(
+                        "the tag of `{}` overflows a u8, give it one with `=`",
+                        variant.name
+                    ),
+                )
+            })?,
+        };
+        if let Some((_, first)) = tags.iter().find(|(t, _)| *t == tag) {
+            return Err(Error::new(
+                variant.position,
+                format!("tag {} is already used by `{}`", tag, first),
+            ));
+        }
+        tags.push((tag, &variant.name));
+
+        if variant.other {
+            if other.is_some() {
+                return Err(Error::new(
+                    variant.position,
+                    "only one variant can be marked `@other`",
+                ));
+            }
+            let shape_ok = match variant.kind {
+                VariantKind::Unit => true,
+                VariantKind::Tuple => {
+                    matches!(variant.fields.as_slice(), [f] if f.ty == Type::Primitive(Primitive::U8))
+                }
+                VariantKind::Struct => false,
+            };
+            if !shape_ok {
+                return Err(Error::new(
+                    variant.position,
+                    "`@other` variant must be a unit variant or hold a single u8 tag",
+                ));
+            }
+            other = Some(variant);
+        }
+
+        if variant.kind == VariantKind::Struct {
+            check_names(&variant.fields)?;
+        }
+        for field in &variant.fields {
+            if field.since.is_some() || field.id.is_some() {
+                return Err(Error::new(
+                    field.position,
+                    "`@since` and `@id` only apply to struct fields",
+                ));
+            }
+            check_field_type(file, &e.name, field)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn check_names(fields: &[Field]) -> Result<(), Error> {
+    for (index, field) in fields.iter().enumerate() {
+        if let Some(first) = fields[..index].iter().find(|f| f.name == field.name) {
+            return Err(Error::new(
+                field.position,
+                format!(
+                    "field `{}` is already defined at {}",
+                    field.name, first.position
+                ),
+            ));
+        }
+        if RESERVED_NAMES.contains(&field.name.as_str()) {
+            return Err(Error::new(
+                field.position,
+                format!("`{}` cannot be used as a field name", field.name),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_field_type(file: &File, owner: &str, field: &Field) -> Result<(), Error> {
+    check_type(file, field, &field.ty)?;
+    if contains(file, &field.ty, owner, &mut Vec::new()) {
+        return Err(Error::new(
+            field.position,
+            format!(
+                "`{}` contains itself without a `vec` or `map` in between",
+                owner
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn check_type(file: &File, field: &Field, ty: &Type) -> Result<(), Error> {
+    match ty {
+        Type::Primitive(_) | Type::String => Ok(()),
+        Type::Option(item) | Type::Vec(item) | Type::Array(item, _) => {
+            check_type(file, field, item)
+        }
+        Type::Map(key, value) => {
+            let valid_key = match **key {
+                Type::Primitive(Primitive::F32 | Primitive::F64) => false,
+                Type::Primitive(_) | Type::String => true,
+                _ => false,
+            };
+            if !valid_key {
+                return Err(Error::new(
+                    field.position,
+                    "map keys must be integers, bool or string",
+                ));
+            }
+            check_type(file, field, value)
+        }
+        Type::Named(name, position) => match file.item(name) {
+            Some(_) => Ok(()),
+            None => Err(Error::new(*position, format!("unknown type `{}`", name))),
+        },
+    }
+}
+
+/// Whether `ty` holds a `target` inline, which would give `target` an infinite size.
+fn contains<'a>(file: &'a File, ty: &'a Type, target: &str, seen: &mut Vec<&'a str>) -> bool {
+    match ty {
+        Type::Primitive(_) | Type::String | Type::Vec(_) | Type::Map(..) => false,
+        Type::Option(item) | Type::Array(item, _) => contains(file, item, target, seen),
+        Type::Named(name, _) => {
+            if name == target {
+                return true;
+            }
+            if seen.contains(&name.as_str()) {
+                return false;
+            }
+            seen.push(name);
+            match file.item(name) {
+                Some(Item::Struct(s)) => {
+                    s.fields.iter().any(|f| contains(file, &f.ty, target, seen))
+                }
+                Some(Item::Enum(e)) => e
+                    .variants
+                    .iter()
+                    .flat_map(|v| &v.fields)
+                    .any(|f| contains(file, &f.ty, target, seen)),
+                None => false,
+            }
+        }
+    }
+}
+
+/// Whether the generated Rust type implements `Default`.
+pub(crate) fn has_default<'a>(file: &'a File, ty: &'a Type, seen: &mut Vec<&'a str>) -> bool {
+    match ty {
+        Type::Primitive(_) | Type::String | Type::Option(_) | Type::Vec(_) | Type::Map(..) => true,
+        // The standard library only implements `Default` for arrays of up to 32 items.
+        Type::Array(item, len) => *len <= 32 && has_default(file, item, seen),
+        Type::Named(name, _) => {
+            if seen.contains(&name.as_str()) {
+                return false;
+            }
+            seen.push(name);
+            let result = match file.item(name) {
+                Some(Item::Struct(s)) => s.fields.iter().all(|f| has_default(file, &f.ty, seen)),
+                Some(Item::Enum(e)) => e.variants.iter().any(|v| v.kind == VariantKind::Unit),
+                None => false,
+            };
+            seen.pop();
+            result
+        }
+    }
+}
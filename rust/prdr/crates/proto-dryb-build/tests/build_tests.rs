@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use proto_dryb::schema::Type;
+use proto_dryb::{Deserialize, Endianness, Schema, Serialize};
+use proto_dryb_build::{compile, generate_rust, parse, BuildError};
+
+mod telemetry {
+    include!("generated/telemetry.rs");
+}
+
+use telemetry::{Config, Kind, Reading, Status};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/telemetry.dryb");
+const GENERATED: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/generated/telemetry.rs");
+
+/// Runs `source` through the parser and returns the error as `line:column: message`.
+fn error(source: &str) -> String {
+    parse(source).unwrap_err().to_string()
+}
+
+#[test]
+fn test_generated_code_is_up_to_date() {
+    let file = parse(&fs::read_to_string(FIXTURE).unwrap()).unwrap();
+    let generated = generate_rust(&file);
+    if std::env::var_os("DRYB_BLESS").is_some_and(|v| v == "1") {
+        fs::write(GENERATED, &generated).unwrap();
+    }
+    assert_eq!(
+        generated,
+        fs::read_to_string(GENERATED).unwrap(),
+        "rerun with DRYB_BLESS=1 to update tests/generated/telemetry.rs"
+    );
+}
+
+#[test]
+fn test_generated_types_roundtrip() {
+    let reading = Reading {
+        sensor: 3,
+        samples: [1.0, 2.5, -1.0, 0.0],
+        status: Status::Fault(7, 0x0102),
+        label: Some("north".to_string()),
+    };
+    let mut buffer = [0u8; 128];
+    let size = reading.serialize(&mut buffer, Endianness::Little).unwrap();
+    assert_eq!(&buffer[..6], &[0, 2, 0, 0, 0, (size - 6) as u8]);
+    assert_eq!(
+        Reading::deserialize(&buffer[..size], Endianness::Little).unwrap(),
+        (reading, size)
+    );
+
+    let config = Config {
+        name: "gw-1".to_string(),
+        r#type: Kind::Gateway,
+        limits: BTreeMap::from([("rate".to_string(), -5)]),
+    };
+    let size = config.serialize(&mut buffer, Endianness::Little).unwrap();
+    assert_eq!(
+        Config::deserialize(&buffer[..size], Endianness::Little).unwrap(),
+        (config, size)
+    );
+}
+
+#[test]
+fn test_explicit_tags_and_keyword_names() {
+    let mut buffer = [0u8; 16];
+    let size = Status::Idle
+        .serialize(&mut buffer, Endianness::Big)
+        .unwrap();
+    assert_eq!(&buffer[..size], &[0x10, 0, 0, 0, 0]);
+    let size = Kind::Gateway
+        .serialize(&mut buffer, Endianness::Big)
+        .unwrap();
+    assert_eq!(&buffer[..size], &[8]);
+    assert_eq!(
+        Status::deserialize(&[0x42, 0, 0, 0, 0], Endianness::Big).unwrap(),
+        (Status::Unknown(0x42), 5)
+    );
+
+    let Type::Struct(config) = Config::schema() else {
+        panic!("Config is a struct");
+    };
+    let names: Vec<_> = config.fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["name", "type", "limits"]);
+}
+
+#[test]
+fn test_syntax_errors_point_at_line_and_column() {
+    assert_eq!(
+        error("struct A {\n    a: u8\n    b: u8,\n}"),
+        "3:5: expected `}`, found `b`"
+    );
+    assert_eq!(
+        error("struct A { a: [u8 4] }"),
+        "1:19: expected `;`, found `4`"
+    );
+    assert_eq!(
+        error("struct A {\n  a: u8 # comment\n}"),
+        "2:9: unexpected character `#`"
+    );
+    assert_eq!(
+        error("@endian(middle) struct A {}"),
+        "1:9: expected `little`, `big` or `native`"
+    );
+    assert_eq!(
+        error("enum E {\n    A = 256,\n}"),
+        "2:9: variant tags must fit in a u8"
+    );
+    assert_eq!(
+        error("@length_prefixed struct A {}"),
+        "1:2: `@length_prefixed` only applies to enums"
+    );
+    assert_eq!(
+        error("type A = u8;"),
+        "1:1: expected `struct` or `enum`, found `type`"
+    );
+}
+
+#[test]
+fn test_validation_errors_point_at_line_and_column() {
+    assert_eq!(error("struct A {\n    b: B,\n}"), "2:8: unknown type `B`");
+    assert_eq!(
+        error("enum E {\n    A = 1,\n    B = 1,\n}"),
+        "3:5: tag 1 is already used by `A`"
+    );
+    assert_eq!(
+        error("enum E { A = 255, B }"),
+        "1:19: the tag of `B` overflows a u8, give it one with `=`"
+    );
+    assert_eq!(
+        error("@version(1)\nstruct A {\n    @since(2) a: u8,\n}"),
+        "3:15: `@since(2)` is newer than the struct version 1"
+    );
+    assert_eq!(
+        error("@tagged\nstruct A {\n    @id(1) a: u8,\n    @id(1) b: u8,\n}"),
+        "4:12: `@id(1)` is already used by `a`"
+    );
+    assert_eq!(
+        error("@tagged\nstruct A {\n    @id(1) e: E,\n}\nenum E { X(u8) }"),
+        "3:12: field `e` of a `@tagged` struct needs a type with a default value"
+    );
+    assert_eq!(
+        error("struct A { m: map<f32, u8> }"),
+        "1:12: map keys must be integers, bool or string"
+    );
+    assert_eq!(
+        error("struct A { b: option<B> }\nstruct B { a: A }"),
+        "1:12: `A` contains itself without a `vec` or `map` in between"
+    );
+    assert_eq!(
+        error("enum E { @other X(u16) }"),
+        "1:17: `@other` variant must be a unit variant or hold a single u8 tag"
+    );
+    assert_eq!(
+        error("struct A {}\nenum A { X }"),
+        "2:6: `A` is already defined at 1:8"
+    );
+}
+
+#[test]
+fn test_compile_writes_output() {
+    let dir = std::env::temp_dir().join(format!("dryb-build-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let output = dir.join("telemetry.rs");
+    compile(FIXTURE, &output).unwrap();
+    assert_eq!(
+        fs::read_to_string(&output).unwrap(),
+        fs::read_to_string(GENERATED).unwrap()
+    );
+
+    let input = dir.join("broken.dryb");
+    fs::write(&input, "struct A {\n    a: u9,\n}\n").unwrap();
+    let err = compile(&input, dir.join("broken.rs")).unwrap_err();
+    assert!(matches!(err, BuildError::Schema { .. }));
+    assert_eq!(
+        err.to_string(),
+        format!("{}:2:8: unknown type `u9`", input.display())
+    );
+    assert!(matches!(
+        compile(Path::new("/nonexistent.dryb"), &output),
+        Err(BuildError::Io { .. })
+    ));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
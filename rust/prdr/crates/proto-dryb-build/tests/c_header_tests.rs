@@ -0,0 +1,177 @@
+use std::fs;
+use std::process;
+
+use proto_dryb::{Deserialize, DeserializeError, Endianness, Schema, Serialize, SerializeError};
+use proto_dryb_build::generate_c_header;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Mode {
+    Off,
+    #[dryb(tag = 0x20)]
+    On,
+    #[dryb(other)]
+    Unknown(u8),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(length_prefixed)]
+enum Command {
+    Move(i16, i16),
+    Turn { degrees: i32 },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Point {
+    x: i64,
+    y: f64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(endian = "big", version = 3)]
+struct Frame {
+    id: u32,
+    mode: Mode,
+    command: Command,
+    position: [f32; 3],
+    #[dryb(endian = "little")]
+    crc: u16,
+    flags: [u8; 2],
+    origin: Point,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Batch {
+    frames: Vec<Frame>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Reply {
+    Ack,
+    Error(u16),
+}
+
+fn frame() -> Frame {
+    Frame {
+        id: 0x01020304,
+        mode: Mode::On,
+        command: Command::Turn { degrees: -90 },
+        position: [1.5, -2.0, 0.25],
+        crc: 0xbeef,
+        flags: [1, 2],
+        origin: Point { x: -7, y: 3.5 },
+    }
+}
+
+#[test]
+fn test_header_declarations() {
+    let header = generate_c_header("robot", Endianness::Little, &[Frame::schema()]).unwrap();
+    let mut buffer = [0u8; 128];
+    let size = frame().serialize(&mut buffer, Endianness::Little).unwrap();
+
+    assert!(header.starts_with("/* Generated by proto-dryb-build. Do not edit. */"));
+    assert!(header.contains("#ifndef ROBOT_H\n#define ROBOT_H\n"));
+    assert!(header.contains("    MODE_OFF = 0,\n    MODE_ON = 32,\n};\ntypedef uint8_t Mode;"));
+    assert!(header.contains(
+        "typedef struct Command {\n    uint8_t tag;\n    uint32_t length;\n    union {\n        struct {\n            int16_t _0;\n            int16_t _1;\n        } move;\n        struct {\n            int32_t degrees;\n        } turn;\n    } body;\n} Command;"
+    ));
+    assert!(header.contains(
+        "typedef struct Frame {\n    uint16_t dryb_version;\n    uint32_t dryb_length;\n    uint32_t id;\n    Mode mode;\n    Command command;\n    float position[3];\n    uint16_t crc;\n    uint8_t flags[2];\n    Point origin;\n} Frame;"
+    ));
+    assert!(header.contains(&format!(
+        "#define FRAME_SIZE {0}\n_Static_assert(sizeof(Frame) == {0}, \"Frame must match its dryb encoding\");\n#define FRAME_VERSION 3\n",
+        size
+    )));
+    assert!(header.contains("    m->crc = dryb_swap_u16(m->crc, 0);\n"));
+    assert!(header.contains("    Point_swap(&m->origin, big_endian);\n"));
+    assert!(header.contains("static inline void Frame_to_host(Frame *m) { Frame_swap(m, 0); }"));
+
+    // Dependencies are declared before the types that use them.
+    let point = header.find("typedef struct Point").unwrap();
+    assert!(point < header.find("typedef struct Frame").unwrap());
+}
+
+#[test]
+fn test_variable_size_types_are_rejected() {
+    let err = generate_c_header("batch", Endianness::Big, &[Batch::schema()]).unwrap_err();
+    assert_eq!(err.to_string(), "Batch.frames: a vec has no fixed size");
+
+    let err = generate_c_header("reply", Endianness::Big, &[Reply::schema()]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Reply: variants encode to different sizes (Ack is 0 bytes, Error is 2 bytes)"
+    );
+
+    let err = generate_c_header("u8", Endianness::Big, &[u8::schema()]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "u8: only structs and enums get a C definition"
+    );
+}
+
+const READER: &str = r#"
+#include <stdio.h>
+#include "robot.h"
+
+int main(int argc, char **argv) {
+    unsigned char bytes[FRAME_SIZE];
+    FILE *f = fopen(argv[1], "rb");
+    if (argc < 2 || !f || fread(bytes, 1, sizeof bytes, f) != sizeof bytes) return 1;
+    Frame frame;
+    memcpy(&frame, bytes, sizeof frame);
+    Frame_to_host(&frame);
+    printf("%u %u %u %u %d %g %g %g %x %u %u %lld %g\n",
+        (unsigned)frame.dryb_version, (unsigned)frame.id, (unsigned)frame.mode,
+        (unsigned)frame.command.tag, (int)frame.command.body.turn.degrees,
+        frame.position[0], frame.position[1], frame.position[2], (unsigned)frame.crc,
+        (unsigned)frame.flags[0], (unsigned)frame.flags[1], (long long)frame.origin.x,
+        frame.origin.y);
+    return 0;
+}
+"#;
+
+/// Compiles a C reader against the header and checks it decodes bytes written by Rust.
+/// Skipped when no C compiler is installed.
+#[test]
+fn test_c_reader_agrees_with_rust() {
+    if process::Command::new("cc")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        return;
+    }
+
+    let dir = std::env::temp_dir().join(format!("dryb-c-header-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let header = generate_c_header("robot", Endianness::Little, &[Frame::schema()]).unwrap();
+    fs::write(dir.join("robot.h"), header).unwrap();
+    fs::write(dir.join("reader.c"), READER).unwrap();
+
+    let mut buffer = [0u8; 128];
+    let size = frame().serialize(&mut buffer, Endianness::Little).unwrap();
+    fs::write(dir.join("frame.bin"), &buffer[..size]).unwrap();
+
+    let build = process::Command::new("cc")
+        .args(["-std=c11", "-Wall", "-Werror", "-o"])
+        .arg(dir.join("reader"))
+        .arg(dir.join("reader.c"))
+        .output()
+        .unwrap();
+    assert!(
+        build.status.success(),
+        "{}",
+        String::from_utf8_lossy(&build.stderr)
+    );
+
+    let run = process::Command::new(dir.join("reader"))
+        .arg(dir.join("frame.bin"))
+        .output()
+        .unwrap();
+    assert!(run.status.success());
+    assert_eq!(
+        String::from_utf8(run.stdout).unwrap(),
+        "3 16909060 32 1 -90 1.5 -2 0.25 beef 1 2 -7 3.5\n"
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
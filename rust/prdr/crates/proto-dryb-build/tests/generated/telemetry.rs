@@ -0,0 +1,48 @@
+// Generated by proto-dryb-build. Do not edit.
+
+use proto_dryb::{Deserialize, DeserializeError, Endianness, Serialize, SerializeError};
+
+/// Sent by every unit once per second.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[dryb(endian = "big", version = 2)]
+pub struct Reading {
+    pub sensor: u16,
+    pub samples: [f32; 4],
+    pub status: Status,
+    /// Added with firmware 2.0.
+    #[dryb(since = 2)]
+    pub label: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[dryb(length_prefixed)]
+pub enum Status {
+    #[default]
+    #[dryb(tag = 16)]
+    Idle,
+    Busy {
+        jobs: Vec<u32>,
+    },
+    Fault(u16, #[dryb(endian = "little")] u32),
+    #[dryb(other)]
+    Unknown(u8),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[dryb(tagged)]
+pub struct Config {
+    #[dryb(id = 1)]
+    pub name: String,
+    #[dryb(id = 2)]
+    pub r#type: Kind,
+    #[dryb(id = 4)]
+    pub limits: ::std::collections::BTreeMap<String, i64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Kind {
+    #[default]
+    Sensor,
+    #[dryb(tag = 8)]
+    Gateway,
+}
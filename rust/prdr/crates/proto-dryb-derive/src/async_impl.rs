@@ -0,0 +1,298 @@
+//! `AsyncSerialize` and `AsyncDeserialize` impls, emitted next to the sync ones for types
+//! marked `#[dryb(async)]`. Positional structs and plain enums are streamed field by field;
+//! encodings with a length up front go through the sync impl and a buffer.
+//!
+//! The futures are boxed, since a type that contains itself through a `Vec` or `Box` would
+//! otherwise have a future of infinite size.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{DataEnum, Field, Fields, Ident};
+
+use crate::attr::{ContainerAttrs, FieldAttrs, OtherVariant};
+use crate::binding;
+
+fn serialize_impl(name: &Ident, attrs: &ContainerAttrs, body: TokenStream) -> TokenStream {
+    let (endian_param, endian_init) = attrs.endian_binding();
+    quote! {
+        impl ::proto_dryb::async_io::AsyncSerialize for #name {
+            fn serialize_async<'a, W>(
+                &'a self,
+                writer: &'a mut W,
+                #endian_param: Endianness,
+            ) -> impl ::core::future::Future<Output = ::std::io::Result<usize>> + Send + 'a
+            where
+                W: ::proto_dryb::async_io::AsyncWrite + Unpin + Send + ?Sized,
+            {
+                ::std::boxed::Box::pin(async move {
+                    #endian_init
+                    #body
+                })
+            }
+        }
+    }
+}
+
+fn deserialize_impl(name: &Ident, attrs: &ContainerAttrs, body: TokenStream) -> TokenStream {
+    let (endian_param, endian_init) = attrs.endian_binding();
+    quote! {
+        impl ::proto_dryb::async_io::AsyncDeserialize for #name {
+            fn deserialize_async<'a, R>(
+                reader: &'a mut R,
+                #endian_param: Endianness,
+            ) -> impl ::core::future::Future<Output = ::std::io::Result<(Self, usize)>> + Send + 'a
+            where
+                R: ::proto_dryb::async_io::AsyncRead + Unpin + Send + ?Sized,
+            {
+                ::std::boxed::Box::pin(async move {
+                    #endian_init
+                    #body
+                })
+            }
+        }
+    }
+}
+
+fn write_value(value: TokenStream, endian: TokenStream) -> TokenStream {
+    quote! {
+        offset += ::proto_dryb::async_io::AsyncSerialize::serialize_async(#value, writer, #endian).await?;
+    }
+}
+
+fn read_value(binding: &Ident, ty: &syn::Type, endian: TokenStream) -> TokenStream {
+    quote! {
+        let (#binding, size) =
+            <#ty as ::proto_dryb::async_io::AsyncDeserialize>::deserialize_async(reader, #endian).await?;
+        offset += size;
+    }
+}
+
+pub(crate) fn serialize_struct(
+    name: &Ident,
+    attrs: &ContainerAttrs,
+    fields: &[(&Field, FieldAttrs)],
+) -> TokenStream {
+    if attrs.tagged || attrs.version.is_some() {
+        return serialize_impl(
+            name,
+            attrs,
+            quote! { ::proto_dryb::async_io::write_buffered(self, writer, endian).await },
+        );
+    }
+
+    let writes = fields.iter().map(|(f, field_attrs)| {
+        let field_name = &f.ident;
+        write_value(quote! { &self.#field_name }, field_attrs.endian())
+    });
+    serialize_impl(
+        name,
+        attrs,
+        quote! {
+            #[allow(unused_mut)]
+            let mut offset = 0;
+            #(#writes)*
+            Ok(offset)
+        },
+    )
+}
+
+pub(crate) fn deserialize_struct(
+    name: &Ident,
+    attrs: &ContainerAttrs,
+    fields: &[(&Field, FieldAttrs)],
+) -> TokenStream {
+    let header = match attrs.version {
+        _ if attrs.tagged => Some(4usize),
+        Some(_) => Some(6),
+        None => None,
+    };
+    if let Some(header) = header {
+        return deserialize_impl(
+            name,
+            attrs,
+            quote! { ::proto_dryb::async_io::read_buffered(reader, #header, endian).await },
+        );
+    }
+
+    let reads = fields.iter().map(|(f, field_attrs)| {
+        read_value(
+            &binding(f.ident.as_ref().unwrap()),
+            &f.ty,
+            field_attrs.endian(),
+        )
+    });
+    let field_names = fields.iter().map(|(f, _)| {
+        let field_name = f.ident.as_ref().unwrap();
+        let field_binding = binding(field_name);
+        quote! { #field_name: #field_binding }
+    });
+    deserialize_impl(
+        name,
+        attrs,
+        quote! {
+            #[allow(unused_mut)]
+            let mut offset = 0;
+            #(#reads)*
+            Ok((Self { #(#field_names),* }, offset))
+        },
+    )
+}
+
+/// Bindings and endianness of a variant's fields, in declaration order.
+fn variant_fields(fields: &Fields) -> syn::Result<Vec<(Ident, &Field, TokenStream)>> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let field_binding = match &field.ident {
+                Some(field_name) => binding(field_name),
+                None => format_ident!("field{}", i),
+            };
+            Ok((
+                field_binding,
+                field,
+                FieldAttrs::parse(&field.attrs)?.endian(),
+            ))
+        })
+        .collect()
+}
+
+fn variant_pattern(name: &Ident, variant: &syn::Variant, bindings: &[&Ident]) -> TokenStream {
+    let variant_name = &variant.ident;
+    match &variant.fields {
+        Fields::Named(fields) => {
+            let field_names = fields.named.iter().map(|f| &f.ident);
+            quote! { #name::#variant_name { #(#field_names: #bindings),* } }
+        }
+        Fields::Unnamed(_) => quote! { #name::#variant_name(#(#bindings),*) },
+        Fields::Unit => quote! { #name::#variant_name },
+    }
+}
+
+pub(crate) fn serialize_enum(
+    name: &Ident,
+    attrs: &ContainerAttrs,
+    e: &DataEnum,
+    tags: &[u8],
+    other: &Option<OtherVariant>,
+) -> syn::Result<TokenStream> {
+    if attrs.length_prefixed {
+        return Ok(serialize_impl(
+            name,
+            attrs,
+            quote! { ::proto_dryb::async_io::write_buffered(self, writer, endian).await },
+        ));
+    }
+
+    let known_tags = OtherVariant::known_tags(other, e, tags);
+    let arms = e
+        .variants
+        .iter()
+        .zip(tags)
+        .map(|(variant, tag)| {
+            let variant_name = &variant.ident;
+            if other.as_ref().is_some_and(|o| o.is_tag(variant_name)) {
+                return Ok(quote! {
+                    #name::#variant_name(tag) => {
+                        if [#(#known_tags),*].contains(tag) {
+                            return Err(::std::io::Error::new(
+                                ::std::io::ErrorKind::InvalidInput,
+                                SerializeError::KnownTag(*tag),
+                            ));
+                        }
+                        ::proto_dryb::async_io::AsyncSerialize::serialize_async(tag, writer, endian).await
+                    }
+                });
+            }
+
+            let fields = variant_fields(&variant.fields)?;
+            let bindings = fields.iter().map(|(b, _, _)| b).collect::<Vec<_>>();
+            let pattern = variant_pattern(name, variant, &bindings);
+            let writes = fields
+                .iter()
+                .map(|(field_binding, _, endian)| write_value(quote! { #field_binding }, endian.clone()));
+            Ok(quote! {
+                #pattern => {
+                    let mut offset = 0;
+                    offset += ::proto_dryb::async_io::AsyncSerialize::serialize_async(&#tag, writer, endian).await?;
+                    #(#writes)*
+                    Ok(offset)
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(serialize_impl(
+        name,
+        attrs,
+        quote! {
+            match self {
+                #(#arms)*
+            }
+        },
+    ))
+}
+
+pub(crate) fn deserialize_enum(
+    name: &Ident,
+    attrs: &ContainerAttrs,
+    e: &DataEnum,
+    tags: &[u8],
+    other: &Option<OtherVariant>,
+) -> syn::Result<TokenStream> {
+    if attrs.length_prefixed {
+        return Ok(deserialize_impl(
+            name,
+            attrs,
+            quote! { ::proto_dryb::async_io::read_buffered(reader, 5, endian).await },
+        ));
+    }
+
+    let arms = e
+        .variants
+        .iter()
+        .zip(tags)
+        .filter(|(variant, _)| !other.as_ref().is_some_and(|o| o.is_tag(&variant.ident)))
+        .map(|(variant, tag)| {
+            let fields = variant_fields(&variant.fields)?;
+            let bindings = fields.iter().map(|(b, _, _)| b).collect::<Vec<_>>();
+            let pattern = variant_pattern(name, variant, &bindings);
+            let reads = fields.iter().map(|(field_binding, field, endian)| {
+                read_value(field_binding, &field.ty, endian.clone())
+            });
+            Ok(quote! {
+                #tag => {
+                    #(#reads)*
+                    #pattern
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let unknown_arm = match other {
+        Some(OtherVariant::Unit(variant_name)) => quote! { _ => #name::#variant_name },
+        Some(OtherVariant::Tag(variant_name)) => quote! { tag => #name::#variant_name(tag) },
+        None => quote! {
+            _ => {
+                return Err(::std::io::Error::new(
+                    ::std::io::ErrorKind::InvalidData,
+                    DeserializeError::Invalid,
+                ))
+            }
+        },
+    };
+
+    Ok(deserialize_impl(
+        name,
+        attrs,
+        quote! {
+            #[allow(unused_mut)]
+            let (tag, mut offset) =
+                <u8 as ::proto_dryb::async_io::AsyncDeserialize>::deserialize_async(reader, endian).await?;
+            let value = match tag {
+                #(#arms,)*
+                #unknown_arm,
+            };
+            Ok((value, offset))
+        },
+    ))
+}
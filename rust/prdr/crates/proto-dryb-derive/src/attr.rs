@@ -0,0 +1,365 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{Attribute, DataEnum, Field, Fields, Ident, LitInt, LitStr};
+
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    pub endian: Option<Endian>,
+    pub length_prefixed: bool,
+    pub version: Option<u16>,
+    pub tagged: bool,
+    /// Also generate a `<Name>View<'a>` that decodes fields on access.
+    pub view: bool,
+    /// Also implement `AsyncSerialize` and `AsyncDeserialize`, which the field types then need
+    /// as well.
+    pub async_io: bool,
+    /// Leave `Schema` out of what `Serialize` implements, for types whose fields don't have one.
+    pub no_schema: bool,
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = ContainerAttrs::default();
+
+        for attr in attrs.iter().filter(|a| a.path().is_ident("dryb")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("endian") {
+                    result.endian = Some(parse_endian(&meta.value()?.parse()?)?);
+                    Ok(())
+                } else if meta.path.is_ident("length_prefixed") {
+                    result.length_prefixed = true;
+                    Ok(())
+                } else if meta.path.is_ident("version") {
+                    result.version = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("tagged") {
+                    result.tagged = true;
+                    Ok(())
+                } else if meta.path.is_ident("view") {
+                    result.view = true;
+                    Ok(())
+                } else if meta.path.is_ident("async") {
+                    result.async_io = true;
+                    Ok(())
+                } else if meta.path.is_ident("no_schema") {
+                    result.no_schema = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown dryb container attribute"))
+                }
+            })?;
+        }
+
+        Ok(result)
+    }
+
+    pub fn check_struct(&self, name: &Ident) -> syn::Result<()> {
+        if self.length_prefixed {
+            return Err(syn::Error::new(
+                name.span(),
+                "#[dryb(length_prefixed)] only applies to enums",
+            ));
+        }
+        if self.tagged && self.version.is_some() {
+            return Err(syn::Error::new(
+                name.span(),
+                "#[dryb(tagged)] structs cannot also be versioned",
+            ));
+        }
+        if self.view && (self.tagged || self.version.is_some()) {
+            return Err(syn::Error::new(
+                name.span(),
+                "#[dryb(view)] does not support tagged or versioned structs",
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn check_enum(&self, name: &Ident) -> syn::Result<()> {
+        if self.version.is_some() || self.tagged || self.view {
+            return Err(syn::Error::new(
+                name.span(),
+                "#[dryb(version)], #[dryb(tagged)] and #[dryb(view)] only apply to structs",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `since` fields are appended in version order and not newer than the struct,
+    /// and that tagged structs number every field uniquely.
+    pub fn check_fields(&self, fields: &[(&Field, FieldAttrs)]) -> syn::Result<()> {
+        let mut previous = 0;
+        let mut ids = Vec::new();
+
+        for (field, attrs) in fields {
+            match attrs.id {
+                Some(_) if !self.tagged => {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "#[dryb(id)] requires a #[dryb(tagged)] struct",
+                    ))
+                }
+                None if self.tagged => {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "fields of a #[dryb(tagged)] struct need a #[dryb(id)]",
+                    ))
+                }
+                Some(id) if ids.contains(&id) => {
+                    return Err(syn::Error::new_spanned(field, "duplicate #[dryb(id)]"))
+                }
+                Some(id) => ids.push(id),
+                None => {}
+            }
+
+            let Some(since) = attrs.since else {
+                if previous > 0 {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "fields without #[dryb(since)] must come before versioned fields",
+                    ));
+                }
+                continue;
+            };
+
+            match self.version {
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "#[dryb(since)] requires a #[dryb(version)] on the struct",
+                    ))
+                }
+                Some(version) if since > version => {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "#[dryb(since)] is newer than the struct version",
+                    ))
+                }
+                _ if since < previous => {
+                    return Err(syn::Error::new_spanned(
+                        field,
+                        "versioned fields must be declared in ascending #[dryb(since)] order",
+                    ))
+                }
+                _ => previous = since,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The parameter name and prologue used to bind `endian` in generated functions.
+    pub fn endian_binding(&self) -> (TokenStream, TokenStream) {
+        match &self.endian {
+            Some(endian) => (quote! { _ }, quote! { let endian = #endian; }),
+            None => (quote! { endian }, TokenStream::new()),
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct VariantAttrs {
+    pub other: bool,
+    pub tag: Option<u8>,
+}
+
+impl VariantAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = VariantAttrs::default();
+
+        for attr in attrs.iter().filter(|a| a.path().is_ident("dryb")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("other") {
+                    result.other = true;
+                    Ok(())
+                } else if meta.path.is_ident("tag") {
+                    result.tag = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown dryb variant attribute"))
+                }
+            })?;
+        }
+
+        Ok(result)
+    }
+}
+
+/// The wire tag of each variant: its `#[dryb(tag)]`, or one more than the previous tag.
+pub(crate) fn variant_tags(e: &DataEnum) -> syn::Result<Vec<u8>> {
+    let mut tags: Vec<u8> = Vec::with_capacity(e.variants.len());
+
+    for variant in &e.variants {
+        let tag = match (VariantAttrs::parse(&variant.attrs)?.tag, tags.last()) {
+            (Some(tag), _) => tag,
+            (None, None) => 0,
+            (None, Some(previous)) => previous.checked_add(1).ok_or_else(|| {
+                syn::Error::new_spanned(variant, "variant tag overflows u8, give it a #[dryb(tag)]")
+            })?,
+        };
+        if tags.contains(&tag) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                format!("variant tag {} is already used", tag),
+            ));
+        }
+        tags.push(tag);
+    }
+
+    Ok(tags)
+}
+
+/// The variant marked `#[dryb(other)]`, which unknown tags decode into.
+pub(crate) enum OtherVariant<'a> {
+    Unit(&'a Ident),
+    Tag(&'a Ident),
+}
+
+impl<'a> OtherVariant<'a> {
+    pub fn find(e: &'a DataEnum) -> syn::Result<Option<Self>> {
+        let mut result = None;
+
+        for variant in &e.variants {
+            if !VariantAttrs::parse(&variant.attrs)?.other {
+                continue;
+            }
+            if result.is_some() {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "only one variant can be marked #[dryb(other)]",
+                ));
+            }
+
+            result = Some(match &variant.fields {
+                Fields::Unit => OtherVariant::Unit(&variant.ident),
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    let field = &fields.unnamed[0];
+                    if !matches!(&field.ty, syn::Type::Path(ty) if ty.qself.is_none() && ty.path.is_ident("u8"))
+                    {
+                        return Err(syn::Error::new_spanned(
+                            &field.ty,
+                            "#[dryb(other)] variant must hold its tag as a u8",
+                        ));
+                    }
+                    OtherVariant::Tag(&variant.ident)
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        "#[dryb(other)] variant must be a unit variant or hold a single u8 tag",
+                    ))
+                }
+            });
+        }
+
+        Ok(result)
+    }
+
+    pub fn is_tag(&self, ident: &Ident) -> bool {
+        matches!(self, OtherVariant::Tag(other) if *other == ident)
+    }
+
+    /// The tags of the variants other than a tag-holding catch-all, which that variant is not
+    /// allowed to serialize since they would decode as the known variant.
+    pub fn known_tags(other: &Option<Self>, e: &DataEnum, tags: &[u8]) -> Vec<u8> {
+        e.variants
+            .iter()
+            .zip(tags)
+            .filter(|(variant, _)| !other.as_ref().is_some_and(|o| o.is_tag(&variant.ident)))
+            .map(|(_, tag)| *tag)
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    pub endian: Option<Endian>,
+    pub since: Option<u16>,
+    pub id: Option<u16>,
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = FieldAttrs::default();
+
+        for attr in attrs.iter().filter(|a| a.path().is_ident("dryb")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("endian") {
+                    result.endian = Some(parse_endian(&meta.value()?.parse()?)?);
+                    Ok(())
+                } else if meta.path.is_ident("since") {
+                    result.since = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("id") {
+                    result.id = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown dryb field attribute"))
+                }
+            })?;
+        }
+
+        Ok(result)
+    }
+
+    /// Expression for the endianness this field is encoded with.
+    pub fn endian(&self) -> TokenStream {
+        match &self.endian {
+            Some(endian) => quote! { #endian },
+            None => quote! { endian },
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Endian {
+    Little,
+    Big,
+    Native,
+}
+
+impl Endian {
+    /// Fully qualified path of the variant, for code that does not import `Endianness`.
+    pub fn path(self) -> TokenStream {
+        let variant = self.variant();
+        quote! { ::proto_dryb::Endianness::#variant }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Endian::Little => "little",
+            Endian::Big => "big",
+            Endian::Native => "native",
+        }
+    }
+
+    fn variant(self) -> Ident {
+        let name = match self {
+            Endian::Little => "Little",
+            Endian::Big => "Big",
+            Endian::Native => "Native",
+        };
+        Ident::new(name, proc_macro2::Span::call_site())
+    }
+}
+
+impl ToTokens for Endian {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let variant = self.variant();
+        tokens.extend(quote! { Endianness::#variant });
+    }
+}
+
+fn parse_endian(lit: &LitStr) -> syn::Result<Endian> {
+    match lit.value().as_str() {
+        "little" => Ok(Endian::Little),
+        "big" => Ok(Endian::Big),
+        "native" => Ok(Endian::Native),
+        _ => Err(syn::Error::new(
+            lit.span(),
+            "expected \"little\", \"big\" or \"native\"",
+        )),
+    }
+}
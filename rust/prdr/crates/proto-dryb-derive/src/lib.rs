@@ -1,168 +1,500 @@
 extern crate proc_macro;
 
+mod async_impl;
+mod attr;
+mod schema;
+mod view;
+
+use attr::{variant_tags, ContainerAttrs, FieldAttrs, OtherVariant};
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
-use syn::{parse_macro_input, DeriveInput, Fields};
+use syn::{ext::IdentExt, parse_macro_input, punctuated::Punctuated, DeriveInput, Fields, Token};
 
-#[proc_macro_derive(Serialize)]
+/// Implements `Serialize`, and `Schema` too unless the type is marked `#[dryb(no_schema)]`.
+#[proc_macro_derive(Serialize, attributes(dryb))]
 pub fn derive_serialize(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     let name = &ast.ident;
 
-    let expanded = match ast.data {
-        syn::Data::Struct(s) => impl_serialize_struct(name, s),
-        syn::Data::Enum(e) => impl_serialize_enum(name, e),
-        _ => panic!("Serialize only works with structs and enums"),
-    };
+    let expanded = ContainerAttrs::parse(&ast.attrs).and_then(|attrs| {
+        let schema = match attrs.no_schema {
+            true => proc_macro2::TokenStream::new(),
+            false => impl_schema(name, &attrs, ast.data.clone())?,
+        };
+        let serialize = match ast.data {
+            syn::Data::Struct(s) => impl_serialize_struct(name, &attrs, s)?,
+            syn::Data::Enum(e) => impl_serialize_enum(name, &attrs, e)?,
+            _ => panic!("Serialize only works with structs and enums"),
+        };
+        Ok(quote! {
+            #serialize
+            #schema
+        })
+    });
 
-    TokenStream::from(expanded)
+    TokenStream::from(expanded.unwrap_or_else(syn::Error::into_compile_error))
 }
 
-fn impl_serialize_struct(name: &syn::Ident, s: syn::DataStruct) -> proc_macro2::TokenStream {
+fn impl_serialize_struct(
+    name: &syn::Ident,
+    attrs: &ContainerAttrs,
+    s: syn::DataStruct,
+) -> syn::Result<proc_macro2::TokenStream> {
+    attrs.check_struct(name)?;
+
     match s.fields {
         Fields::Named(fields) => {
-            let field_serializations = fields.named.iter().map(|f| {
+            let fields = parse_fields(&fields.named)?;
+            attrs.check_fields(&fields)?;
+
+            let field_serializations = fields.iter().map(|(f, field_attrs)| {
                 let field_name = &f.ident;
-                quote! {
-                    offset += self.#field_name.serialize(&mut buffer[offset..], endian)?;
+                let field_endian = field_attrs.endian();
+                match field_attrs.id {
+                    Some(id) => quote! {
+                        if buffer.len() < offset + FIELD_HEADER_SIZE {
+                            return Err(SerializeError::BufferOverflow);
+                        }
+                        #id.serialize(&mut buffer[offset..], endian)?;
+                        let start = offset + FIELD_HEADER_SIZE;
+                        let size = self.#field_name.serialize(&mut buffer[start..], #field_endian)?;
+                        (size as u32).serialize(&mut buffer[offset + 2..], endian)?;
+                        offset = start + size;
+                    },
+                    None => quote! {
+                        offset += self.#field_name.serialize(&mut buffer[offset..], #field_endian)?;
+                    },
                 }
             });
-
-            quote! {
+            let (endian_param, endian_init) = attrs.endian_binding();
+            let (body_start, write_header) = match attrs.version {
+                _ if attrs.tagged => (
+                    quote! {
+                        const LENGTH_SIZE: usize = 4;
+                        const FIELD_HEADER_SIZE: usize = 6;
+                        if buffer.len() < LENGTH_SIZE {
+                            return Err(SerializeError::BufferOverflow);
+                        }
+                        let mut offset = LENGTH_SIZE;
+                    },
+                    quote! {
+                        ((offset - LENGTH_SIZE) as u32).serialize(&mut buffer[..], endian)?;
+                    },
+                ),
+                Some(version) => (
+                    quote! {
+                        const VERSION_HEADER_SIZE: usize = 6;
+                        if buffer.len() < VERSION_HEADER_SIZE {
+                            return Err(SerializeError::BufferOverflow);
+                        }
+                        let mut offset = VERSION_HEADER_SIZE;
+                    },
+                    quote! {
+                        #version.serialize(&mut buffer[..], endian)?;
+                        ((offset - VERSION_HEADER_SIZE) as u32).serialize(&mut buffer[2..], endian)?;
+                    },
+                ),
+                None => (quote! { let mut offset = 0; }, quote! {}),
+            };
+
+            let async_impl = attrs
+                .async_io
+                .then(|| async_impl::serialize_struct(name, attrs, &fields));
+
+            Ok(quote! {
                 impl Serialize for #name {
-                    fn serialize(&self, buffer: &mut [u8], endian: Endianness) -> Result<usize, SerializeError> {
-                        let mut offset = 0;
+                    fn serialize(&self, buffer: &mut [u8], #endian_param: Endianness) -> Result<usize, SerializeError> {
+                        #endian_init
+                        #body_start
                         #(#field_serializations)*
+                        #write_header
                         Ok(offset)
                     }
                 }
-            }
+
+                #async_impl
+            })
         }
         _ => panic!("Serialize only works with structs that have named fields"),
     }
 }
 
-fn impl_serialize_enum(name: &syn::Ident, e: syn::DataEnum) -> proc_macro2::TokenStream {
-    let variant_arms = e.variants.iter().enumerate().map(|(index, variant)| {
-        let variant_name = &variant.ident;
-        let (enum_fields, variant_pattern) = match &variant.fields {
-            Fields::Named(fields) => {
-                let field_names = fields
-                    .named
-                    .iter()
-                    .map(|i| i.ident.as_ref().unwrap())
-                    .collect::<Vec<_>>();
-
-                let field_calculations = fields
-                    .named
-                    .iter()
-                    .map(|field| {
-                        let field_name = &field.ident;
-                        quote! {
-                            offset += #field_name.serialize(&mut buf[offset..], endian)?;
+fn impl_serialize_enum(
+    name: &syn::Ident,
+    attrs: &ContainerAttrs,
+    e: syn::DataEnum,
+) -> syn::Result<proc_macro2::TokenStream> {
+    attrs.check_enum(name)?;
+    let other = OtherVariant::find(&e)?;
+    let tags = variant_tags(&e)?;
+    let known_tags = OtherVariant::known_tags(&other, &e, &tags);
+    let variant_arms = e
+        .variants
+        .iter()
+        .zip(&tags)
+        .map(|(variant, tag)| {
+            let variant_name = &variant.ident;
+            if other.as_ref().is_some_and(|o| o.is_tag(variant_name)) {
+                return Ok(quote! {
+                    #name::#variant_name(tag) => {
+                        if [#(#known_tags),*].contains(tag) {
+                            return Err(SerializeError::KnownTag(*tag));
                         }
-                    })
-                    .collect::<Vec<_>>();
-
-                let pattern = quote! { #name::#variant_name { #(#field_names),* } };
-
-                (field_calculations, pattern)
+                        *tag
+                    }
+                });
             }
-            Fields::Unnamed(fields) => {
-                let field_names: Vec<_> = (0..fields.unnamed.len())
-                    .map(|i| format_ident!("field{}", i))
-                    .collect();
-
-                let field_calculations = field_names
-                    .iter()
-                    .map(|field_name| {
-                        quote! {
-                            offset += #field_name.serialize(&mut buf[offset..], endian)?;
-                        }
-                    })
-                    .collect::<Vec<_>>();
 
-                let pattern = quote! { #name::#variant_name(#(#field_names),*) };
-
-                (field_calculations, pattern)
-            }
-            Fields::Unit => (Vec::default(), quote! { #name::#variant_name }),
-        };
+            let (enum_fields, variant_pattern) = match &variant.fields {
+                Fields::Named(fields) => {
+                    let field_names = fields
+                        .named
+                        .iter()
+                        .map(|i| i.ident.as_ref().unwrap())
+                        .collect::<Vec<_>>();
+
+                    let bindings = field_names.iter().map(|i| binding(i)).collect::<Vec<_>>();
+
+                    let field_calculations = fields
+                        .named
+                        .iter()
+                        .zip(&bindings)
+                        .map(|(field, field_binding)| {
+                            let field_endian = FieldAttrs::parse(&field.attrs)?.endian();
+                            Ok(quote! {
+                                offset += #field_binding.serialize(&mut buf[offset..], #field_endian)?;
+                            })
+                        })
+                        .collect::<syn::Result<Vec<_>>>()?;
+
+                    let pattern = quote! { #name::#variant_name { #(#field_names: #bindings),* } };
+
+                    (field_calculations, pattern)
+                }
+                Fields::Unnamed(fields) => {
+                    let field_names: Vec<_> = (0..fields.unnamed.len())
+                        .map(|i| format_ident!("field{}", i))
+                        .collect();
+
+                    let field_calculations = fields
+                        .unnamed
+                        .iter()
+                        .zip(&field_names)
+                        .map(|(field, field_name)| {
+                            let field_endian = FieldAttrs::parse(&field.attrs)?.endian();
+                            Ok(quote! {
+                                offset += #field_name.serialize(&mut buf[offset..], #field_endian)?;
+                            })
+                        })
+                        .collect::<syn::Result<Vec<_>>>()?;
+
+                    let pattern = quote! { #name::#variant_name(#(#field_names),*) };
+
+                    (field_calculations, pattern)
+                }
+                Fields::Unit => (Vec::default(), quote! { #name::#variant_name }),
+            };
 
-        quote! {
-            #variant_pattern => {
-                #(#enum_fields)*
-                #index as u8
-            }
-        }
-    });
+            Ok(quote! {
+                #variant_pattern => {
+                    #(#enum_fields)*
+                    #tag
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let (endian_param, endian_init) = attrs.endian_binding();
+    let (body_start, write_length) = if attrs.length_prefixed {
+        (
+            quote! { 1 + VARIANT_LENGTH_SIZE },
+            quote! {
+                ((offset - 1 - VARIANT_LENGTH_SIZE) as u32).serialize(&mut buf[1..], endian)?;
+            },
+        )
+    } else {
+        (quote! { 1 }, quote! {})
+    };
+    let async_impl = attrs
+        .async_io
+        .then(|| async_impl::serialize_enum(name, attrs, &e, &tags, &other))
+        .transpose()?;
 
-    quote! {
+    Ok(quote! {
         impl Serialize for #name {
-            fn serialize(&self, buf: &mut [u8], endian: Endianness) -> Result<usize, SerializeError> {
-                if buf.len() < 1 {
+            fn serialize(&self, buf: &mut [u8], #endian_param: Endianness) -> Result<usize, SerializeError> {
+                #endian_init
+                const VARIANT_LENGTH_SIZE: usize = 4;
+                if buf.len() < #body_start {
                     return Err(SerializeError::BufferOverflow);
                 }
 
-                let mut offset = 1;
+                let mut offset = #body_start;
 
                 buf[0] = match self {
                     #(#variant_arms,)*
                 };
+                #write_length
 
                 Ok(offset)
             }
         }
-    }
+
+        #async_impl
+    })
+}
+
+fn parse_fields(
+    fields: &Punctuated<syn::Field, Token![,]>,
+) -> syn::Result<Vec<(&syn::Field, FieldAttrs)>> {
+    fields
+        .iter()
+        .map(|f| Ok((f, FieldAttrs::parse(&f.attrs)?)))
+        .collect()
+}
+
+/// Local variable holding a field's value in generated code, kept apart from the
+/// generated code's own locals.
+fn binding(field_name: &syn::Ident) -> syn::Ident {
+    format_ident!("field_{}", field_name.unraw())
 }
 
-#[proc_macro_derive(Deserialize)]
+#[proc_macro_derive(Deserialize, attributes(dryb))]
 pub fn derive_deserialize(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     let name = &ast.ident;
 
-    let expanded = match ast.data {
-        syn::Data::Struct(s) => impl_deserialize_struct(name, s),
-        syn::Data::Enum(e) => impl_deserialize_enum(name, e),
+    let expanded = ContainerAttrs::parse(&ast.attrs).and_then(|attrs| match ast.data {
+        syn::Data::Struct(s) => impl_deserialize_struct(name, &ast.vis, &attrs, s),
+        syn::Data::Enum(e) => impl_deserialize_enum(name, &attrs, e),
         _ => panic!("Deserialize only works with structs and enums"),
-    };
+    });
 
-    TokenStream::from(expanded)
+    TokenStream::from(expanded.unwrap_or_else(syn::Error::into_compile_error))
 }
 
-fn impl_deserialize_struct(name: &syn::Ident, s: syn::DataStruct) -> proc_macro2::TokenStream {
+fn impl_deserialize_struct(
+    name: &syn::Ident,
+    vis: &syn::Visibility,
+    attrs: &ContainerAttrs,
+    s: syn::DataStruct,
+) -> syn::Result<proc_macro2::TokenStream> {
+    attrs.check_struct(name)?;
+
     match s.fields {
-        Fields::Named(fields) => {
-            let field_deserializations = fields.named.iter().map(|f| {
-                let field_name = &f.ident;
+        Fields::Named(fields) if attrs.tagged => {
+            let fields = parse_fields(&fields.named)?;
+            attrs.check_fields(&fields)?;
+
+            let field_names = fields.iter().map(|(f, _)| &f.ident).collect::<Vec<_>>();
+            let bindings = field_names
+                .iter()
+                .map(|i| binding(i.as_ref().unwrap()))
+                .collect::<Vec<_>>();
+            let field_arms = fields.iter().zip(&bindings).map(|((f, field_attrs), field_binding)| {
                 let field_type = &f.ty;
+                let field_endian = field_attrs.endian();
+                let id = field_attrs.id;
                 quote! {
-                    let (#field_name, size) = #field_type::deserialize(&buf[offset..], endian)?;
-                    offset += size;
+                    #id => {
+                        let (value, _) = <#field_type as Deserialize>::deserialize(&buf[offset..field_end], #field_endian)?;
+                        #field_binding = Some(value);
+                    }
                 }
             });
+            let (endian_param, endian_init) = attrs.endian_binding();
+            let async_impl = attrs
+                .async_io
+                .then(|| async_impl::deserialize_struct(name, attrs, &fields));
 
-            let field_names = fields.named.iter().map(|f| {
-                let field_name = &f.ident;
-                quote! { #field_name }
+            Ok(quote! {
+                impl Deserialize for #name {
+                    fn deserialize(buf: &[u8], #endian_param: Endianness) -> Result<(Self, usize), DeserializeError> {
+                        #endian_init
+                        const LENGTH_SIZE: usize = 4;
+                        const FIELD_HEADER_SIZE: usize = 6;
+                        let (length, _) = u32::deserialize(buf, endian)?;
+                        let end = LENGTH_SIZE + length as usize;
+                        if buf.len() < end {
+                            return Err(DeserializeError::Invalid);
+                        }
+                        let buf = &buf[..end];
+
+                        #(let mut #bindings = None;)*
+                        let mut offset = LENGTH_SIZE;
+                        while offset < end {
+                            let (id, _) = u16::deserialize(&buf[offset..], endian)?;
+                            let (length, _) = u32::deserialize(&buf[offset + 2..], endian)?;
+                            offset += FIELD_HEADER_SIZE;
+                            let field_end = offset + length as usize;
+                            if field_end > end {
+                                return Err(DeserializeError::Invalid);
+                            }
+
+                            match id {
+                                #(#field_arms)*
+                                _ => {}
+                            }
+                            offset = field_end;
+                        }
+
+                        Ok((Self { #(#field_names: #bindings.unwrap_or_default()),* }, end))
+                    }
+
+                    fn skip(buf: &[u8], #endian_param: Endianness) -> Result<usize, DeserializeError> {
+                        #endian_init
+                        ::proto_dryb::skip_length_prefixed(buf, 4, endian)
+                    }
+                }
+
+                #async_impl
+            })
+        }
+        Fields::Named(fields) => {
+            let fields = parse_fields(&fields.named)?;
+            attrs.check_fields(&fields)?;
+
+            let field_deserializations = fields.iter().map(|(f, field_attrs)| {
+                let field_binding = binding(f.ident.as_ref().unwrap());
+                let field_type = &f.ty;
+                let field_endian = field_attrs.endian();
+                let deserialize = quote! {
+                    <#field_type as Deserialize>::deserialize(&buf[offset..], #field_endian)?
+                };
+
+                match field_attrs.since {
+                    Some(since) => quote! {
+                        let (#field_binding, size) = if version >= #since {
+                            #deserialize
+                        } else {
+                            (Default::default(), 0)
+                        };
+                        offset += size;
+                    },
+                    None => quote! {
+                        let (#field_binding, size) = #deserialize;
+                        offset += size;
+                    },
+                }
             });
 
-            quote! {
+            let field_in_place = fields.iter().map(|(f, field_attrs)| {
+                let field_name = &f.ident;
+                let field_type = &f.ty;
+                let field_endian = field_attrs.endian();
+                let deserialize = quote! {
+                    offset += <#field_type as Deserialize>::deserialize_in_place(&mut self.#field_name, &buf[offset..], #field_endian)?;
+                };
+
+                match field_attrs.since {
+                    Some(since) => quote! {
+                        if version >= #since {
+                            #deserialize
+                        } else {
+                            self.#field_name = Default::default();
+                        }
+                    },
+                    None => deserialize,
+                }
+            });
+            let field_names = fields.iter().map(|(f, _)| {
+                let field_name = f.ident.as_ref().unwrap();
+                let field_binding = binding(field_name);
+                quote! { #field_name: #field_binding }
+            });
+            let field_types = fields.iter().map(|(f, _)| &f.ty);
+            let (fixed_size, skip_body) = match attrs.version {
+                Some(_) => (
+                    quote! { None },
+                    quote! { ::proto_dryb::skip_length_prefixed(buf, 6, endian) },
+                ),
+                None => {
+                    let field_skips = fields.iter().map(|(f, field_attrs)| {
+                        let field_type = &f.ty;
+                        let field_endian = field_attrs.endian();
+                        quote! {
+                            offset += <#field_type as Deserialize>::skip(&buf[offset..], #field_endian)?;
+                        }
+                    });
+                    (
+                        quote! {
+                            ::proto_dryb::fixed_size_sum(&[#(<#field_types as Deserialize>::FIXED_SIZE),*])
+                        },
+                        quote! {
+                            #[allow(unused_mut)]
+                            let mut offset = 0;
+                            #(#field_skips)*
+                            Ok(offset)
+                        },
+                    )
+                }
+            };
+            let (endian_param, endian_init) = attrs.endian_binding();
+            let (body_start, body_end) = match attrs.version {
+                Some(_) => (
+                    quote! {
+                        const VERSION_HEADER_SIZE: usize = 6;
+                        let (version, _) = u16::deserialize(buf, endian)?;
+                        let (length, _) = u32::deserialize(&buf[2..], endian)?;
+                        let end = VERSION_HEADER_SIZE + length as usize;
+                        if buf.len() < end {
+                            return Err(DeserializeError::Invalid);
+                        }
+                        let buf = &buf[..end];
+                        let mut offset = VERSION_HEADER_SIZE;
+                    },
+                    quote! { end },
+                ),
+                None => (quote! { let mut offset = 0; }, quote! { offset }),
+            };
+            let async_impl = attrs
+                .async_io
+                .then(|| async_impl::deserialize_struct(name, attrs, &fields));
+            let view = attrs.view.then(|| view::view_struct(name, vis, attrs, &fields));
+
+            Ok(quote! {
                 impl Deserialize for #name {
-                    fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
-                        let mut offset = 0;
+                    const FIXED_SIZE: Option<usize> = #fixed_size;
+
+                    fn deserialize(buf: &[u8], #endian_param: Endianness) -> Result<(Self, usize), DeserializeError> {
+                        #endian_init
+                        #body_start
                         #(#field_deserializations)*
-                        Ok((Self { #(#field_names),* }, offset))
+                        Ok((Self { #(#field_names),* }, #body_end))
+                    }
+
+                    fn deserialize_in_place(&mut self, buf: &[u8], #endian_param: Endianness) -> Result<usize, DeserializeError> {
+                        #endian_init
+                        #body_start
+                        #(#field_in_place)*
+                        Ok(#body_end)
+                    }
+
+                    fn skip(buf: &[u8], #endian_param: Endianness) -> Result<usize, DeserializeError> {
+                        #endian_init
+                        #skip_body
                     }
                 }
-            }
+
+                #async_impl
+
+                #view
+            })
         }
         _ => panic!("Deserialize only works with structs that have named fields"),
     }
 }
 
-fn impl_deserialize_enum(name: &syn::Ident, e: syn::DataEnum) -> proc_macro2::TokenStream {
-    let variant_arms = e.variants.iter().enumerate().map(|(index, variant)| {
+fn impl_deserialize_enum(
+    name: &syn::Ident,
+    attrs: &ContainerAttrs,
+    e: syn::DataEnum,
+) -> syn::Result<proc_macro2::TokenStream> {
+    attrs.check_enum(name)?;
+    let other = OtherVariant::find(&e)?;
+    let tags = variant_tags(&e)?;
+    let variant_arms = e.variants.iter().zip(&tags).filter(|(variant, _)| {
+        !other.as_ref().is_some_and(|o| o.is_tag(&variant.ident))
+    }).map(|(variant, tag)| {
         let variant_name = &variant.ident;
         let (enum_fields, variant_pattern) = match &variant.fields {
             Fields::Named(fields) => {
@@ -170,42 +502,46 @@ fn impl_deserialize_enum(name: &syn::Ident, e: syn::DataEnum) -> proc_macro2::To
                     .map(|i| i.ident.as_ref().unwrap())
                     .collect::<Vec<_>>();
 
+                let bindings = field_names.iter().map(|i| binding(i)).collect::<Vec<_>>();
+
                 let field_calculations = fields
                     .named
                     .iter()
-                    .map(|field| {
-                        let field_name = &field.ident;
+                    .zip(&bindings)
+                    .map(|(field, field_binding)| {
                         let field_type = &field.ty;
+                        let field_endian = FieldAttrs::parse(&field.attrs)?.endian();
 
-                        return quote! {
-                            let (#field_name, size) = #field_type::deserialize(&buf[offset..], endian)?;
+                        Ok(quote! {
+                            let (#field_binding, size) = <#field_type as Deserialize>::deserialize(&buf[offset..], #field_endian)?;
                             offset += size;
-                        };
+                        })
                     })
-                    .collect::<Vec<_>>();
+                    .collect::<syn::Result<Vec<_>>>()?;
 
-                let pattern = quote! { #name::#variant_name { #(#field_names),* } };
+                let pattern = quote! { #name::#variant_name { #(#field_names: #bindings),* } };
 
                 (field_calculations, pattern)
             }
             Fields::Unnamed(fields) => {
-                let count = fields.unnamed.len();
-                let field_names = (0..count)
-                    .map(|_| format_ident!("x"))
+                let field_names = (0..fields.unnamed.len())
+                    .map(|i| format_ident!("field{}", i))
                     .collect::<Vec<_>>();
 
                 let field_calculations = fields
                     .unnamed
                     .iter()
-                    .map(|field| {
+                    .zip(&field_names)
+                    .map(|(field, field_name)| {
                         let field_type = &field.ty;
+                        let field_endian = FieldAttrs::parse(&field.attrs)?.endian();
 
-                        return quote! {
-                            let (x, size) = #field_type::deserialize(&buf[offset..], endian)?;
+                        Ok(quote! {
+                            let (#field_name, size) = <#field_type as Deserialize>::deserialize(&buf[offset..], #field_endian)?;
                             offset += size;
-                        };
+                        })
                     })
-                    .collect::<Vec<_>>();
+                    .collect::<syn::Result<Vec<_>>>()?;
 
                 let pattern = quote! { #name::#variant_name(#(#field_names),*) };
 
@@ -213,30 +549,130 @@ fn impl_deserialize_enum(name: &syn::Ident, e: syn::DataEnum) -> proc_macro2::To
             }
             Fields::Unit => (Vec::default(), quote! { #name::#variant_name }),
         };
-        let index_u8 = index as u8;
-
-        quote! {
-            #index_u8 => {
+        Ok(quote! {
+            #tag => {
                 #(#enum_fields)*
                 #variant_pattern
             }
+        })
+    }).collect::<syn::Result<Vec<_>>>()?;
+    let skip_arms = e
+        .variants
+        .iter()
+        .zip(&tags)
+        .filter(|(variant, _)| !other.as_ref().is_some_and(|o| o.is_tag(&variant.ident)))
+        .map(|(variant, tag)| {
+            let field_skips = variant
+                .fields
+                .iter()
+                .map(|field| {
+                    let field_type = &field.ty;
+                    let field_endian = FieldAttrs::parse(&field.attrs)?.endian();
+                    Ok(quote! {
+                        offset += <#field_type as Deserialize>::skip(&buf[offset..], #field_endian)?;
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            Ok(quote! { #tag => { #(#field_skips)* } })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let skip_body = if attrs.length_prefixed {
+        quote! { ::proto_dryb::skip_length_prefixed(buf, 5, endian) }
+    } else {
+        let unknown_skip = match other {
+            Some(_) => quote! { _ => {} },
+            None => quote! { _ => return Err(DeserializeError::Invalid) },
+        };
+        quote! {
+            let Some(tag) = buf.first() else {
+                return Err(DeserializeError::Invalid);
+            };
+            #[allow(unused_mut)]
+            let mut offset = 1;
+            match tag {
+                #(#skip_arms)*
+                #unknown_skip
+            }
+            Ok(offset)
         }
-    });
+    };
+    let (endian_param, endian_init) = attrs.endian_binding();
+    let async_impl = attrs
+        .async_io
+        .then(|| async_impl::deserialize_enum(name, attrs, &e, &tags, &other))
+        .transpose()?;
+    let unknown_arm = match other {
+        Some(OtherVariant::Unit(variant_name)) => quote! { _ => #name::#variant_name },
+        Some(OtherVariant::Tag(variant_name)) => quote! { tag => #name::#variant_name(tag) },
+        None => quote! { _ => return Err(DeserializeError::Invalid) },
+    };
+    let (body_start, body_end) = if attrs.length_prefixed {
+        (
+            quote! {
+                const VARIANT_LENGTH_SIZE: usize = 4;
+                let (length, _) = u32::deserialize(&buf[1..], endian)?;
+                let end = 1 + VARIANT_LENGTH_SIZE + length as usize;
+                if buf.len() < end {
+                    return Err(DeserializeError::Invalid);
+                }
+                let buf = &buf[..end];
+                let mut offset = 1 + VARIANT_LENGTH_SIZE;
+            },
+            quote! { end },
+        )
+    } else {
+        (quote! { let mut offset = 1; }, quote! { offset })
+    };
 
-    quote! {
+    Ok(quote! {
         impl Deserialize for #name {
-            fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
+            fn deserialize(buf: &[u8], #endian_param: Endianness) -> Result<(Self, usize), DeserializeError> {
+                #endian_init
                 if buf.len() < 1 {
                     return Err(DeserializeError::Invalid);
                 }
 
-                let mut offset = 1;
+                #body_start
 
-                Ok((match buf[0] {
+                let value = match buf[0] {
                     #(#variant_arms,)*
-                    _ => return Err(DeserializeError::Invalid),
-                }, offset))
+                    #unknown_arm,
+                };
+
+                Ok((value, #body_end))
+            }
+
+            fn skip(buf: &[u8], #endian_param: Endianness) -> Result<usize, DeserializeError> {
+                #endian_init
+                #skip_body
             }
         }
+
+        #async_impl
+    })
+}
+
+/// Implements `Schema` on its own, for types that derive only `Deserialize`, or that opt out of
+/// the impl `Serialize` derives with `#[dryb(no_schema)]` and write their own `Serialize`.
+#[proc_macro_derive(Schema, attributes(dryb))]
+pub fn derive_schema(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    let name = &ast.ident;
+
+    let expanded =
+        ContainerAttrs::parse(&ast.attrs).and_then(|attrs| impl_schema(name, &attrs, ast.data));
+
+    TokenStream::from(expanded.unwrap_or_else(syn::Error::into_compile_error))
+}
+
+fn impl_schema(
+    name: &syn::Ident,
+    attrs: &ContainerAttrs,
+    data: syn::Data,
+) -> syn::Result<proc_macro2::TokenStream> {
+    match data {
+        syn::Data::Struct(s) => schema::impl_schema_struct(name, attrs, s),
+        syn::Data::Enum(e) => schema::impl_schema_enum(name, attrs, e),
+        _ => panic!("Schema only works with structs and enums"),
     }
 }
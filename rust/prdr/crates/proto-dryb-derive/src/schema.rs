@@ -0,0 +1,208 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{ext::IdentExt, DataEnum, DataStruct, Fields, Ident};
+
+use crate::attr::{variant_tags, ContainerAttrs, Endian, FieldAttrs, VariantAttrs};
+use crate::parse_fields;
+
+pub(crate) fn impl_schema_struct(
+    name: &Ident,
+    attrs: &ContainerAttrs,
+    s: DataStruct,
+) -> syn::Result<TokenStream> {
+    attrs.check_struct(name)?;
+
+    let fields = match &s.fields {
+        Fields::Named(fields) => parse_fields(&fields.named)?,
+        _ => panic!("Schema only works with structs that have named fields"),
+    };
+    attrs.check_fields(&fields)?;
+
+    let name_str = name.unraw().to_string();
+    let endian = schema_endian(attrs.endian);
+    let encoding = match attrs.version {
+        _ if attrs.tagged => quote! { ::proto_dryb::schema::StructEncoding::Tagged },
+        Some(version) => quote! { ::proto_dryb::schema::StructEncoding::Versioned(#version) },
+        None => quote! { ::proto_dryb::schema::StructEncoding::Positional },
+    };
+    let field_schemas = fields.iter().map(|(f, field_attrs)| {
+        let field_name = f.ident.as_ref().unwrap().unraw().to_string();
+        field_schema(&field_name, &f.ty, field_attrs)
+    });
+    let encoding_description = match attrs.version {
+        _ if attrs.tagged => " tagged".to_string(),
+        Some(version) => format!(" version={}", version),
+        None => String::new(),
+    };
+    let description = format!(
+        "struct {} endian={}{}",
+        name_str,
+        endian_name(attrs.endian),
+        encoding_description
+    );
+    let field_fingerprints = fields.iter().map(|(f, field_attrs)| {
+        let field_name = f.ident.as_ref().unwrap().unraw().to_string();
+        field_fingerprint(&field_name, &f.ty, field_attrs)
+    });
+
+    Ok(quote! {
+        impl ::proto_dryb::Schema for #name {
+            const FINGERPRINT: u64 = ::proto_dryb::fingerprint::combine(&[
+                ::proto_dryb::fingerprint::hash(#description),
+                #(#field_fingerprints)*
+            ]);
+
+            fn schema() -> ::proto_dryb::schema::Type {
+                ::proto_dryb::schema::Type::Struct(::proto_dryb::schema::Struct {
+                    name: ::std::string::String::from(#name_str),
+                    endian: #endian,
+                    encoding: #encoding,
+                    fields: ::std::vec![#(#field_schemas),*],
+                })
+            }
+        }
+    })
+}
+
+pub(crate) fn impl_schema_enum(
+    name: &Ident,
+    attrs: &ContainerAttrs,
+    e: DataEnum,
+) -> syn::Result<TokenStream> {
+    attrs.check_enum(name)?;
+
+    let name_str = name.unraw().to_string();
+    let endian = schema_endian(attrs.endian);
+    let length_prefixed = attrs.length_prefixed;
+    let tags = variant_tags(&e)?;
+    let mut fingerprints = Vec::new();
+    let variant_schemas = e
+        .variants
+        .iter()
+        .zip(tags)
+        .map(|(variant, tag)| {
+            let variant_name = variant.ident.unraw().to_string();
+            let other = VariantAttrs::parse(&variant.attrs)?.other;
+            let (kind, fields) = match &variant.fields {
+                Fields::Named(fields) => (quote! { Struct }, parse_fields(&fields.named)?),
+                Fields::Unnamed(fields) => (quote! { Tuple }, parse_fields(&fields.unnamed)?),
+                Fields::Unit => (quote! { Unit }, Vec::new()),
+            };
+            let field_names = fields
+                .iter()
+                .enumerate()
+                .map(|(i, (f, _))| match &f.ident {
+                    Some(ident) => ident.unraw().to_string(),
+                    None => i.to_string(),
+                })
+                .collect::<Vec<_>>();
+            let field_schemas = fields
+                .iter()
+                .zip(&field_names)
+                .map(|((f, field_attrs), field_name)| field_schema(field_name, &f.ty, field_attrs));
+
+            let description = format!(
+                "variant {} tag={} {}{}",
+                variant_name,
+                tag,
+                kind.to_string().to_lowercase(),
+                if other { " other" } else { "" }
+            );
+            fingerprints.push(quote! { ::proto_dryb::fingerprint::hash(#description), });
+            fingerprints.extend(fields.iter().zip(&field_names).map(
+                |((f, field_attrs), field_name)| field_fingerprint(field_name, &f.ty, field_attrs),
+            ));
+
+            Ok(quote! {
+                ::proto_dryb::schema::Variant {
+                    name: ::std::string::String::from(#variant_name),
+                    tag: #tag,
+                    kind: ::proto_dryb::schema::VariantKind::#kind,
+                    fields: ::std::vec![#(#field_schemas),*],
+                    other: #other,
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let description = format!(
+        "enum {} endian={}{}",
+        name_str,
+        endian_name(attrs.endian),
+        if length_prefixed {
+            " length_prefixed"
+        } else {
+            ""
+        }
+    );
+
+    Ok(quote! {
+        impl ::proto_dryb::Schema for #name {
+            const FINGERPRINT: u64 = ::proto_dryb::fingerprint::combine(&[
+                ::proto_dryb::fingerprint::hash(#description),
+                #(#fingerprints)*
+            ]);
+
+            fn schema() -> ::proto_dryb::schema::Type {
+                ::proto_dryb::schema::Type::Enum(::proto_dryb::schema::Enum {
+                    name: ::std::string::String::from(#name_str),
+                    endian: #endian,
+                    length_prefixed: #length_prefixed,
+                    variants: ::std::vec![#(#variant_schemas),*],
+                })
+            }
+        }
+    })
+}
+
+fn field_schema(name: &str, ty: &syn::Type, attrs: &FieldAttrs) -> TokenStream {
+    let endian = schema_endian(attrs.endian);
+    let since = option(attrs.since);
+    let id = option(attrs.id);
+
+    quote! {
+        ::proto_dryb::schema::Field {
+            name: ::std::string::String::from(#name),
+            ty: <#ty as ::proto_dryb::Schema>::schema(),
+            endian: #endian,
+            since: #since,
+            id: #id,
+        }
+    }
+}
+
+/// The description hash and type fingerprint of a field, matching `fingerprint::of`.
+fn field_fingerprint(name: &str, ty: &syn::Type, attrs: &FieldAttrs) -> TokenStream {
+    let mut description = format!("field {} endian={}", name, endian_name(attrs.endian));
+    if let Some(since) = attrs.since {
+        description += &format!(" since={}", since);
+    }
+    if let Some(id) = attrs.id {
+        description += &format!(" id={}", id);
+    }
+
+    quote! {
+        ::proto_dryb::fingerprint::hash(#description),
+        <#ty as ::proto_dryb::Schema>::FINGERPRINT,
+    }
+}
+
+fn endian_name(endian: Option<Endian>) -> &'static str {
+    endian.map_or("inherit", Endian::name)
+}
+
+fn schema_endian(endian: Option<Endian>) -> TokenStream {
+    match endian {
+        Some(endian) => {
+            let path = endian.path();
+            quote! { ::std::option::Option::Some(#path) }
+        }
+        None => quote! { ::std::option::Option::None },
+    }
+}
+
+fn option(value: Option<u16>) -> TokenStream {
+    match value {
+        Some(value) => quote! { ::std::option::Option::Some(#value) },
+        None => quote! { ::std::option::Option::None },
+    }
+}
@@ -0,0 +1,106 @@
+//! `#[dryb(view)]`: a borrowed view of an encoded struct with one accessor per field. An
+//! accessor skips the fields before its own and decodes only that one, borrowing `String` and
+//! `Vec<u8>` fields as `&str` and `&[u8]`.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Field, Ident, Visibility};
+
+use crate::attr::{ContainerAttrs, FieldAttrs};
+
+/// The type an accessor returns for a field of type `ty`.
+fn borrowed_type(ty: &syn::Type) -> TokenStream {
+    let syn::Type::Path(path) = ty else {
+        return quote! { #ty };
+    };
+    if path.qself.is_none() && path.path.is_ident("String") {
+        return quote! { &'a str };
+    }
+
+    let last = path.path.segments.last().unwrap();
+    if last.ident == "Vec" {
+        if let syn::PathArguments::AngleBracketed(args) = &last.arguments {
+            if let Some(syn::GenericArgument::Type(syn::Type::Path(element))) = args.args.first() {
+                if element.path.is_ident("u8") {
+                    return quote! { &'a [u8] };
+                }
+            }
+        }
+    }
+    quote! { #ty }
+}
+
+pub(crate) fn view_struct(
+    name: &Ident,
+    vis: &Visibility,
+    attrs: &ContainerAttrs,
+    fields: &[(&Field, FieldAttrs)],
+) -> TokenStream {
+    let view_name = format_ident!("{}View", name);
+    let endian_init = match &attrs.endian {
+        Some(endian) => quote! { #[allow(unused_variables)] let endian = #endian; },
+        None => quote! { #[allow(unused_variables)] let endian = self.endian; },
+    };
+
+    let accessors = fields.iter().enumerate().map(|(i, (f, field_attrs))| {
+        let field_name = &f.ident;
+        let field_type = &f.ty;
+        let field_endian = field_attrs.endian();
+        let returned = borrowed_type(field_type);
+        let skips = fields[..i].iter().map(|(f, field_attrs)| {
+            let skipped_type = &f.ty;
+            let skipped_endian = field_attrs.endian();
+            quote! {
+                offset += <#skipped_type as Deserialize>::skip(&self.buf[offset..], #skipped_endian)?;
+            }
+        });
+        let doc = format!(
+            "Decodes `{}`, skipping the fields before it.",
+            field_name.as_ref().unwrap()
+        );
+
+        quote! {
+            #[doc = #doc]
+            #vis fn #field_name(&self) -> Result<#returned, DeserializeError> {
+                #endian_init
+                #[allow(unused_mut)]
+                let mut offset = 0;
+                #(#skips)*
+                let (value, _) = <#returned as ::proto_dryb::DeserializeBorrowed<'a>>::deserialize_borrowed(
+                    &self.buf[offset..],
+                    #field_endian,
+                )?;
+                Ok(value)
+            }
+        }
+    });
+    let doc = format!(
+        "A `{}` still in its encoded form, decoding fields only when they are accessed.",
+        name
+    );
+
+    quote! {
+        #[doc = #doc]
+        #[derive(Clone, Copy, Debug)]
+        #vis struct #view_name<'a> {
+            buf: &'a [u8],
+            endian: Endianness,
+        }
+
+        impl<'a> #view_name<'a> {
+            /// Wraps the value encoded at the start of `buf`. Nothing is checked until a field
+            /// is read.
+            #vis fn new(buf: &'a [u8], endian: Endianness) -> Self {
+                #view_name { buf, endian }
+            }
+
+            /// Decodes the whole value.
+            #vis fn decode(&self) -> Result<#name, DeserializeError> {
+                let (value, _) = <#name as Deserialize>::deserialize(self.buf, self.endian)?;
+                Ok(value)
+            }
+
+            #(#accessors)*
+        }
+    }
+}
@@ -0,0 +1,485 @@
+use proto_dryb::{Deserialize, DeserializeError, Endianness, Serialize, SerializeError};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(endian = "big")]
+struct NetworkHeader {
+    length: u16,
+    sequence: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct FileRecord {
+    magic: u16,
+    header: NetworkHeader,
+    #[dryb(endian = "big")]
+    checksum: u32,
+    #[dryb(endian = "native")]
+    local: u16,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Sample {
+    Pair(u16, #[dryb(endian = "big")] u16),
+    Named {
+        #[dryb(endian = "little")]
+        value: u32,
+    },
+}
+
+#[test]
+fn test_field_and_container_endianness() {
+    let record = FileRecord {
+        magic: 0x0102,
+        header: NetworkHeader {
+            length: 0x0304,
+            sequence: 0x05060708,
+        },
+        checksum: 0x090a0b0c,
+        local: 0x0d0e,
+    };
+    let mut buffer = [0u8; 64];
+    let size = record.serialize(&mut buffer, Endianness::Little).unwrap();
+
+    let mut expected = vec![0x02, 0x01, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+    expected.extend_from_slice(&[0x09, 0x0a, 0x0b, 0x0c]);
+    expected.extend_from_slice(&0x0d0eu16.to_ne_bytes());
+    assert_eq!(&buffer[..size], &expected[..]);
+
+    let (decoded, read) = FileRecord::deserialize(&buffer[..size], Endianness::Little).unwrap();
+    assert_eq!(decoded, record);
+    assert_eq!(read, size);
+}
+
+#[test]
+fn test_container_endianness_ignores_inherited() {
+    let header = NetworkHeader {
+        length: 1,
+        sequence: 2,
+    };
+    let mut little = [0u8; 16];
+    let mut big = [0u8; 16];
+    header.serialize(&mut little, Endianness::Little).unwrap();
+    header.serialize(&mut big, Endianness::Big).unwrap();
+    assert_eq!(little, big);
+}
+
+#[test]
+fn test_enum_variant_field_endianness() {
+    let mut buffer = [0u8; 16];
+
+    let pair = Sample::Pair(1, 2);
+    let size = pair.serialize(&mut buffer, Endianness::Little).unwrap();
+    assert_eq!(&buffer[..size], &[0, 1, 0, 0, 2]);
+    assert_eq!(
+        Sample::deserialize(&buffer[..size], Endianness::Little).unwrap(),
+        (pair, size)
+    );
+
+    let named = Sample::Named { value: 3 };
+    let size = named.serialize(&mut buffer, Endianness::Big).unwrap();
+    assert_eq!(&buffer[..size], &[1, 3, 0, 0, 0]);
+    assert_eq!(
+        Sample::deserialize(&buffer[..size], Endianness::Big).unwrap(),
+        (named, size)
+    );
+}
+
+#[test]
+fn test_native_endianness() {
+    let mut buffer = [0u8; 4];
+    0x01020304u32
+        .serialize(&mut buffer, Endianness::Native)
+        .unwrap();
+    assert_eq!(buffer, 0x01020304u32.to_ne_bytes());
+    assert_eq!(
+        u32::deserialize(&buffer, Endianness::Native).unwrap(),
+        (0x01020304, 4)
+    );
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum CacheStatusV1 {
+    Miss,
+    Hit,
+    #[dryb(other)]
+    Unknown(u8),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum CacheStatusV2 {
+    Miss,
+    Hit,
+    Stale,
+    Revalidated,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Plan {
+    Free,
+    #[dryb(other)]
+    Other,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(length_prefixed)]
+enum EventV1 {
+    Start {
+        at: u64,
+    },
+    #[dryb(other)]
+    Unknown(u8),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(length_prefixed)]
+enum EventV2 {
+    Start { at: u64 },
+    Progress(u32, String),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct EventRecord {
+    event: EventV1,
+    sequence: u16,
+}
+
+#[test]
+fn test_other_variant_captures_unknown_tag() {
+    let mut buffer = [0u8; 16];
+    let size = CacheStatusV2::Revalidated
+        .serialize(&mut buffer, Endianness::Little)
+        .unwrap();
+
+    let (status, read) = CacheStatusV1::deserialize(&buffer[..size], Endianness::Little).unwrap();
+    assert_eq!(status, CacheStatusV1::Unknown(3));
+    assert_eq!(read, 1);
+
+    let size = status.serialize(&mut buffer, Endianness::Little).unwrap();
+    assert_eq!(
+        CacheStatusV2::deserialize(&buffer[..size], Endianness::Little).unwrap(),
+        (CacheStatusV2::Revalidated, 1)
+    );
+    assert_eq!(
+        CacheStatusV1::deserialize(&[1], Endianness::Little).unwrap(),
+        (CacheStatusV1::Hit, 1)
+    );
+}
+
+#[test]
+fn test_other_variant_refuses_known_tag() {
+    let mut buffer = [0u8; 16];
+    for tag in [0, 1] {
+        assert!(matches!(
+            CacheStatusV1::Unknown(tag).serialize(&mut buffer, Endianness::Little),
+            Err(SerializeError::KnownTag(t)) if t == tag
+        ));
+    }
+    // The catch-all's own position in the declaration order doesn't make its tag known.
+    assert!(CacheStatusV1::Unknown(2)
+        .serialize(&mut buffer, Endianness::Little)
+        .is_ok());
+}
+
+#[test]
+fn test_unit_other_variant() {
+    assert_eq!(
+        Plan::deserialize(&[9], Endianness::Little).unwrap(),
+        (Plan::Other, 1)
+    );
+    assert!(CacheStatusV2::deserialize(&[9], Endianness::Little).is_err());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Opcode {
+    #[dryb(tag = 0x10)]
+    Read,
+    Write(u8),
+    #[dryb(tag = 0x80)]
+    Reset,
+}
+
+#[test]
+fn test_explicit_variant_tags() {
+    let mut buffer = [0u8; 16];
+    let size = Opcode::Read.serialize(&mut buffer, Endianness::Little).unwrap();
+    assert_eq!(&buffer[..size], &[0x10]);
+    let size = Opcode::Write(7)
+        .serialize(&mut buffer, Endianness::Little)
+        .unwrap();
+    assert_eq!(&buffer[..size], &[0x11, 7]);
+
+    assert_eq!(
+        Opcode::deserialize(&[0x80], Endianness::Little).unwrap(),
+        (Opcode::Reset, 1)
+    );
+    assert!(matches!(
+        Opcode::deserialize(&[0x00], Endianness::Little),
+        Err(DeserializeError::Invalid)
+    ));
+}
+
+#[test]
+fn test_length_prefixed_unknown_variant_is_skipped() {
+    let mut buffer = [0u8; 64];
+    let mut size = EventV2::Progress(50, "halfway".to_string())
+        .serialize(&mut buffer, Endianness::Big)
+        .unwrap();
+    size += 7u16
+        .serialize(&mut buffer[size..], Endianness::Big)
+        .unwrap();
+
+    let (record, read) = EventRecord::deserialize(&buffer[..size], Endianness::Big).unwrap();
+    assert_eq!(
+        record,
+        EventRecord {
+            event: EventV1::Unknown(1),
+            sequence: 7,
+        }
+    );
+    assert_eq!(read, size);
+}
+
+#[test]
+fn test_length_prefixed_roundtrip() {
+    let mut buffer = [0u8; 64];
+    let event = EventV2::Start { at: 42 };
+    let size = event.serialize(&mut buffer, Endianness::Little).unwrap();
+    assert_eq!(&buffer[..5], &[0, 8, 0, 0, 0]);
+    assert_eq!(
+        EventV1::deserialize(&buffer[..size], Endianness::Little).unwrap(),
+        (EventV1::Start { at: 42 }, size)
+    );
+    assert_eq!(
+        EventV2::deserialize(&buffer[..size], Endianness::Little).unwrap(),
+        (event, size)
+    );
+    assert!(EventV2::deserialize(&buffer[..size - 1], Endianness::Little).is_err());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(version = 1)]
+struct LogV1 {
+    timestamp: i64,
+    status: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(version = 3)]
+struct LogV3 {
+    timestamp: i64,
+    status: u32,
+    #[dryb(since = 2)]
+    ray_id: String,
+    #[dryb(since = 3)]
+    bytes: Option<u64>,
+}
+
+#[test]
+fn test_versioned_header() {
+    let mut buffer = [0u8; 64];
+    let log = LogV1 {
+        timestamp: 1,
+        status: 200,
+    };
+    let size = log.serialize(&mut buffer, Endianness::Big).unwrap();
+    assert_eq!(&buffer[..6], &[0, 1, 0, 0, 0, 12]);
+    assert_eq!(size, 18);
+    assert_eq!(
+        LogV1::deserialize(&buffer[..size], Endianness::Big).unwrap(),
+        (log, size)
+    );
+}
+
+#[test]
+fn test_old_reader_skips_newer_fields() {
+    let logs = vec![
+        LogV3 {
+            timestamp: 1,
+            status: 200,
+            ray_id: "10c73629cce30078-LAX".to_string(),
+            bytes: Some(123456),
+        },
+        LogV3 {
+            timestamp: 2,
+            status: 404,
+            ray_id: String::new(),
+            bytes: None,
+        },
+    ];
+    let mut buffer = [0u8; 128];
+    let size = logs.serialize(&mut buffer, Endianness::Little).unwrap();
+
+    let (decoded, read) = Vec::<LogV1>::deserialize(&buffer[..size], Endianness::Little).unwrap();
+    assert_eq!(
+        decoded,
+        vec![
+            LogV1 {
+                timestamp: 1,
+                status: 200
+            },
+            LogV1 {
+                timestamp: 2,
+                status: 404
+            },
+        ]
+    );
+    assert_eq!(read, size);
+}
+
+#[test]
+fn test_new_reader_defaults_missing_fields() {
+    let mut buffer = [0u8; 64];
+    let size = LogV1 {
+        timestamp: 7,
+        status: 500,
+    }
+    .serialize(&mut buffer, Endianness::Little)
+    .unwrap();
+
+    let (decoded, read) = LogV3::deserialize(&buffer[..size], Endianness::Little).unwrap();
+    assert_eq!(
+        decoded,
+        LogV3 {
+            timestamp: 7,
+            status: 500,
+            ray_id: String::new(),
+            bytes: None,
+        }
+    );
+    assert_eq!(read, size);
+    assert!(LogV3::deserialize(&buffer[..size - 1], Endianness::Little).is_err());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(tagged)]
+struct OriginV1 {
+    #[dryb(id = 1)]
+    ip: String,
+    #[dryb(id = 2)]
+    port: u32,
+    #[dryb(id = 3)]
+    hostname: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(tagged)]
+struct OriginV2 {
+    #[dryb(id = 4)]
+    protocol: Option<u8>,
+    #[dryb(id = 2)]
+    port: u32,
+    #[dryb(id = 1, endian = "big")]
+    ip: String,
+}
+
+#[test]
+fn test_tagged_layout() {
+    let mut buffer = [0u8; 64];
+    let origin = OriginV2 {
+        protocol: None,
+        port: 8000,
+        ip: String::new(),
+    };
+    let size = origin.serialize(&mut buffer, Endianness::Little).unwrap();
+    assert_eq!(
+        &buffer[..size],
+        &[
+            27, 0, 0, 0, // body length
+            4, 0, 1, 0, 0, 0, 0, // protocol
+            2, 0, 4, 0, 0, 0, 0x40, 0x1f, 0, 0, // port
+            1, 0, 4, 0, 0, 0, 0, 0, 0, 0, // ip
+        ]
+    );
+    assert_eq!(
+        OriginV2::deserialize(&buffer[..size], Endianness::Little).unwrap(),
+        (origin, size)
+    );
+}
+
+#[test]
+fn test_tagged_fields_can_be_reordered_added_and_removed() {
+    let mut buffer = [0u8; 128];
+    let old = OriginV1 {
+        ip: "1.2.3.4".to_string(),
+        port: 8000,
+        hostname: "www.example.com".to_string(),
+    };
+    let size = old.serialize(&mut buffer, Endianness::Big).unwrap();
+    let (new, read) = OriginV2::deserialize(&buffer[..size], Endianness::Big).unwrap();
+    assert_eq!(
+        new,
+        OriginV2 {
+            protocol: None,
+            port: 8000,
+            ip: "1.2.3.4".to_string(),
+        }
+    );
+    assert_eq!(read, size);
+
+    let new = OriginV2 {
+        protocol: Some(2),
+        ..new
+    };
+    let size = new.serialize(&mut buffer, Endianness::Big).unwrap();
+    let (old, read) = OriginV1::deserialize(&buffer[..size], Endianness::Big).unwrap();
+    assert_eq!(
+        old,
+        OriginV1 {
+            ip: "1.2.3.4".to_string(),
+            port: 8000,
+            hostname: String::new(),
+        }
+    );
+    assert_eq!(read, size);
+}
+
+#[test]
+fn test_tagged_truncated_field_is_invalid() {
+    let mut buffer = [0u8; 64];
+    let size = OriginV1 {
+        ip: "1.2.3.4".to_string(),
+        port: 1,
+        hostname: String::new(),
+    }
+    .serialize(&mut buffer, Endianness::Little)
+    .unwrap();
+    buffer[0] -= 1;
+    assert!(OriginV1::deserialize(&buffer[..size - 1], Endianness::Little).is_err());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(version = 1)]
+struct Shadowing {
+    buf: u8,
+    offset: u16,
+    version: u32,
+    end: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum ShadowingEnum {
+    Fields { size: u8, buf: u16 },
+}
+
+#[test]
+fn test_field_names_do_not_shadow_generated_locals() {
+    let mut buffer = [0u8; 64];
+    let value = Shadowing {
+        buf: 1,
+        offset: 2,
+        version: 3,
+        end: "4".to_string(),
+    };
+    let size = value.serialize(&mut buffer, Endianness::Little).unwrap();
+    assert_eq!(
+        Shadowing::deserialize(&buffer[..size], Endianness::Little).unwrap(),
+        (value, size)
+    );
+
+    let value = ShadowingEnum::Fields { size: 5, buf: 6 };
+    let size = value.serialize(&mut buffer, Endianness::Little).unwrap();
+    assert_eq!(
+        ShadowingEnum::deserialize(&buffer[..size], Endianness::Little).unwrap(),
+        (value, size)
+    );
+}
@@ -400,6 +400,14 @@ fn criterion_benchmark(c: &mut Criterion) {
             black_box(Log::deserialize(&buffer[..len], Endianness::Little)).unwrap();
         })
     });
+    c.bench_function("deserialize Log in place", |b| {
+        let mut value = Log::new();
+        let mut buffer = [0u8; 1024];
+        let len = value.serialize(&mut buffer, Endianness::Little).unwrap();
+        b.iter(|| {
+            black_box(value.deserialize_in_place(&buffer[..len], Endianness::Little)).unwrap();
+        })
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
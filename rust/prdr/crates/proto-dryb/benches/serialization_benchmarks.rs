@@ -1,3 +1,5 @@
+#![allow(clippy::approx_constant)]
+
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use proto_dryb::{Deserialize, DeserializeError, Endianness, Serialize, SerializeError};
 
@@ -152,6 +154,48 @@ fn criterion_benchmark(c: &mut Criterion) {
             black_box(CustomEnum::deserialize(&buffer[..len], Endianness::Little)).unwrap();
         })
     });
+
+    // Bulk paths for runs of primitives
+    let blob: Vec<u8> = (0..4 * 1024 * 1024).map(|i| i as u8).collect();
+    let samples: Vec<u32> = (0..1024 * 1024).collect();
+    let sample_array: [u32; 1024] = std::array::from_fn(|i| i as u32);
+    let mut buffer = vec![0u8; 5 * 1024 * 1024];
+
+    c.bench_function("serialize Vec<u8> 4 MiB", |b| {
+        b.iter(|| {
+            black_box(blob.serialize(&mut buffer, Endianness::Little)).unwrap();
+        })
+    });
+    c.bench_function("deserialize Vec<u8> 4 MiB", |b| {
+        let len = blob.serialize(&mut buffer, Endianness::Little).unwrap();
+        b.iter(|| {
+            black_box(Vec::<u8>::deserialize(&buffer[..len], Endianness::Little)).unwrap();
+        })
+    });
+    c.bench_function("serialize Vec<u32> 4 MiB big endian", |b| {
+        b.iter(|| {
+            black_box(samples.serialize(&mut buffer, Endianness::Big)).unwrap();
+        })
+    });
+    c.bench_function("deserialize Vec<u32> 4 MiB big endian", |b| {
+        let len = samples.serialize(&mut buffer, Endianness::Big).unwrap();
+        b.iter(|| {
+            black_box(Vec::<u32>::deserialize(&buffer[..len], Endianness::Big)).unwrap();
+        })
+    });
+    c.bench_function("serialize [u32; 1024]", |b| {
+        b.iter(|| {
+            black_box(sample_array.serialize(&mut buffer, Endianness::Little)).unwrap();
+        })
+    });
+    c.bench_function("deserialize [u32; 1024]", |b| {
+        let len = sample_array
+            .serialize(&mut buffer, Endianness::Little)
+            .unwrap();
+        b.iter(|| {
+            black_box(<[u32; 1024]>::deserialize(&buffer[..len], Endianness::Little)).unwrap();
+        })
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -1,3 +1,5 @@
+#![allow(clippy::disallowed_names)]
+
 use proto_dryb::{Deserialize, Endianness, Serialize};
 
 fn main() {
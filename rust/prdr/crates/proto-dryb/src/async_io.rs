@@ -0,0 +1,470 @@
+//! Writes and reads dryb values directly to and from tokio's `AsyncWrite` and `AsyncRead`,
+//! one field at a time, so a large `Vec` never has to sit in a buffer whole.
+//!
+//! The `Serialize` and `Deserialize` derives implement these traits too for types marked
+//! `#[dryb(async)]`, whose field types then need them as well. Versioned and tagged structs
+//! and length-prefixed enums carry their length up front, so they are still encoded to a
+//! buffer first and read into one before decoding. Derived impls box their futures, so
+//! recursive types work, at the cost of an allocation per value.
+//!
+//! Invalid payloads are reported as `io::ErrorKind::InvalidData` errors wrapping the
+//! `DeserializeError`, and a stream that ends early as `io::ErrorKind::UnexpectedEof`. A `Vec`
+//! or array of primitives is written a chunk at a time, but every other primitive is a
+//! separate write, so wrap unbuffered writers in a `BufWriter`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::hash::Hash;
+use std::io;
+
+pub use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::checksum::{Checksum, Checksummed};
+use crate::{
+    Deserialize, DeserializeError, Endianness, Fingerprinted, Schema, Serialize, SerializeError,
+};
+
+pub trait AsyncSerialize: Sync {
+    /// Writes the same bytes as `Serialize::serialize` and returns how many there were.
+    fn serialize_async<'a, W>(
+        &'a self,
+        writer: &'a mut W,
+        endian: Endianness,
+    ) -> impl Future<Output = io::Result<usize>> + Send + 'a
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized;
+
+    /// Writes `values` one after another, as `Vec` and arrays lay out their elements. The
+    /// primitives override this to write a chunk of values at a time.
+    fn serialize_slice_async<'a, W>(
+        values: &'a [Self],
+        writer: &'a mut W,
+        endian: Endianness,
+    ) -> impl Future<Output = io::Result<usize>> + Send + 'a
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+        Self: Sized,
+    {
+        async move {
+            let mut offset = 0;
+            for value in values {
+                offset += value.serialize_async(writer, endian).await?;
+            }
+            Ok(offset)
+        }
+    }
+}
+
+pub trait AsyncDeserialize: Sized + Send + 'static {
+    /// Reads exactly one value, returning it and the number of bytes it took.
+    fn deserialize_async<'a, R>(
+        reader: &'a mut R,
+        endian: Endianness,
+    ) -> impl Future<Output = io::Result<(Self, usize)>> + Send + 'a
+    where
+        R: AsyncRead + Unpin + Send + ?Sized;
+}
+
+pub(crate) fn invalid(error: DeserializeError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// The bytes `serialize_slice_async` encodes a run of primitives into before each write.
+const CHUNK_SIZE: usize = 512;
+
+macro_rules! fixed_size {
+    ($($ty:ty => $size:expr),* $(,)?) => {$(
+        impl AsyncSerialize for $ty {
+            async fn serialize_async<'a, W>(
+                &'a self,
+                writer: &'a mut W,
+                endian: Endianness,
+            ) -> io::Result<usize>
+            where
+                W: AsyncWrite + Unpin + Send + ?Sized,
+            {
+                let mut bytes = [0u8; $size];
+                self.serialize(&mut bytes, endian).map_err(io::Error::other)?;
+                writer.write_all(&bytes).await?;
+                Ok($size)
+            }
+
+            async fn serialize_slice_async<'a, W>(
+                values: &'a [Self],
+                writer: &'a mut W,
+                endian: Endianness,
+            ) -> io::Result<usize>
+            where
+                W: AsyncWrite + Unpin + Send + ?Sized,
+            {
+                let mut bytes = [0u8; CHUNK_SIZE];
+                for chunk in values.chunks(CHUNK_SIZE / $size) {
+                    let size = <$ty>::serialize_slice(chunk, &mut bytes, endian)
+                        .map_err(io::Error::other)?;
+                    writer.write_all(&bytes[..size]).await?;
+                }
+                Ok(values.len() * $size)
+            }
+        }
+
+        impl AsyncDeserialize for $ty {
+            async fn deserialize_async<R>(
+                reader: &mut R,
+                endian: Endianness,
+            ) -> io::Result<(Self, usize)>
+            where
+                R: AsyncRead + Unpin + Send + ?Sized,
+            {
+                let mut bytes = [0u8; $size];
+                reader.read_exact(&mut bytes).await?;
+                let (value, _) = <$ty>::deserialize(&bytes, endian).map_err(invalid)?;
+                Ok((value, $size))
+            }
+        }
+    )*};
+}
+
+fixed_size! {
+    u8 => 1, i8 => 1, u16 => 2, i16 => 2, u32 => 4, i32 => 4,
+    u64 => 8, i64 => 8, f32 => 4, f64 => 8, bool => 1,
+}
+
+async fn write_count<W>(count: usize, writer: &mut W, endian: Endianness) -> io::Result<usize>
+where
+    W: AsyncWrite + Unpin + Send + ?Sized,
+{
+    let count = u32::try_from(count).map_err(|_| {
+        let error = format!("{} items don't fit in a u32 count", count);
+        io::Error::new(io::ErrorKind::InvalidInput, error)
+    })?;
+    count.serialize_async(writer, endian).await
+}
+
+/// Reads a `u32` count, returning it and the bytes read. Collections grow as items arrive
+/// rather than trusting it for an allocation.
+async fn read_count<R>(reader: &mut R, endian: Endianness) -> io::Result<(usize, usize)>
+where
+    R: AsyncRead + Unpin + Send + ?Sized,
+{
+    let (count, size) = u32::deserialize_async(reader, endian).await?;
+    Ok((count as usize, size))
+}
+
+const PREALLOCATE_LIMIT: usize = 1024;
+
+impl<T: AsyncSerialize> AsyncSerialize for Option<T> {
+    async fn serialize_async<'a, W>(
+        &'a self,
+        writer: &'a mut W,
+        endian: Endianness,
+    ) -> io::Result<usize>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        match self {
+            Some(value) => {
+                writer.write_u8(1).await?;
+                Ok(1 + value.serialize_async(writer, endian).await?)
+            }
+            None => {
+                writer.write_u8(0).await?;
+                Ok(1)
+            }
+        }
+    }
+}
+
+impl<T: AsyncDeserialize> AsyncDeserialize for Option<T> {
+    async fn deserialize_async<R>(reader: &mut R, endian: Endianness) -> io::Result<(Self, usize)>
+    where
+        R: AsyncRead + Unpin + Send + ?Sized,
+    {
+        match reader.read_u8().await? {
+            0 => Ok((None, 1)),
+            1 => {
+                let (value, size) = T::deserialize_async(reader, endian).await?;
+                Ok((Some(value), size + 1))
+            }
+            _ => Err(invalid(DeserializeError::Invalid)),
+        }
+    }
+}
+
+impl<T: AsyncSerialize> AsyncSerialize for Vec<T> {
+    async fn serialize_async<'a, W>(
+        &'a self,
+        writer: &'a mut W,
+        endian: Endianness,
+    ) -> io::Result<usize>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        let size = write_count(self.len(), writer, endian).await?;
+        Ok(size + T::serialize_slice_async(self, writer, endian).await?)
+    }
+}
+
+impl<T: AsyncDeserialize> AsyncDeserialize for Vec<T> {
+    async fn deserialize_async<R>(reader: &mut R, endian: Endianness) -> io::Result<(Self, usize)>
+    where
+        R: AsyncRead + Unpin + Send + ?Sized,
+    {
+        let (count, mut offset) = read_count(reader, endian).await?;
+        let mut vec = Vec::with_capacity(count.min(PREALLOCATE_LIMIT));
+        for _ in 0..count {
+            let (item, size) = T::deserialize_async(reader, endian).await?;
+            vec.push(item);
+            offset += size;
+        }
+        Ok((vec, offset))
+    }
+}
+
+impl AsyncSerialize for String {
+    async fn serialize_async<'a, W>(
+        &'a self,
+        writer: &'a mut W,
+        endian: Endianness,
+    ) -> io::Result<usize>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        let size = write_count(self.len(), writer, endian).await?;
+        writer.write_all(self.as_bytes()).await?;
+        Ok(size + self.len())
+    }
+}
+
+impl AsyncDeserialize for String {
+    async fn deserialize_async<R>(reader: &mut R, endian: Endianness) -> io::Result<(Self, usize)>
+    where
+        R: AsyncRead + Unpin + Send + ?Sized,
+    {
+        let (count, size) = read_count(reader, endian).await?;
+        let mut bytes = Vec::with_capacity(count.min(PREALLOCATE_LIMIT));
+        let read = (&mut *reader)
+            .take(count as u64)
+            .read_to_end(&mut bytes)
+            .await?;
+        if read < count {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let string = String::from_utf8(bytes).map_err(|_| invalid(DeserializeError::Invalid))?;
+        Ok((string, size + count))
+    }
+}
+
+impl<T: AsyncSerialize, const N: usize> AsyncSerialize for [T; N] {
+    async fn serialize_async<'a, W>(
+        &'a self,
+        writer: &'a mut W,
+        endian: Endianness,
+    ) -> io::Result<usize>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        T::serialize_slice_async(self, writer, endian).await
+    }
+}
+
+impl<T: AsyncDeserialize, const N: usize> AsyncDeserialize for [T; N] {
+    async fn deserialize_async<R>(reader: &mut R, endian: Endianness) -> io::Result<(Self, usize)>
+    where
+        R: AsyncRead + Unpin + Send + ?Sized,
+    {
+        let mut items = Vec::with_capacity(N);
+        let mut offset = 0;
+        for _ in 0..N {
+            let (item, size) = T::deserialize_async(reader, endian).await?;
+            items.push(item);
+            offset += size;
+        }
+        match items.try_into() {
+            Ok(array) => Ok((array, offset)),
+            Err(_) => unreachable!("read exactly N items"),
+        }
+    }
+}
+
+macro_rules! map {
+    ($map:ident, $($bound:path),*) => {
+        impl<K: AsyncSerialize, V: AsyncSerialize> AsyncSerialize for $map<K, V> {
+            async fn serialize_async<'a, W>(
+                &'a self,
+                writer: &'a mut W,
+                endian: Endianness,
+            ) -> io::Result<usize>
+            where
+                W: AsyncWrite + Unpin + Send + ?Sized,
+            {
+                let mut offset = write_count(self.len(), writer, endian).await?;
+                for (key, value) in self {
+                    offset += key.serialize_async(writer, endian).await?;
+                    offset += value.serialize_async(writer, endian).await?;
+                }
+                Ok(offset)
+            }
+        }
+
+        impl<K: AsyncDeserialize $(+ $bound)*, V: AsyncDeserialize> AsyncDeserialize for $map<K, V> {
+            async fn deserialize_async<R>(
+                reader: &mut R,
+                endian: Endianness,
+            ) -> io::Result<(Self, usize)>
+            where
+                R: AsyncRead + Unpin + Send + ?Sized,
+            {
+                let (count, mut offset) = read_count(reader, endian).await?;
+                let mut map = $map::new();
+                for _ in 0..count {
+                    let (key, size) = K::deserialize_async(reader, endian).await?;
+                    offset += size;
+                    let (value, size) = V::deserialize_async(reader, endian).await?;
+                    offset += size;
+                    map.insert(key, value);
+                }
+                Ok((map, offset))
+            }
+        }
+    };
+}
+
+map!(HashMap, Eq, Hash);
+map!(BTreeMap, Ord);
+
+impl<T: AsyncSerialize> AsyncSerialize for Box<T> {
+    async fn serialize_async<'a, W>(
+        &'a self,
+        writer: &'a mut W,
+        endian: Endianness,
+    ) -> io::Result<usize>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        self.as_ref().serialize_async(writer, endian).await
+    }
+}
+
+impl<T: AsyncDeserialize> AsyncDeserialize for Box<T> {
+    async fn deserialize_async<R>(reader: &mut R, endian: Endianness) -> io::Result<(Self, usize)>
+    where
+        R: AsyncRead + Unpin + Send + ?Sized,
+    {
+        let (value, size) = T::deserialize_async(reader, endian).await?;
+        Ok((Box::new(value), size))
+    }
+}
+
+impl<T: Schema + AsyncSerialize> AsyncSerialize for Fingerprinted<T> {
+    async fn serialize_async<'a, W>(
+        &'a self,
+        writer: &'a mut W,
+        endian: Endianness,
+    ) -> io::Result<usize>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        let size = T::FINGERPRINT.serialize_async(writer, endian).await?;
+        Ok(size + self.0.serialize_async(writer, endian).await?)
+    }
+}
+
+impl<T: Schema + AsyncDeserialize> AsyncDeserialize for Fingerprinted<T> {
+    async fn deserialize_async<R>(reader: &mut R, endian: Endianness) -> io::Result<(Self, usize)>
+    where
+        R: AsyncRead + Unpin + Send + ?Sized,
+    {
+        let (found, size) = u64::deserialize_async(reader, endian).await?;
+        if found != T::FINGERPRINT {
+            return Err(invalid(DeserializeError::SchemaMismatch {
+                expected: T::FINGERPRINT,
+                found,
+            }));
+        }
+        let (value, value_size) = T::deserialize_async(reader, endian).await?;
+        Ok((Fingerprinted(value), size + value_size))
+    }
+}
+
+impl<T: Serialize + Sync, C: Checksum + Sync> AsyncSerialize for Checksummed<T, C> {
+    async fn serialize_async<'a, W>(
+        &'a self,
+        writer: &'a mut W,
+        endian: Endianness,
+    ) -> io::Result<usize>
+    where
+        W: AsyncWrite + Unpin + Send + ?Sized,
+    {
+        write_buffered(self, writer, endian).await
+    }
+}
+
+impl<T, C> AsyncDeserialize for Checksummed<T, C>
+where
+    T: Deserialize + Send + 'static,
+    C: Checksum + Send + 'static,
+{
+    async fn deserialize_async<R>(reader: &mut R, endian: Endianness) -> io::Result<(Self, usize)>
+    where
+        R: AsyncRead + Unpin + Send + ?Sized,
+    {
+        let mut buf = vec![0u8; 4];
+        reader.read_exact(&mut buf).await?;
+        let (length, _) = u32::deserialize(&buf, endian).map_err(invalid)?;
+        let total = 4 + length as u64 + C::SIZE as u64;
+        let read = (&mut *reader).take(total - 4).read_to_end(&mut buf).await?;
+        if (read as u64) < total - 4 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Self::deserialize(&buf, endian).map_err(invalid)
+    }
+}
+
+/// Used by derived impls for encodings that write their length before their body.
+#[doc(hidden)]
+pub async fn write_buffered<T, W>(
+    value: &T,
+    writer: &mut W,
+    endian: Endianness,
+) -> io::Result<usize>
+where
+    T: Serialize + ?Sized,
+    W: AsyncWrite + Unpin + Send + ?Sized,
+{
+    let mut buf = vec![0u8; 256];
+    let size = loop {
+        match value.serialize(&mut buf, endian) {
+            Ok(size) => break size,
+            Err(SerializeError::BufferOverflow) => buf.resize(buf.len() * 2, 0),
+            Err(e) => return Err(io::Error::other(e)),
+        }
+    };
+    writer.write_all(&buf[..size]).await?;
+    Ok(size)
+}
+
+/// Used by derived impls: reads a `header`-byte header whose last four bytes are the length of
+/// the body that follows, and decodes header and body with `T`'s sync impl.
+#[doc(hidden)]
+pub async fn read_buffered<T, R>(
+    reader: &mut R,
+    header: usize,
+    endian: Endianness,
+) -> io::Result<(T, usize)>
+where
+    T: Deserialize,
+    R: AsyncRead + Unpin + Send + ?Sized,
+{
+    let mut buf = vec![0u8; header];
+    reader.read_exact(&mut buf).await?;
+    let (length, _) = u32::deserialize(&buf[header - 4..], endian).map_err(invalid)?;
+    let read = (&mut *reader)
+        .take(length as u64)
+        .read_to_end(&mut buf)
+        .await?;
+    if read < length as usize {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    T::deserialize(&buf, endian).map_err(invalid)
+}
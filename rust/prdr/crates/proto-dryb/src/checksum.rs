@@ -0,0 +1,233 @@
+//! CRC-32C and xxHash64, and an envelope that uses them to catch corrupted payloads.
+
+use std::marker::PhantomData;
+
+use crate::deserialize::{fixed_size_sum, skip_length_prefixed, Deserialize};
+use crate::endian::Endianness;
+use crate::error::{DeserializeError, SerializeError};
+use crate::serialize::Serialize;
+
+pub trait Checksum {
+    type Value: Serialize + Deserialize + Copy + PartialEq + Into<u64>;
+    /// Encoded size of `Value`.
+    const SIZE: usize;
+
+    fn compute(bytes: &[u8]) -> Self::Value;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Crc32c;
+
+impl Checksum for Crc32c {
+    type Value = u32;
+    const SIZE: usize = 4;
+
+    fn compute(bytes: &[u8]) -> u32 {
+        crc32c(bytes)
+    }
+}
+
+/// xxHash64 with seed 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct XxHash64;
+
+impl Checksum for XxHash64 {
+    type Value = u64;
+    const SIZE: usize = 8;
+
+    fn compute(bytes: &[u8]) -> u64 {
+        xxhash64(bytes, 0)
+    }
+}
+
+const CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0x82f63b78
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// CRC-32C (Castagnoli).
+pub fn crc32c(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, byte| {
+        CRC32C_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+const PRIME64_1: u64 = 0x9e3779b185ebca87;
+const PRIME64_2: u64 = 0xc2b2ae3d27d4eb4f;
+const PRIME64_3: u64 = 0x165667b19e3779f9;
+const PRIME64_4: u64 = 0x85ebca77c2b2ae63;
+const PRIME64_5: u64 = 0x27d4eb2f165667c5;
+
+fn xxh_round(acc: u64, input: u64) -> u64 {
+    acc.wrapping_add(input.wrapping_mul(PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(PRIME64_1)
+}
+
+fn xxh_merge(hash: u64, acc: u64) -> u64 {
+    (hash ^ xxh_round(0, acc))
+        .wrapping_mul(PRIME64_1)
+        .wrapping_add(PRIME64_4)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..8].try_into().unwrap())
+}
+
+/// XXH64, as in the reference implementation.
+pub fn xxhash64(bytes: &[u8], seed: u64) -> u64 {
+    let mut rest = bytes;
+    let mut hash = if bytes.len() >= 32 {
+        let mut acc = [
+            seed.wrapping_add(PRIME64_1).wrapping_add(PRIME64_2),
+            seed.wrapping_add(PRIME64_2),
+            seed,
+            seed.wrapping_sub(PRIME64_1),
+        ];
+        while rest.len() >= 32 {
+            for (i, acc) in acc.iter_mut().enumerate() {
+                *acc = xxh_round(*acc, read_u64(&rest[8 * i..]));
+            }
+            rest = &rest[32..];
+        }
+        let hash = acc[0]
+            .rotate_left(1)
+            .wrapping_add(acc[1].rotate_left(7))
+            .wrapping_add(acc[2].rotate_left(12))
+            .wrapping_add(acc[3].rotate_left(18));
+        acc.iter().fold(hash, |hash, acc| xxh_merge(hash, *acc))
+    } else {
+        seed.wrapping_add(PRIME64_5)
+    };
+    hash = hash.wrapping_add(bytes.len() as u64);
+
+    while rest.len() >= 8 {
+        hash ^= xxh_round(0, read_u64(rest));
+        hash = hash
+            .rotate_left(27)
+            .wrapping_mul(PRIME64_1)
+            .wrapping_add(PRIME64_4);
+        rest = &rest[8..];
+    }
+    if rest.len() >= 4 {
+        let word = u32::from_le_bytes(rest[..4].try_into().unwrap()) as u64;
+        hash ^= word.wrapping_mul(PRIME64_1);
+        hash = hash
+            .rotate_left(23)
+            .wrapping_mul(PRIME64_2)
+            .wrapping_add(PRIME64_3);
+        rest = &rest[4..];
+    }
+    for byte in rest {
+        hash ^= (*byte as u64).wrapping_mul(PRIME64_5);
+        hash = hash.rotate_left(11).wrapping_mul(PRIME64_1);
+    }
+
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(PRIME64_2);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(PRIME64_3);
+    hash ^ (hash >> 32)
+}
+
+/// Wraps a value as a `u32` payload length, the payload, and a checksum of the payload. The
+/// checksum is verified before the payload is decoded, so corruption is reported as
+/// `DeserializeError::ChecksumMismatch` rather than as a wrong value or a decode error.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checksummed<T, C = Crc32c>(pub T, PhantomData<C>);
+
+impl<T, C> Checksummed<T, C> {
+    pub fn new(value: T) -> Self {
+        Checksummed(value, PhantomData)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+const LENGTH_SIZE: usize = 4;
+
+impl<T: Serialize, C: Checksum> Serialize for Checksummed<T, C> {
+    fn serialize(&self, buf: &mut [u8], endian: Endianness) -> Result<usize, SerializeError> {
+        if buf.len() < LENGTH_SIZE {
+            return Err(SerializeError::BufferOverflow);
+        }
+
+        let size = self.0.serialize(&mut buf[LENGTH_SIZE..], endian)?;
+        (size as u32).serialize(buf, endian)?;
+        let end = LENGTH_SIZE + size;
+        let checksum = C::compute(&buf[LENGTH_SIZE..end]);
+        Ok(end + checksum.serialize(&mut buf[end..], endian)?)
+    }
+}
+
+/// Checks the checksum of the envelope at the start of `buf`, returning its payload and the size
+/// of the whole envelope.
+fn verify<C: Checksum>(buf: &[u8], endian: Endianness) -> Result<(&[u8], usize), DeserializeError> {
+    let (length, _) = u32::deserialize(buf, endian)?;
+    let end = LENGTH_SIZE + length as usize;
+    if buf.len() < end {
+        return Err(DeserializeError::Invalid);
+    }
+
+    let (found, checksum_size) = C::Value::deserialize(&buf[end..], endian)?;
+    let expected = C::compute(&buf[LENGTH_SIZE..end]);
+    if found != expected {
+        return Err(DeserializeError::ChecksumMismatch {
+            expected: expected.into(),
+            found: found.into(),
+        });
+    }
+    Ok((&buf[LENGTH_SIZE..end], end + checksum_size))
+}
+
+impl<T: Deserialize, C: Checksum> Deserialize for Checksummed<T, C> {
+    const FIXED_SIZE: Option<usize> =
+        fixed_size_sum(&[Some(LENGTH_SIZE), T::FIXED_SIZE, Some(C::SIZE)]);
+
+    fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
+        let (payload, total) = verify::<C>(buf, endian)?;
+        let (value, size) = T::deserialize(payload, endian)?;
+        if size != payload.len() {
+            return Err(DeserializeError::Invalid);
+        }
+        Ok((Checksummed::new(value), total))
+    }
+
+    fn deserialize_in_place(
+        &mut self,
+        buf: &[u8],
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        let (payload, total) = verify::<C>(buf, endian)?;
+        if self.0.deserialize_in_place(payload, endian)? != payload.len() {
+            return Err(DeserializeError::Invalid);
+        }
+        Ok(total)
+    }
+
+    /// Skips without verifying the checksum.
+    fn skip(buf: &[u8], endian: Endianness) -> Result<usize, DeserializeError> {
+        let end = skip_length_prefixed(buf, LENGTH_SIZE, endian)? + C::SIZE;
+        if buf.len() < end {
+            return Err(DeserializeError::Invalid);
+        }
+        Ok(end)
+    }
+}
@@ -0,0 +1,102 @@
+//! `tokio-util` codecs over the framing in [`frame`](crate::frame), for use with `Framed`,
+//! `FramedRead` and `FramedWrite`.
+
+use std::marker::PhantomData;
+
+use bytes::{Buf, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::frame::{Config, Frame, FrameError};
+use crate::{Deserialize, Serialize};
+
+/// Reads and writes raw frames, keeping their type ids.
+#[derive(Clone, Debug, Default)]
+pub struct FrameCodec {
+    config: Config,
+}
+
+impl FrameCodec {
+    pub fn new(config: Config) -> Self {
+        FrameCodec { config }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+}
+
+impl Decoder for FrameCodec {
+    type Item = Frame;
+    type Error = FrameError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Frame>, FrameError> {
+        match self.config.decode(src)? {
+            Some((frame, size)) => {
+                src.advance(size);
+                Ok(Some(frame))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl Encoder<Frame> for FrameCodec {
+    type Error = FrameError;
+
+    fn encode(&mut self, frame: Frame, dst: &mut BytesMut) -> Result<(), FrameError> {
+        let mut bytes = Vec::new();
+        self.config.encode(&frame, &mut bytes)?;
+        dst.extend_from_slice(&bytes);
+        Ok(())
+    }
+}
+
+/// Reads and writes one `T` per frame, in the config's byte order. Frames are written with type
+/// id 0 and read whatever their type id is.
+#[derive(Debug)]
+pub struct DrybCodec<T> {
+    frames: FrameCodec,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> DrybCodec<T> {
+    pub fn new(config: Config) -> Self {
+        DrybCodec {
+            frames: FrameCodec::new(config),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for DrybCodec<T> {
+    fn default() -> Self {
+        DrybCodec::new(Config::default())
+    }
+}
+
+impl<T> Clone for DrybCodec<T> {
+    fn clone(&self) -> Self {
+        DrybCodec::new(self.frames.config.clone())
+    }
+}
+
+impl<T: Deserialize> Decoder for DrybCodec<T> {
+    type Item = T;
+    type Error = FrameError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>, FrameError> {
+        match self.frames.decode(src)? {
+            Some(frame) => Ok(Some(frame.decode(self.frames.config.endian)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T: Serialize> Encoder<T> for DrybCodec<T> {
+    type Error = FrameError;
+
+    fn encode(&mut self, value: T, dst: &mut BytesMut) -> Result<(), FrameError> {
+        let frame = Frame::new(0, &value, self.frames.config.endian)?;
+        self.frames.encode(frame, dst)
+    }
+}
@@ -0,0 +1,434 @@
+use std::{fmt, fs, path::Path};
+
+use crate::endian::Endianness;
+use crate::schema::{self, Enum, Field, Primitive, Schema, Struct, StructEncoding, Type, Variant};
+
+/// A change between two schemas that breaks decoding in at least one direction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Incompatibility {
+    /// Where the change is, e.g. `Record.statuses[]?`.
+    pub path: String,
+    pub change: Change,
+    pub direction: Direction,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    /// Readers built against the old schema can't decode data written with the new one.
+    Forward,
+    /// Readers built against the new schema can't decode data written with the old one.
+    Backward,
+    Both,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    FieldReordered {
+        name: String,
+        old: usize,
+        new: usize,
+    },
+    FieldAdded(String),
+    FieldRemoved(String),
+    PrimitiveChanged {
+        old: Primitive,
+        new: Primitive,
+    },
+    TypeChanged {
+        old: String,
+        new: String,
+    },
+    ArrayLengthChanged {
+        old: u32,
+        new: u32,
+    },
+    EndiannessChanged {
+        old: Option<Endianness>,
+        new: Option<Endianness>,
+    },
+    EncodingChanged {
+        old: String,
+        new: String,
+    },
+    VersionDecreased {
+        old: u16,
+        new: u16,
+    },
+    VariantAdded(String),
+    VariantRemoved(String),
+    VariantTagChanged {
+        name: String,
+        old: u8,
+        new: u8,
+    },
+}
+
+/// Compares two versions of a type and lists the changes that break the wire format.
+pub fn check(old: &Type, new: &Type) -> Vec<Incompatibility> {
+    let mut checker = Checker { found: Vec::new() };
+    checker.ty(&root_path(old), old, new);
+    checker.found
+}
+
+/// Fails the calling test if `T` can't be read by, or can't read, the schema saved at
+/// `baseline`, or if there is no baseline. With the `DRYB_BLESS=1` environment variable set,
+/// the baseline is written out from `T` instead, creating it if needed.
+pub fn assert_compatible<T: Schema>(baseline: impl AsRef<Path>) {
+    let baseline = baseline.as_ref();
+    let current = T::schema();
+    let bless = std::env::var_os("DRYB_BLESS").is_some_and(|v| v == "1");
+    if bless {
+        fs::write(baseline, schema::export(&current))
+            .unwrap_or_else(|e| panic!("failed to write {}: {}", baseline.display(), e));
+        return;
+    }
+    if !baseline.exists() {
+        panic!(
+            "no schema baseline at {}\nrerun with DRYB_BLESS=1 to create it",
+            baseline.display()
+        );
+    }
+
+    let bytes = fs::read(baseline)
+        .unwrap_or_else(|e| panic!("failed to read {}: {}", baseline.display(), e));
+    let old = schema::import(&bytes)
+        .unwrap_or_else(|e| panic!("failed to parse {}: {}", baseline.display(), e));
+    let found = check(&old, &current);
+    if !found.is_empty() {
+        let report = found
+            .iter()
+            .map(|i| format!("  {}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        panic!(
+            "{} is not wire compatible with {}:\n{}\nrerun with DRYB_BLESS=1 to accept the new schema",
+            std::any::type_name::<T>(),
+            baseline.display(),
+            report
+        );
+    }
+}
+
+struct Checker {
+    found: Vec<Incompatibility>,
+}
+
+impl Checker {
+    fn push(&mut self, path: &str, change: Change, direction: Direction) {
+        self.found.push(Incompatibility {
+            path: path.to_string(),
+            change,
+            direction,
+        });
+    }
+
+    fn ty(&mut self, path: &str, old: &Type, new: &Type) {
+        match (old, new) {
+            (Type::Primitive(old), Type::Primitive(new)) => {
+                if old != new {
+                    let change = Change::PrimitiveChanged {
+                        old: *old,
+                        new: *new,
+                    };
+                    self.push(path, change, Direction::Both);
+                }
+            }
+            (Type::Option(old), Type::Option(new)) => self.ty(&format!("{}?", path), old, new),
+            (Type::Vec { item }, Type::Vec { item: new_item }) => {
+                self.ty(&format!("{}[]", path), item, new_item)
+            }
+            (Type::String, Type::String) => {}
+            (
+                Type::Array { len, item },
+                Type::Array {
+                    len: new_len,
+                    item: new_item,
+                },
+            ) => {
+                if len != new_len {
+                    let change = Change::ArrayLengthChanged {
+                        old: *len,
+                        new: *new_len,
+                    };
+                    self.push(path, change, Direction::Both);
+                }
+                self.ty(&format!("{}[]", path), item, new_item);
+            }
+            (
+                Type::Map { key, value },
+                Type::Map {
+                    key: new_key,
+                    value: new_value,
+                },
+            ) => {
+                self.ty(&format!("{}[key]", path), key, new_key);
+                self.ty(&format!("{}[value]", path), value, new_value);
+            }
+            (Type::Struct(old), Type::Struct(new)) => self.structure(path, old, new),
+            (Type::Enum(old), Type::Enum(new)) => self.enumeration(path, old, new),
+            _ => {
+                let change = Change::TypeChanged {
+                    old: type_name(old),
+                    new: type_name(new),
+                };
+                self.push(path, change, Direction::Both);
+            }
+        }
+    }
+
+    fn endian(&mut self, path: &str, old: Option<Endianness>, new: Option<Endianness>) {
+        if old != new {
+            self.push(
+                path,
+                Change::EndiannessChanged { old, new },
+                Direction::Both,
+            );
+        }
+    }
+
+    fn structure(&mut self, path: &str, old: &Struct, new: &Struct) {
+        self.endian(path, old.endian, new.endian);
+
+        match (old.encoding, new.encoding) {
+            (StructEncoding::Positional, StructEncoding::Positional) => {
+                self.positional(path, &old.fields, &new.fields)
+            }
+            (StructEncoding::Versioned(old_version), StructEncoding::Versioned(new_version)) => {
+                if new_version < old_version {
+                    let change = Change::VersionDecreased {
+                        old: old_version,
+                        new: new_version,
+                    };
+                    self.push(path, change, Direction::Both);
+                }
+                self.versioned(path, old_version, &old.fields, &new.fields);
+            }
+            (StructEncoding::Tagged, StructEncoding::Tagged) => {
+                self.tagged(path, &old.fields, &new.fields)
+            }
+            (old, new) => {
+                let change = Change::EncodingChanged {
+                    old: encoding_name(old),
+                    new: encoding_name(new),
+                };
+                self.push(path, change, Direction::Both);
+            }
+        }
+    }
+
+    /// Compares fields that are decoded one after another with nothing to skip by.
+    fn positional(&mut self, path: &str, old: &[Field], new: &[Field]) {
+        self.common_fields(path, old, new);
+        for field in old.iter().skip(new.len()) {
+            let change = Change::FieldRemoved(field.name.clone());
+            self.push(path, change, Direction::Both);
+        }
+        for field in new.iter().skip(old.len()) {
+            let change = Change::FieldAdded(field.name.clone());
+            self.push(path, change, Direction::Both);
+        }
+    }
+
+    /// Versioned bodies are length prefixed, so new readers skip fields they don't know
+    /// and fill in fields that are newer than the payload.
+    fn versioned(&mut self, path: &str, old_version: u16, old: &[Field], new: &[Field]) {
+        self.common_fields(path, old, new);
+        for field in old.iter().skip(new.len()) {
+            let change = Change::FieldRemoved(field.name.clone());
+            self.push(path, change, Direction::Forward);
+        }
+        for field in new.iter().skip(old.len()) {
+            if field.since.is_none_or(|since| since <= old_version) {
+                let change = Change::FieldAdded(field.name.clone());
+                self.push(path, change, Direction::Backward);
+            }
+        }
+    }
+
+    /// Tagged fields are matched by id; missing ids default and unknown ids are skipped.
+    fn tagged(&mut self, path: &str, old: &[Field], new: &[Field]) {
+        for old_field in old {
+            if let Some(new_field) = new.iter().find(|f| f.id == old_field.id) {
+                self.field(path, old_field, new_field);
+            }
+        }
+    }
+
+    fn common_fields(&mut self, path: &str, old: &[Field], new: &[Field]) {
+        for (index, (old_field, new_field)) in old.iter().zip(new).enumerate() {
+            if old_field.name != new_field.name {
+                if let Some(moved) = new.iter().position(|f| f.name == old_field.name) {
+                    let change = Change::FieldReordered {
+                        name: old_field.name.clone(),
+                        old: index,
+                        new: moved,
+                    };
+                    self.push(path, change, Direction::Both);
+                    continue;
+                }
+            }
+            self.field(path, old_field, new_field);
+        }
+    }
+
+    fn field(&mut self, path: &str, old: &Field, new: &Field) {
+        let path = format!("{}.{}", path, new.name);
+        self.endian(&path, old.endian, new.endian);
+        self.ty(&path, &old.ty, &new.ty);
+    }
+
+    fn enumeration(&mut self, path: &str, old: &Enum, new: &Enum) {
+        self.endian(path, old.endian, new.endian);
+        if old.length_prefixed != new.length_prefixed {
+            let change = Change::EncodingChanged {
+                old: variant_encoding_name(old.length_prefixed).to_string(),
+                new: variant_encoding_name(new.length_prefixed).to_string(),
+            };
+            self.push(path, change, Direction::Both);
+            return;
+        }
+
+        for old_variant in &old.variants {
+            let Some(new_variant) = new.variants.iter().find(|v| v.name == old_variant.name) else {
+                if !catches(new, old_variant) {
+                    let change = Change::VariantRemoved(old_variant.name.clone());
+                    self.push(path, change, Direction::Backward);
+                }
+                continue;
+            };
+
+            let variant_path = format!("{}::{}", path, new_variant.name);
+            if old_variant.tag != new_variant.tag
+                && !(old_variant.captures_tag() && new_variant.captures_tag())
+            {
+                let change = Change::VariantTagChanged {
+                    name: new_variant.name.clone(),
+                    old: old_variant.tag,
+                    new: new_variant.tag,
+                };
+                self.push(path, change, Direction::Both);
+            } else if !new_variant.captures_tag() {
+                self.positional(&variant_path, &old_variant.fields, &new_variant.fields);
+            }
+        }
+
+        for new_variant in &new.variants {
+            if !old.variants.iter().any(|v| v.name == new_variant.name)
+                && !catches(old, new_variant)
+            {
+                let change = Change::VariantAdded(new_variant.name.clone());
+                self.push(path, change, Direction::Forward);
+            }
+        }
+    }
+}
+
+/// Whether a reader of `reader` still decodes `variant` once it no longer knows the tag,
+/// by falling back to its `#[dryb(other)]` variant.
+fn catches(reader: &Enum, variant: &Variant) -> bool {
+    reader.variants.iter().any(|v| v.other)
+        && (variant.fields.is_empty() || variant.captures_tag() || reader.length_prefixed)
+}
+
+fn root_path(ty: &Type) -> String {
+    match ty {
+        Type::Struct(s) => s.name.clone(),
+        Type::Enum(e) => e.name.clone(),
+        _ => String::new(),
+    }
+}
+
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Primitive(primitive) => primitive.name().to_string(),
+        Type::Option(_) => "option".to_string(),
+        Type::Vec { .. } => "vec".to_string(),
+        Type::String => "string".to_string(),
+        Type::Array { len, .. } => format!("array of {}", len),
+        Type::Struct(s) => format!("struct {}", s.name),
+        Type::Enum(e) => format!("enum {}", e.name),
+        Type::Map { .. } => "map".to_string(),
+    }
+}
+
+fn encoding_name(encoding: StructEncoding) -> String {
+    match encoding {
+        StructEncoding::Positional => "positional".to_string(),
+        StructEncoding::Versioned(version) => format!("version {}", version),
+        StructEncoding::Tagged => "tagged".to_string(),
+    }
+}
+
+fn variant_encoding_name(length_prefixed: bool) -> &'static str {
+    if length_prefixed {
+        "length prefixed variants"
+    } else {
+        "plain variants"
+    }
+}
+
+fn endian_name(endian: Option<Endianness>) -> &'static str {
+    match endian {
+        None => "inherited",
+        Some(Endianness::Little) => "little",
+        Some(Endianness::Big) => "big",
+        Some(Endianness::Native) => "native",
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::FieldReordered { name, old, new } => {
+                write!(f, "field `{}` moved from position {} to {}", name, old, new)
+            }
+            Change::FieldAdded(name) => write!(f, "field `{}` added", name),
+            Change::FieldRemoved(name) => write!(f, "field `{}` removed", name),
+            Change::PrimitiveChanged { old, new } => write!(
+                f,
+                "{} ({} bytes) changed to {} ({} bytes)",
+                old.name(),
+                old.width(),
+                new.name(),
+                new.width()
+            ),
+            Change::TypeChanged { old, new } => write!(f, "{} changed to {}", old, new),
+            Change::ArrayLengthChanged { old, new } => {
+                write!(f, "array length changed from {} to {}", old, new)
+            }
+            Change::EndiannessChanged { old, new } => write!(
+                f,
+                "endianness changed from {} to {}",
+                endian_name(*old),
+                endian_name(*new)
+            ),
+            Change::EncodingChanged { old, new } => {
+                write!(f, "encoding changed from {} to {}", old, new)
+            }
+            Change::VersionDecreased { old, new } => {
+                write!(f, "version decreased from {} to {}", old, new)
+            }
+            Change::VariantAdded(name) => write!(f, "variant `{}` added", name),
+            Change::VariantRemoved(name) => write!(f, "variant `{}` removed", name),
+            Change::VariantTagChanged { name, old, new } => {
+                write!(f, "variant `{}` tag changed from {} to {}", name, old, new)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::Forward => write!(f, "old readers can't read new data"),
+            Direction::Backward => write!(f, "new readers can't read old data"),
+            Direction::Both => write!(f, "breaks old and new readers"),
+        }
+    }
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({})", self.path, self.change, self.direction)
+    }
+}
@@ -1,11 +1,264 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::mem::MaybeUninit;
+
 use crate::endian::Endianness;
 use crate::error::DeserializeError;
 
 pub trait Deserialize: Sized {
+    /// The encoded size of every value of the type, for types whose size doesn't depend on the
+    /// value.
+    const FIXED_SIZE: Option<usize> = None;
+
     fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError>;
+
+    /// Returns the size of the value at the start of `buf` without decoding it. Only lengths and
+    /// tags are checked, so a `String` holding invalid UTF-8 is skipped like any other.
+    fn skip(buf: &[u8], endian: Endianness) -> Result<usize, DeserializeError> {
+        match Self::FIXED_SIZE {
+            Some(size) if buf.len() < size => Err(DeserializeError::Invalid),
+            Some(size) => Ok(size),
+            None => Self::deserialize(buf, endian).map(|(_, size)| size),
+        }
+    }
+
+    /// Decodes into `self`, reusing the allocations it already owns where it can, and returns
+    /// the number of bytes read. After an error `self` holds a mix of old and new values.
+    fn deserialize_in_place(
+        &mut self,
+        buf: &[u8],
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        let (value, size) = Self::deserialize(buf, endian)?;
+        *self = value;
+        Ok(size)
+    }
+
+    /// Fills `slots` with values laid out one after another and returns the number of bytes
+    /// read. `Vec` and arrays decode fixed-size elements through this, and the primitives
+    /// override it to convert the whole run in one pass.
+    fn deserialize_slots(
+        slots: &mut Slots<'_, Self>,
+        buf: &[u8],
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        let mut offset = 0;
+        while slots.remaining() > 0 {
+            let (value, size) = Self::deserialize(&buf[offset..], endian)?;
+            slots.push(value);
+            offset += size;
+        }
+        Ok(offset)
+    }
+}
+
+/// Uninitialized room for values that `Deserialize::deserialize_slots` fills front to back.
+/// Only the values actually pushed count, and they are dropped if the run isn't completed.
+pub struct Slots<'a, T> {
+    out: &'a mut [MaybeUninit<T>],
+    filled: usize,
+}
+
+impl<'a, T> Slots<'a, T> {
+    fn new(out: &'a mut [MaybeUninit<T>]) -> Self {
+        Slots { out, filled: 0 }
+    }
+
+    /// How many slots are still empty.
+    pub fn remaining(&self) -> usize {
+        self.out.len() - self.filled
+    }
+
+    /// Fills the next slot. Panics if there is none left.
+    pub fn push(&mut self, value: T) {
+        self.out[self.filled].write(value);
+        self.filled += 1;
+    }
+
+    /// The empty slots, for a primitive to convert a whole run into.
+    fn rest(&mut self) -> &mut [MaybeUninit<T>] {
+        &mut self.out[self.filled..]
+    }
+
+    /// Counts the first `count` slots of `rest` as filled.
+    ///
+    /// # Safety
+    ///
+    /// Those slots must have been written.
+    unsafe fn assume_filled(&mut self, count: usize) {
+        debug_assert!(count <= self.remaining());
+        self.filled += count;
+    }
+
+    /// Hands the values over to the caller if every slot was filled, and drops them otherwise.
+    fn finish(self) -> bool {
+        if self.remaining() > 0 {
+            return false;
+        }
+        std::mem::forget(self);
+        true
+    }
+}
+
+impl<T> Drop for Slots<'_, T> {
+    fn drop(&mut self) {
+        for slot in &mut self.out[..self.filled] {
+            // SAFETY: `push` and `assume_filled` only count slots that were written.
+            unsafe { slot.assume_init_drop() };
+        }
+    }
+}
+
+/// `deserialize_slots` for a multi-byte primitive: one bounds check, then a loop over the whole
+/// run that the compiler can vectorise.
+macro_rules! deserialize_slots {
+    () => {
+        fn deserialize_slots(
+            slots: &mut Slots<'_, Self>,
+            buf: &[u8],
+            endian: Endianness,
+        ) -> Result<usize, DeserializeError> {
+            let out = slots.rest();
+            let count = out.len();
+            let width = std::mem::size_of::<Self>();
+            let size = count * width;
+            if buf.len() < size {
+                return Err(DeserializeError::Invalid);
+            }
+
+            let chunks = out.iter_mut().zip(buf[..size].chunks_exact(width));
+            match endian {
+                Endianness::Little => chunks.for_each(|(slot, chunk)| {
+                    slot.write(Self::from_le_bytes(chunk.try_into().unwrap()));
+                }),
+                Endianness::Big => chunks.for_each(|(slot, chunk)| {
+                    slot.write(Self::from_be_bytes(chunk.try_into().unwrap()));
+                }),
+                Endianness::Native => chunks.for_each(|(slot, chunk)| {
+                    slot.write(Self::from_ne_bytes(chunk.try_into().unwrap()));
+                }),
+            }
+            // SAFETY: `buf` held all `count` values, so the loop wrote every slot.
+            unsafe { slots.assume_filled(count) };
+            Ok(size)
+        }
+    };
+}
+
+/// `deserialize_slots` for `u8` and `i8`: a single copy of the whole run.
+macro_rules! deserialize_bytes {
+    () => {
+        fn deserialize_slots(
+            slots: &mut Slots<'_, Self>,
+            buf: &[u8],
+            _: Endianness,
+        ) -> Result<usize, DeserializeError> {
+            let out = slots.rest();
+            let count = out.len();
+            if buf.len() < count {
+                return Err(DeserializeError::Invalid);
+            }
+
+            // SAFETY: `Self` is `u8` or `i8`, one byte wide and valid for every bit pattern,
+            // `buf` holds at least `count` bytes, and a shared and a mutable borrow can't
+            // overlap. The copy writes every slot of `out`.
+            unsafe {
+                std::ptr::copy_nonoverlapping(buf.as_ptr(), out.as_mut_ptr().cast::<u8>(), count);
+                slots.assume_filled(count);
+            }
+            Ok(count)
+        }
+    };
+}
+
+/// Appends `length` fixed-size values to `vec` through `deserialize_slots`, after checking that
+/// `buf` is long enough to hold them, so a bogus length can't cause a huge allocation.
+fn extend_fixed<T: Deserialize>(
+    vec: &mut Vec<T>,
+    length: usize,
+    size: usize,
+    buf: &[u8],
+    endian: Endianness,
+) -> Result<usize, DeserializeError> {
+    if length
+        .checked_mul(size)
+        .is_none_or(|total| buf.len() < total)
+    {
+        return Err(DeserializeError::Invalid);
+    }
+
+    vec.reserve(length);
+    let mut slots = Slots::new(&mut vec.spare_capacity_mut()[..length]);
+    let read = T::deserialize_slots(&mut slots, buf, endian)?;
+    if !slots.finish() {
+        return Err(DeserializeError::Invalid);
+    }
+    // SAFETY: `finish` checked that all `length` slots were filled and left them to the `Vec`.
+    unsafe { vec.set_len(vec.len() + length) };
+    Ok(read)
+}
+
+/// The `FIXED_SIZE` of a value made of parts laid out one after another: their sum, if every
+/// part has a fixed size.
+pub const fn fixed_size_sum(sizes: &[Option<usize>]) -> Option<usize> {
+    let mut total = 0;
+    let mut i = 0;
+    while i < sizes.len() {
+        match sizes[i] {
+            Some(size) => total += size,
+            None => return None,
+        }
+        i += 1;
+    }
+    Some(total)
+}
+
+/// Skips `count` values starting at `offset`, with arithmetic alone when they have a fixed size.
+fn skip_elements(
+    buf: &[u8],
+    mut offset: usize,
+    count: usize,
+    fixed_size: Option<usize>,
+    skip: impl Fn(&[u8]) -> Result<usize, DeserializeError>,
+) -> Result<usize, DeserializeError> {
+    if let Some(size) = fixed_size {
+        let end = count
+            .checked_mul(size)
+            .and_then(|size| size.checked_add(offset))
+            .ok_or(DeserializeError::Invalid)?;
+        if buf.len() < end {
+            return Err(DeserializeError::Invalid);
+        }
+        return Ok(end);
+    }
+
+    for _ in 0..count {
+        offset += skip(&buf[offset..])?;
+    }
+    Ok(offset)
+}
+
+/// Skips a value made of a `header`-byte header, ending in a `u32` length, and that many bytes.
+#[doc(hidden)]
+pub fn skip_length_prefixed(
+    buf: &[u8],
+    header: usize,
+    endian: Endianness,
+) -> Result<usize, DeserializeError> {
+    if buf.len() < header {
+        return Err(DeserializeError::Invalid);
+    }
+    let (length, _) = u32::deserialize(&buf[header - 4..], endian)?;
+    let end = header + length as usize;
+    if buf.len() < end {
+        return Err(DeserializeError::Invalid);
+    }
+    Ok(end)
 }
 
 impl Deserialize for u8 {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
     fn deserialize(buf: &[u8], _: Endianness) -> Result<(Self, usize), DeserializeError> {
         if buf.is_empty() {
             return Err(DeserializeError::Invalid);
@@ -13,9 +266,13 @@ impl Deserialize for u8 {
 
         Ok((buf[0], 1))
     }
+
+    deserialize_bytes!();
 }
 
 impl Deserialize for i8 {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
     fn deserialize(buf: &[u8], _: Endianness) -> Result<(Self, usize), DeserializeError> {
         if buf.is_empty() {
             return Err(DeserializeError::Invalid);
@@ -23,9 +280,13 @@ impl Deserialize for i8 {
 
         Ok((buf[0] as i8, 1))
     }
+
+    deserialize_bytes!();
 }
 
 impl Deserialize for u16 {
+    const FIXED_SIZE: Option<usize> = Some(2);
+
     fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
         if buf.len() < 2 {
             return Err(DeserializeError::Invalid);
@@ -34,13 +295,18 @@ impl Deserialize for u16 {
         let value = match endian {
             Endianness::Little => u16::from_le_bytes([buf[0], buf[1]]),
             Endianness::Big => u16::from_be_bytes([buf[0], buf[1]]),
+            Endianness::Native => u16::from_ne_bytes([buf[0], buf[1]]),
         };
 
         Ok((value, 2))
     }
+
+    deserialize_slots!();
 }
 
 impl Deserialize for i16 {
+    const FIXED_SIZE: Option<usize> = Some(2);
+
     fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
         if buf.len() < 2 {
             return Err(DeserializeError::Invalid);
@@ -49,13 +315,18 @@ impl Deserialize for i16 {
         let value = match endian {
             Endianness::Little => i16::from_le_bytes([buf[0], buf[1]]),
             Endianness::Big => i16::from_be_bytes([buf[0], buf[1]]),
+            Endianness::Native => i16::from_ne_bytes([buf[0], buf[1]]),
         };
 
         Ok((value, 2))
     }
+
+    deserialize_slots!();
 }
 
 impl Deserialize for u32 {
+    const FIXED_SIZE: Option<usize> = Some(4);
+
     fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
         if buf.len() < 4 {
             return Err(DeserializeError::Invalid);
@@ -64,13 +335,18 @@ impl Deserialize for u32 {
         let value = match endian {
             Endianness::Little => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]),
             Endianness::Big => u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]),
+            Endianness::Native => u32::from_ne_bytes([buf[0], buf[1], buf[2], buf[3]]),
         };
 
         Ok((value, 4))
     }
+
+    deserialize_slots!();
 }
 
 impl Deserialize for i32 {
+    const FIXED_SIZE: Option<usize> = Some(4);
+
     fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
         if buf.len() < 4 {
             return Err(DeserializeError::Invalid);
@@ -79,13 +355,18 @@ impl Deserialize for i32 {
         let value = match endian {
             Endianness::Little => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]),
             Endianness::Big => i32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]),
+            Endianness::Native => i32::from_ne_bytes([buf[0], buf[1], buf[2], buf[3]]),
         };
 
         Ok((value, 4))
     }
+
+    deserialize_slots!();
 }
 
 impl Deserialize for u64 {
+    const FIXED_SIZE: Option<usize> = Some(8);
+
     fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
         if buf.len() < 8 {
             return Err(DeserializeError::Invalid);
@@ -98,13 +379,20 @@ impl Deserialize for u64 {
             Endianness::Big => u64::from_be_bytes([
                 buf[0], buf[1], buf[2], buf[3], buf[4], buf[5], buf[6], buf[7],
             ]),
+            Endianness::Native => u64::from_ne_bytes([
+                buf[0], buf[1], buf[2], buf[3], buf[4], buf[5], buf[6], buf[7],
+            ]),
         };
 
         Ok((value, 8))
     }
+
+    deserialize_slots!();
 }
 
 impl Deserialize for i64 {
+    const FIXED_SIZE: Option<usize> = Some(8);
+
     fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
         if buf.len() < 8 {
             return Err(DeserializeError::Invalid);
@@ -117,13 +405,20 @@ impl Deserialize for i64 {
             Endianness::Big => i64::from_be_bytes([
                 buf[0], buf[1], buf[2], buf[3], buf[4], buf[5], buf[6], buf[7],
             ]),
+            Endianness::Native => i64::from_ne_bytes([
+                buf[0], buf[1], buf[2], buf[3], buf[4], buf[5], buf[6], buf[7],
+            ]),
         };
 
         Ok((value, 8))
     }
+
+    deserialize_slots!();
 }
 
 impl Deserialize for f32 {
+    const FIXED_SIZE: Option<usize> = Some(4);
+
     fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
         if buf.len() < 4 {
             return Err(DeserializeError::Invalid);
@@ -132,13 +427,18 @@ impl Deserialize for f32 {
         let value = match endian {
             Endianness::Little => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]),
             Endianness::Big => f32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]),
+            Endianness::Native => f32::from_ne_bytes([buf[0], buf[1], buf[2], buf[3]]),
         };
 
         Ok((value, 4))
     }
+
+    deserialize_slots!();
 }
 
 impl Deserialize for f64 {
+    const FIXED_SIZE: Option<usize> = Some(8);
+
     fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
         if buf.len() < 8 {
             return Err(DeserializeError::Invalid);
@@ -151,13 +451,20 @@ impl Deserialize for f64 {
             Endianness::Big => f64::from_be_bytes([
                 buf[0], buf[1], buf[2], buf[3], buf[4], buf[5], buf[6], buf[7],
             ]),
+            Endianness::Native => f64::from_ne_bytes([
+                buf[0], buf[1], buf[2], buf[3], buf[4], buf[5], buf[6], buf[7],
+            ]),
         };
 
         Ok((value, 8))
     }
+
+    deserialize_slots!();
 }
 
 impl Deserialize for bool {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
     fn deserialize(buf: &[u8], _: Endianness) -> Result<(Self, usize), DeserializeError> {
         if buf.is_empty() {
             return Err(DeserializeError::Invalid);
@@ -182,6 +489,29 @@ impl<T: Deserialize> Deserialize for Option<T> {
             _ => Err(DeserializeError::Invalid),
         }
     }
+
+    fn deserialize_in_place(
+        &mut self,
+        buf: &[u8],
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        match (buf.first(), self.as_mut()) {
+            (Some(1), Some(value)) => Ok(1 + value.deserialize_in_place(&buf[1..], endian)?),
+            _ => {
+                let (value, size) = Self::deserialize(buf, endian)?;
+                *self = value;
+                Ok(size)
+            }
+        }
+    }
+
+    fn skip(buf: &[u8], endian: Endianness) -> Result<usize, DeserializeError> {
+        match buf.first() {
+            Some(0) => Ok(1),
+            Some(1) => Ok(1 + T::skip(&buf[1..], endian)?),
+            _ => Err(DeserializeError::Invalid),
+        }
+    }
 }
 
 const VEC_LENGTH_SIZE: usize = 4;
@@ -192,6 +522,12 @@ impl<T: Deserialize> Deserialize for Vec<T> {
         }
 
         let length = u32::deserialize(&buf[..VEC_LENGTH_SIZE], endian)?.0 as usize;
+        if let Some(size) = T::FIXED_SIZE {
+            let mut vec = Vec::new();
+            let read = extend_fixed(&mut vec, length, size, &buf[VEC_LENGTH_SIZE..], endian)?;
+            return Ok((vec, read + VEC_LENGTH_SIZE));
+        }
+
         let mut vec = Vec::with_capacity(length); // TODO: think about performance if length is
                                                   // huge & payload is invalid
         let mut offset = 0;
@@ -203,6 +539,147 @@ impl<T: Deserialize> Deserialize for Vec<T> {
 
         Ok((vec, offset + VEC_LENGTH_SIZE))
     }
+
+    fn deserialize_in_place(
+        &mut self,
+        buf: &[u8],
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        let (length, mut offset) = u32::deserialize(buf, endian)?;
+        let length = length as usize;
+        if let Some(size) = T::FIXED_SIZE {
+            self.clear();
+            return Ok(offset + extend_fixed(self, length, size, &buf[offset..], endian)?);
+        }
+
+        self.truncate(length);
+        for value in self.iter_mut() {
+            offset += value.deserialize_in_place(&buf[offset..], endian)?;
+        }
+        while self.len() < length {
+            let (value, size) = T::deserialize(&buf[offset..], endian)?;
+            self.push(value);
+            offset += size;
+        }
+
+        Ok(offset)
+    }
+
+    fn skip(buf: &[u8], endian: Endianness) -> Result<usize, DeserializeError> {
+        let (length, offset) = u32::deserialize(buf, endian)?;
+        skip_elements(buf, offset, length as usize, T::FIXED_SIZE, |buf| {
+            T::skip(buf, endian)
+        })
+    }
+}
+
+fn skip_entries<K: Deserialize, V: Deserialize>(
+    buf: &[u8],
+    endian: Endianness,
+) -> Result<usize, DeserializeError> {
+    let (length, offset) = u32::deserialize(buf, endian)?;
+    let entry_size = fixed_size_sum(&[K::FIXED_SIZE, V::FIXED_SIZE]);
+    skip_elements(buf, offset, length as usize, entry_size, |buf| {
+        let size = K::skip(buf, endian)?;
+        Ok(size + V::skip(&buf[size..], endian)?)
+    })
+}
+
+impl<K: Deserialize + Eq + Hash, V: Deserialize> Deserialize for HashMap<K, V> {
+    fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
+        let (length, mut offset) = u32::deserialize(buf, endian)?;
+        let mut map = HashMap::new();
+        for _ in 0..length {
+            let (key, size) = K::deserialize(&buf[offset..], endian)?;
+            offset += size;
+            let (value, size) = V::deserialize(&buf[offset..], endian)?;
+            offset += size;
+            map.insert(key, value);
+        }
+
+        Ok((map, offset))
+    }
+
+    fn deserialize_in_place(
+        &mut self,
+        buf: &[u8],
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        let (length, mut offset) = u32::deserialize(buf, endian)?;
+        self.clear();
+        for _ in 0..length {
+            let (key, size) = K::deserialize(&buf[offset..], endian)?;
+            offset += size;
+            let (value, size) = V::deserialize(&buf[offset..], endian)?;
+            offset += size;
+            self.insert(key, value);
+        }
+
+        Ok(offset)
+    }
+
+    fn skip(buf: &[u8], endian: Endianness) -> Result<usize, DeserializeError> {
+        skip_entries::<K, V>(buf, endian)
+    }
+}
+
+impl<K: Deserialize + Ord, V: Deserialize> Deserialize for BTreeMap<K, V> {
+    fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
+        let (length, mut offset) = u32::deserialize(buf, endian)?;
+        let mut map = BTreeMap::new();
+        for _ in 0..length {
+            let (key, size) = K::deserialize(&buf[offset..], endian)?;
+            offset += size;
+            let (value, size) = V::deserialize(&buf[offset..], endian)?;
+            offset += size;
+            map.insert(key, value);
+        }
+
+        Ok((map, offset))
+    }
+
+    fn deserialize_in_place(
+        &mut self,
+        buf: &[u8],
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        let (length, mut offset) = u32::deserialize(buf, endian)?;
+        self.clear();
+        for _ in 0..length {
+            let (key, size) = K::deserialize(&buf[offset..], endian)?;
+            offset += size;
+            let (value, size) = V::deserialize(&buf[offset..], endian)?;
+            offset += size;
+            self.insert(key, value);
+        }
+
+        Ok(offset)
+    }
+
+    fn skip(buf: &[u8], endian: Endianness) -> Result<usize, DeserializeError> {
+        skip_entries::<K, V>(buf, endian)
+    }
+}
+
+impl<T: Deserialize> Deserialize for Box<T> {
+    const FIXED_SIZE: Option<usize> = T::FIXED_SIZE;
+
+    fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
+        let (value, size) = T::deserialize(buf, endian)?;
+        Ok((Box::new(value), size))
+    }
+
+    fn deserialize_in_place(
+        &mut self,
+        buf: &[u8],
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        self.as_mut().deserialize_in_place(buf, endian)
+    }
+
+    fn skip(buf: &[u8], endian: Endianness) -> Result<usize, DeserializeError> {
+        T::skip(buf, endian)
+    }
 }
 
 impl Deserialize for String {
@@ -213,24 +690,103 @@ impl Deserialize for String {
             size,
         ))
     }
+
+    fn deserialize_in_place(
+        &mut self,
+        buf: &[u8],
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        let (value, size) = <&str>::deserialize_borrowed(buf, endian)?;
+        self.clear();
+        self.push_str(value);
+        Ok(size)
+    }
+
+    fn skip(buf: &[u8], endian: Endianness) -> Result<usize, DeserializeError> {
+        Vec::<u8>::skip(buf, endian)
+    }
 }
 
 impl<T: Deserialize, const N: usize> Deserialize for [T; N] {
-    fn deserialize(buffer: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
-        let mut result = std::mem::MaybeUninit::<[T; N]>::uninit();
-        let mut total_size = 0;
+    const FIXED_SIZE: Option<usize> = match T::FIXED_SIZE {
+        Some(size) => Some(size * N),
+        None => None,
+    };
 
-        for i in 0..N {
-            let (item, size) = T::deserialize(&buffer[total_size..], endian)?;
-            // SAFETY: We're writing to the i-th element, which is within bounds.
-            unsafe {
-                result.as_mut_ptr().cast::<T>().add(i).write(item);
-            }
-            total_size += size;
+    fn deserialize(buffer: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
+        let mut result = MaybeUninit::<[T; N]>::uninit();
+        // SAFETY: `MaybeUninit<[T; N]>` and `[MaybeUninit<T>; N]` have the same layout.
+        let mut slots =
+            Slots::new(unsafe { &mut *result.as_mut_ptr().cast::<[MaybeUninit<T>; N]>() });
+        let total_size = T::deserialize_slots(&mut slots, buffer, endian)?;
+        if !slots.finish() {
+            return Err(DeserializeError::Invalid);
         }
 
-        // SAFETY: All elements have been initialized.
+        // SAFETY: `finish` checked that all `N` elements were filled and left them in `result`.
         let result = unsafe { result.assume_init() };
         Ok((result, total_size))
     }
+
+    fn deserialize_in_place(
+        &mut self,
+        buf: &[u8],
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        let mut offset = 0;
+        for value in self.iter_mut() {
+            offset += value.deserialize_in_place(&buf[offset..], endian)?;
+        }
+        Ok(offset)
+    }
+
+    fn skip(buf: &[u8], endian: Endianness) -> Result<usize, DeserializeError> {
+        skip_elements(buf, 0, N, T::FIXED_SIZE, |buf| T::skip(buf, endian))
+    }
+}
+
+/// Decoding that may borrow from the buffer, so strings and byte arrays aren't copied. Every
+/// `Deserialize` type implements it by decoding an owned value.
+pub trait DeserializeBorrowed<'de>: Sized {
+    fn deserialize_borrowed(
+        buf: &'de [u8],
+        endian: Endianness,
+    ) -> Result<(Self, usize), DeserializeError>;
+}
+
+impl<'de, T: Deserialize> DeserializeBorrowed<'de> for T {
+    fn deserialize_borrowed(
+        buf: &'de [u8],
+        endian: Endianness,
+    ) -> Result<(Self, usize), DeserializeError> {
+        T::deserialize(buf, endian)
+    }
+}
+
+impl<'de> DeserializeBorrowed<'de> for &'de [u8] {
+    fn deserialize_borrowed(
+        buf: &'de [u8],
+        endian: Endianness,
+    ) -> Result<(Self, usize), DeserializeError> {
+        let (length, offset) = u32::deserialize(buf, endian)?;
+        let end = offset + length as usize;
+        if buf.len() < end {
+            return Err(DeserializeError::Invalid);
+        }
+
+        Ok((&buf[offset..end], end))
+    }
+}
+
+impl<'de> DeserializeBorrowed<'de> for &'de str {
+    fn deserialize_borrowed(
+        buf: &'de [u8],
+        endian: Endianness,
+    ) -> Result<(Self, usize), DeserializeError> {
+        let (bytes, size) = <&[u8]>::deserialize_borrowed(buf, endian)?;
+        Ok((
+            std::str::from_utf8(bytes).map_err(|_| DeserializeError::Invalid)?,
+            size,
+        ))
+    }
 }
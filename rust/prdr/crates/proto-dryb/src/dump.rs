@@ -0,0 +1,563 @@
+use std::fmt::Write;
+
+use crate::deserialize::Deserialize;
+use crate::endian::Endianness;
+use crate::error::{DeserializeError, SerializeError};
+use crate::schema::{Enum, Field, Schema, Struct, StructEncoding, Type};
+use crate::serialize::Serialize;
+use crate::value::Value;
+
+/// Raw bytes shown per line before the rest is elided.
+const HEX_BYTES_PER_LINE: usize = 16;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Style {
+    #[default]
+    Plain,
+    /// Colours offsets, paths, values and errors with ANSI escape codes.
+    Ansi,
+}
+
+/// Renders `buf` as an annotated tree of the fields `ty` describes, one line per field
+/// with its offset, length, raw bytes and decoded value. Decoding errors are shown
+/// inline at the offset where they happen, so a broken payload still dumps up to there.
+pub fn dump(ty: &Type, buf: &[u8], endian: Endianness, style: Style) -> String {
+    let mut walker = Walker {
+        buf,
+        lines: Vec::new(),
+    };
+    if let Ok(size) = walker.walk(ty, "", 0, 0, buf.len(), endian) {
+        if size < buf.len() {
+            walker.push(
+                Kind::Meta,
+                size,
+                Some(buf.len() - size),
+                0,
+                "[trailing bytes]",
+            );
+        }
+    }
+
+    walker.render(style)
+}
+
+/// Serializes `value` and dumps the result, for use in failing test messages.
+pub fn dump_value<T: Schema + Serialize>(value: &T, endian: Endianness, style: Style) -> String {
+    let mut buffer = vec![0u8; 256];
+    loop {
+        match value.serialize(&mut buffer, endian) {
+            Ok(size) => return dump(&T::schema(), &buffer[..size], endian, style),
+            Err(SerializeError::BufferOverflow) => buffer.resize(buffer.len() * 2, 0),
+            Err(error) => return format!("failed to serialize: {}", error),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    /// A value with children, whose length is filled in once they're walked.
+    Group,
+    Leaf,
+    /// Framing that isn't a field: length prefixes, headers, tags.
+    Meta,
+    Error,
+}
+
+struct Line {
+    kind: Kind,
+    offset: usize,
+    len: Option<usize>,
+    depth: usize,
+    label: String,
+}
+
+struct Walker<'a> {
+    buf: &'a [u8],
+    lines: Vec<Line>,
+}
+
+impl Walker<'_> {
+    fn push(
+        &mut self,
+        kind: Kind,
+        offset: usize,
+        len: Option<usize>,
+        depth: usize,
+        label: impl Into<String>,
+    ) -> usize {
+        self.lines.push(Line {
+            kind,
+            offset,
+            len,
+            depth,
+            label: label.into(),
+        });
+        self.lines.len() - 1
+    }
+
+    fn fail(&mut self, offset: usize, depth: usize, path: &str, error: &str) -> DeserializeError {
+        let label = if path.is_empty() {
+            format!("error: {}", error)
+        } else {
+            format!("error: {}: {}", path, error)
+        };
+        self.push(Kind::Error, offset, None, depth, label);
+        DeserializeError::Invalid
+    }
+
+    /// Walks one value starting at `offset` that must end by `end`, returning its size.
+    fn walk(
+        &mut self,
+        ty: &Type,
+        path: &str,
+        depth: usize,
+        offset: usize,
+        end: usize,
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        match ty {
+            Type::Primitive(_) | Type::String => {
+                match Value::decode(ty, &self.buf[offset..end], endian) {
+                    Ok((value, size)) => {
+                        let label = format!("{}: {}", display_path(path), format_value(&value));
+                        self.push(Kind::Leaf, offset, Some(size), depth, label);
+                        Ok(size)
+                    }
+                    Err(_) => Err(self.fail(offset, depth, path, &expected(ty))),
+                }
+            }
+            Type::Option(item) => match self.buf[offset..end].first() {
+                Some(0) => {
+                    let label = format!("{}: None", display_path(path));
+                    self.push(Kind::Leaf, offset, Some(1), depth, label);
+                    Ok(1)
+                }
+                Some(1) => {
+                    let label = format!("[{}: Some]", display_path(path));
+                    self.push(Kind::Meta, offset, Some(1), depth, label);
+                    Ok(1 + self.walk(item, path, depth, offset + 1, end, endian)?)
+                }
+                Some(flag) => {
+                    let error = format!("invalid option flag {}", flag);
+                    Err(self.fail(offset, depth, path, &error))
+                }
+                None => Err(self.fail(offset, depth, path, "expected an option flag")),
+            },
+            Type::Vec { item } => {
+                let group = self.push(Kind::Group, offset, None, depth, display_path(path));
+                let (length, mut size) = self.length(path, depth + 1, offset, end, endian)?;
+                for i in 0..length {
+                    let item_path = format!("{}[{}]", path, i);
+                    size += self.walk(item, &item_path, depth + 1, offset + size, end, endian)?;
+                }
+
+                self.close(
+                    group,
+                    size,
+                    format!("{}: {} items", display_path(path), length),
+                );
+                Ok(size)
+            }
+            Type::Array { len, item } => {
+                let group = self.push(Kind::Group, offset, None, depth, display_path(path));
+                let mut size = 0;
+                for i in 0..*len {
+                    let item_path = format!("{}[{}]", path, i);
+                    size += self.walk(item, &item_path, depth + 1, offset + size, end, endian)?;
+                }
+
+                self.close(group, size, format!("{}: [{}]", display_path(path), len));
+                Ok(size)
+            }
+            Type::Map { key, value } => {
+                let group = self.push(Kind::Group, offset, None, depth, display_path(path));
+                let (length, mut size) = self.length(path, depth + 1, offset, end, endian)?;
+                for i in 0..length {
+                    let key_path = format!("{}[{}].key", path, i);
+                    size += self.walk(key, &key_path, depth + 1, offset + size, end, endian)?;
+                    let value_path = format!("{}[{}].value", path, i);
+                    size += self.walk(value, &value_path, depth + 1, offset + size, end, endian)?;
+                }
+
+                self.close(
+                    group,
+                    size,
+                    format!("{}: {} entries", display_path(path), length),
+                );
+                Ok(size)
+            }
+            Type::Struct(s) => self.structure(s, path, depth, offset, end, endian),
+            Type::Enum(e) => self.enumeration(e, path, depth, offset, end, endian),
+        }
+    }
+
+    fn close(&mut self, group: usize, size: usize, label: String) {
+        self.lines[group].len = Some(size);
+        self.lines[group].label = label;
+    }
+
+    fn length(
+        &mut self,
+        path: &str,
+        depth: usize,
+        offset: usize,
+        end: usize,
+        endian: Endianness,
+    ) -> Result<(usize, usize), DeserializeError> {
+        match u32::deserialize(&self.buf[offset..end], endian) {
+            Ok((length, size)) => {
+                let length = length as usize;
+                self.push(
+                    Kind::Meta,
+                    offset,
+                    Some(size),
+                    depth,
+                    format!("[length: {}]", length),
+                );
+                Ok((length, size))
+            }
+            Err(_) => Err(self.fail(offset, depth, path, "expected a u32 length")),
+        }
+    }
+
+    fn u32_header(
+        &mut self,
+        path: &str,
+        depth: usize,
+        offset: usize,
+        end: usize,
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        match u32::deserialize(&self.buf[offset.min(end)..end], endian) {
+            Ok((length, _)) => Ok(length as usize),
+            Err(_) => Err(self.fail(offset, depth, path, "expected a u32 length")),
+        }
+    }
+
+    fn fields(
+        &mut self,
+        fields: &[Field],
+        path: &str,
+        depth: usize,
+        offset: usize,
+        end: usize,
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        let mut size = 0;
+        for field in fields {
+            let field_path = join(path, &field.name);
+            let field_endian = field.endian.unwrap_or(endian);
+            size += self.walk(
+                &field.ty,
+                &field_path,
+                depth,
+                offset + size,
+                end,
+                field_endian,
+            )?;
+        }
+        Ok(size)
+    }
+
+    fn structure(
+        &mut self,
+        s: &Struct,
+        path: &str,
+        depth: usize,
+        offset: usize,
+        end: usize,
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        let endian = s.endian.unwrap_or(endian);
+        let label = if path.is_empty() {
+            s.name.clone()
+        } else {
+            format!("{}: {}", path, s.name)
+        };
+        let group = self.push(Kind::Group, offset, None, depth, label);
+        let depth = depth + 1;
+
+        let size = match s.encoding {
+            StructEncoding::Positional => {
+                self.fields(&s.fields, path, depth, offset, end, endian)?
+            }
+            StructEncoding::Versioned(_) => {
+                let version = match u16::deserialize(&self.buf[offset..end], endian) {
+                    Ok((version, _)) => version,
+                    Err(_) => return Err(self.fail(offset, depth, path, "expected a version")),
+                };
+                let length = self.u32_header(path, depth, offset + 2, end, endian)?;
+                let body_end = offset + 6 + length;
+                if body_end > end {
+                    let error = format!("body of {} bytes overruns the buffer", length);
+                    return Err(self.fail(offset, depth, path, &error));
+                }
+                let header = format!("[version: {}, body: {} bytes]", version, length);
+                self.push(Kind::Meta, offset, Some(6), depth, header);
+
+                let mut size = 6;
+                for field in &s.fields {
+                    let field_path = join(path, &field.name);
+                    if let Some(since) = field.since.filter(|since| version < *since) {
+                        let label = format!("[{}: absent before version {}]", field_path, since);
+                        self.push(Kind::Meta, offset + size, Some(0), depth, label);
+                        continue;
+                    }
+                    let field_endian = field.endian.unwrap_or(endian);
+                    size += self.walk(
+                        &field.ty,
+                        &field_path,
+                        depth,
+                        offset + size,
+                        body_end,
+                        field_endian,
+                    )?;
+                }
+                if offset + size < body_end {
+                    let skipped = body_end - offset - size;
+                    self.push(
+                        Kind::Meta,
+                        offset + size,
+                        Some(skipped),
+                        depth,
+                        "[newer fields]",
+                    );
+                }
+                6 + length
+            }
+            StructEncoding::Tagged => {
+                let length = self.u32_header(path, depth, offset, end, endian)?;
+                let body_end = offset + 4 + length;
+                if body_end > end {
+                    let error = format!("body of {} bytes overruns the buffer", length);
+                    return Err(self.fail(offset, depth, path, &error));
+                }
+                self.push(
+                    Kind::Meta,
+                    offset,
+                    Some(4),
+                    depth,
+                    format!("[body: {} bytes]", length),
+                );
+
+                let mut cursor = offset + 4;
+                while cursor < body_end {
+                    let id = match u16::deserialize(&self.buf[cursor..body_end], endian) {
+                        Ok((id, _)) => id,
+                        Err(_) => {
+                            return Err(self.fail(cursor, depth, path, "expected a field id"))
+                        }
+                    };
+                    let field_length =
+                        self.u32_header(path, depth, cursor + 2, body_end, endian)?;
+                    let field_end = cursor + 6 + field_length;
+                    if field_end > body_end {
+                        let error = format!("field {} overruns the struct body", id);
+                        return Err(self.fail(cursor, depth, path, &error));
+                    }
+
+                    match s.fields.iter().find(|f| f.id == Some(id)) {
+                        Some(field) => {
+                            let field_path = join(path, &field.name);
+                            let header =
+                                format!("[{}: id {}, {} bytes]", field_path, id, field_length);
+                            self.push(Kind::Meta, cursor, Some(6), depth, header);
+                            let field_endian = field.endian.unwrap_or(endian);
+                            self.walk(
+                                &field.ty,
+                                &field_path,
+                                depth,
+                                cursor + 6,
+                                field_end,
+                                field_endian,
+                            )?;
+                        }
+                        None => {
+                            let label = format!("[unknown field id {}]", id);
+                            self.push(Kind::Meta, cursor, Some(6 + field_length), depth, label);
+                        }
+                    }
+                    cursor = field_end;
+                }
+                4 + length
+            }
+        };
+
+        self.lines[group].len = Some(size);
+        Ok(size)
+    }
+
+    fn enumeration(
+        &mut self,
+        e: &Enum,
+        path: &str,
+        depth: usize,
+        offset: usize,
+        end: usize,
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        let endian = e.endian.unwrap_or(endian);
+        let label = if path.is_empty() {
+            e.name.clone()
+        } else {
+            format!("{}: {}", path, e.name)
+        };
+        let group = self.push(Kind::Group, offset, None, depth, label);
+        let depth = depth + 1;
+
+        let Some(&tag) = self.buf[offset..end].first() else {
+            return Err(self.fail(offset, depth, path, "expected a variant tag"));
+        };
+        let (header, body_end) = if e.length_prefixed {
+            let length = self.u32_header(path, depth, offset + 1, end, endian)?;
+            if offset + 5 + length > end {
+                let error = format!("variant body of {} bytes overruns the buffer", length);
+                return Err(self.fail(offset, depth, path, &error));
+            }
+            let label = format!("[tag: {}, body: {} bytes]", tag, length);
+            self.push(Kind::Meta, offset, Some(5), depth, label);
+            (5, Some(offset + 5 + length))
+        } else {
+            self.push(
+                Kind::Meta,
+                offset,
+                Some(1),
+                depth,
+                format!("[tag: {}]", tag),
+            );
+            (1, None)
+        };
+
+        let Some(variant) = e.variant(tag) else {
+            let error = format!("unknown variant tag {}", tag);
+            return Err(self.fail(offset, depth, path, &error));
+        };
+        let size = if variant.tag == tag && !variant.captures_tag() {
+            let fields_end = body_end.unwrap_or(end);
+            header
+                + self.fields(
+                    &variant.fields,
+                    path,
+                    depth,
+                    offset + header,
+                    fields_end,
+                    endian,
+                )?
+        } else {
+            header
+        };
+        let size = body_end.map_or(size, |body_end| body_end - offset);
+
+        let label = format!("{}::{}", self.lines[group].label, variant.name);
+        self.close(group, size, label);
+        Ok(size)
+    }
+
+    fn render(&self, style: Style) -> String {
+        let columns = self
+            .lines
+            .iter()
+            .map(|line| {
+                let len = line.len.map_or("?".to_string(), |len| len.to_string());
+                let position = format!("{:06x}  {:>5}  ", line.offset, len);
+                let text = format!("{}{}", "  ".repeat(line.depth), line.label);
+                (position, text)
+            })
+            .collect::<Vec<_>>();
+        let width = columns
+            .iter()
+            .map(|(position, text)| position.len() + text.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut out = String::new();
+        for (line, (position, text)) in self.lines.iter().zip(&columns) {
+            let hex = match line.len {
+                Some(len) => hex(&self.buf[line.offset..line.offset + len]),
+                None => hex(&self.buf[line.offset.min(self.buf.len())..]),
+            };
+            let padding = " ".repeat(width - position.len() - text.chars().count());
+
+            let _ = match (style, line.kind) {
+                (Style::Plain, Kind::Group) => writeln!(out, "{}{}", position, text),
+                (Style::Plain, _) => writeln!(out, "{}{}{}  {}", position, text, padding, hex),
+                (Style::Ansi, Kind::Group) => {
+                    writeln!(out, "\x1b[2m{}\x1b[0m\x1b[1m{}\x1b[0m", position, text)
+                }
+                (Style::Ansi, kind) => {
+                    let colour = match kind {
+                        Kind::Meta => "33",
+                        Kind::Error => "1;31",
+                        _ => "32",
+                    };
+                    writeln!(
+                        out,
+                        "\x1b[2m{}\x1b[0m\x1b[{}m{}\x1b[0m{}  \x1b[2m{}\x1b[0m",
+                        position, colour, text, padding, hex
+                    )
+                }
+            };
+        }
+
+        out.truncate(out.trim_end().len());
+        out
+    }
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn display_path(path: &str) -> &str {
+    if path.is_empty() {
+        "value"
+    } else {
+        path
+    }
+}
+
+fn expected(ty: &Type) -> String {
+    match ty {
+        Type::Primitive(primitive) => format!(
+            "expected {} bytes for a {}",
+            primitive.width(),
+            primitive.name()
+        ),
+        _ => "expected a UTF-8 string".to_string(),
+    }
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::U8(v) => v.to_string(),
+        Value::I8(v) => v.to_string(),
+        Value::U16(v) => v.to_string(),
+        Value::I16(v) => v.to_string(),
+        Value::U32(v) => v.to_string(),
+        Value::I32(v) => v.to_string(),
+        Value::U64(v) => v.to_string(),
+        Value::I64(v) => v.to_string(),
+        Value::F32(v) => v.to_string(),
+        Value::F64(v) => v.to_string(),
+        Value::Bool(v) => v.to_string(),
+        Value::String(v) => format!("{:?}", v),
+        other => format!("{:?}", other),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut out = bytes
+        .iter()
+        .take(HEX_BYTES_PER_LINE)
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if bytes.len() > HEX_BYTES_PER_LINE {
+        out += " ..";
+    }
+    out
+}
@@ -1,11 +1,11 @@
-#[derive(Clone, Copy, Debug, PartialEq)]
+use crate::{Deserialize, DeserializeError, Serialize, SerializeError};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "async", dryb(async))]
 pub enum Endianness {
+    #[default]
     Little,
     Big,
-}
-
-impl Default for Endianness {
-    fn default() -> Self {
-        Endianness::Little
-    }
+    /// The byte order of the host the code is running on.
+    Native,
 }
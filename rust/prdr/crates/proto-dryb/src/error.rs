@@ -3,12 +3,19 @@ use std::{error::Error, fmt};
 #[derive(Debug)]
 pub enum SerializeError {
     BufferOverflow,
+    /// A dynamic value doesn't have the shape its schema describes.
+    ValueMismatch,
+    /// A `#[dryb(other)]` variant holds the tag of a known variant, so it would decode as that
+    /// variant.
+    KnownTag(u8),
 }
 
 impl fmt::Display for SerializeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             SerializeError::BufferOverflow => write!(f, "Buffer overflow"),
+            SerializeError::ValueMismatch => write!(f, "Value does not match schema"),
+            SerializeError::KnownTag(tag) => write!(f, "Tag {} belongs to a known variant", tag),
         }
     }
 }
@@ -18,12 +25,24 @@ impl Error for SerializeError {}
 #[derive(Debug)]
 pub enum DeserializeError {
     Invalid,
+    SchemaMismatch { expected: u64, found: u64 },
+    ChecksumMismatch { expected: u64, found: u64 },
 }
 
 impl fmt::Display for DeserializeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             DeserializeError::Invalid => write!(f, "Invalid payload"),
+            DeserializeError::SchemaMismatch { expected, found } => write!(
+                f,
+                "Schema mismatch: expected fingerprint {:016x}, found {:016x}",
+                expected, found
+            ),
+            DeserializeError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "Checksum mismatch: expected {:016x}, found {:016x}",
+                expected, found
+            ),
         }
     }
 }
@@ -0,0 +1,176 @@
+use crate::deserialize::Deserialize;
+use crate::endian::Endianness;
+use crate::error::{DeserializeError, SerializeError};
+use crate::schema::{Schema, StructEncoding, Type, VariantKind};
+use crate::serialize::Serialize;
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// FNV-1a hash of a schema description string.
+pub const fn hash(description: &str) -> u64 {
+    let bytes = description.as_bytes();
+    let mut hash = FNV_OFFSET;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+        i += 1;
+    }
+    hash
+}
+
+/// Folds the fingerprints of a type's parts, in order, into a single fingerprint.
+pub const fn combine(parts: &[u64]) -> u64 {
+    let mut hash = FNV_OFFSET;
+    let mut i = 0;
+    while i < parts.len() {
+        let bytes = parts[i].to_le_bytes();
+        let mut j = 0;
+        while j < bytes.len() {
+            hash ^= bytes[j] as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+            j += 1;
+        }
+        i += 1;
+    }
+    hash
+}
+
+/// Computes the same value as `Schema::FINGERPRINT` from a schema tree.
+pub fn of(ty: &Type) -> u64 {
+    match ty {
+        Type::Primitive(primitive) => hash(primitive.name()),
+        Type::Option(item) => combine(&[hash("option"), of(item)]),
+        Type::Vec { item } => combine(&[hash("vec"), hash("u32"), of(item)]),
+        Type::String => combine(&[hash("string"), hash("u32")]),
+        Type::Array { len, item } => combine(&[hash("array"), *len as u64, of(item)]),
+        Type::Map { key, value } => combine(&[hash("map"), hash("u32"), of(key), of(value)]),
+        Type::Struct(s) => {
+            let encoding = match s.encoding {
+                StructEncoding::Positional => String::new(),
+                StructEncoding::Versioned(version) => format!(" version={}", version),
+                StructEncoding::Tagged => " tagged".to_string(),
+            };
+            let mut parts = vec![hash(&format!(
+                "struct {} endian={}{}",
+                s.name,
+                endian_name(s.endian),
+                encoding
+            ))];
+            for field in &s.fields {
+                parts.push(field_hash(&field.name, field.endian, field.since, field.id));
+                parts.push(of(&field.ty));
+            }
+            combine(&parts)
+        }
+        Type::Enum(e) => {
+            let mut parts = vec![hash(&format!(
+                "enum {} endian={}{}",
+                e.name,
+                endian_name(e.endian),
+                if e.length_prefixed {
+                    " length_prefixed"
+                } else {
+                    ""
+                }
+            ))];
+            for variant in &e.variants {
+                let kind = match variant.kind {
+                    VariantKind::Unit => "unit",
+                    VariantKind::Tuple => "tuple",
+                    VariantKind::Struct => "struct",
+                };
+                parts.push(hash(&format!(
+                    "variant {} tag={} {}{}",
+                    variant.name,
+                    variant.tag,
+                    kind,
+                    if variant.other { " other" } else { "" }
+                )));
+                for field in &variant.fields {
+                    parts.push(field_hash(&field.name, field.endian, field.since, field.id));
+                    parts.push(of(&field.ty));
+                }
+            }
+            combine(&parts)
+        }
+    }
+}
+
+fn field_hash(name: &str, endian: Option<Endianness>, since: Option<u16>, id: Option<u16>) -> u64 {
+    let mut description = format!("field {} endian={}", name, endian_name(endian));
+    if let Some(since) = since {
+        description += &format!(" since={}", since);
+    }
+    if let Some(id) = id {
+        description += &format!(" id={}", id);
+    }
+    hash(&description)
+}
+
+fn endian_name(endian: Option<Endianness>) -> &'static str {
+    match endian {
+        None => "inherit",
+        Some(Endianness::Little) => "little",
+        Some(Endianness::Big) => "big",
+        Some(Endianness::Native) => "native",
+    }
+}
+
+/// Prefixes a value with its schema fingerprint, so that a peer built against a
+/// different version of the type rejects it instead of decoding garbage.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fingerprinted<T>(pub T);
+
+impl<T: Schema + Serialize> Serialize for Fingerprinted<T> {
+    fn serialize(&self, buf: &mut [u8], endian: Endianness) -> Result<usize, SerializeError> {
+        let size = T::FINGERPRINT.serialize(buf, endian)?;
+        Ok(size + self.0.serialize(&mut buf[size..], endian)?)
+    }
+}
+
+impl<T: Schema + Deserialize> Deserialize for Fingerprinted<T> {
+    const FIXED_SIZE: Option<usize> = crate::fixed_size_sum(&[Some(8), T::FIXED_SIZE]);
+
+    fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
+        let (found, size) = u64::deserialize(buf, endian)?;
+        if found != T::FINGERPRINT {
+            return Err(DeserializeError::SchemaMismatch {
+                expected: T::FINGERPRINT,
+                found,
+            });
+        }
+
+        let (value, value_size) = T::deserialize(&buf[size..], endian)?;
+        Ok((Fingerprinted(value), size + value_size))
+    }
+
+    fn deserialize_in_place(
+        &mut self,
+        buf: &[u8],
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        let (found, size) = u64::deserialize(buf, endian)?;
+        if found != T::FINGERPRINT {
+            return Err(DeserializeError::SchemaMismatch {
+                expected: T::FINGERPRINT,
+                found,
+            });
+        }
+
+        Ok(size + self.0.deserialize_in_place(&buf[size..], endian)?)
+    }
+
+    fn skip(buf: &[u8], endian: Endianness) -> Result<usize, DeserializeError> {
+        let (found, size) = u64::deserialize(buf, endian)?;
+        if found != T::FINGERPRINT {
+            return Err(DeserializeError::SchemaMismatch {
+                expected: T::FINGERPRINT,
+                found,
+            });
+        }
+
+        Ok(size + T::skip(&buf[size..], endian)?)
+    }
+}
@@ -0,0 +1,395 @@
+//! Length-delimited framing for sending dryb payloads over byte streams.
+//!
+//! A frame is laid out as
+//!
+//! ```text
+//! [magic] [type id: u16] length payload [crc32c: u32]
+//! ```
+//!
+//! where the bracketed parts are present when the `Config` asks for them. The length counts the
+//! payload bytes only, and the checksum covers everything between the magic and itself.
+
+use std::io::{self, Read, Write};
+use std::{error, fmt};
+
+use crate::checksum::crc32c;
+use crate::{Deserialize, DeserializeError, Endianness, Serialize, SerializeError};
+
+/// How the payload length is written.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Length {
+    U16,
+    #[default]
+    U32,
+    /// Unsigned LEB128, one to ten bytes.
+    Varint,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub length: Length,
+    /// Written before every frame and checked when reading.
+    pub magic: &'static [u8],
+    pub type_id: bool,
+    pub checksum: bool,
+    /// Frames with longer payloads are refused by both the reader and the writer.
+    pub max_length: usize,
+    /// Byte order of the fixed-size length, type id and checksum.
+    pub endian: Endianness,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            length: Length::U32,
+            magic: b"",
+            type_id: false,
+            checksum: false,
+            max_length: 16 * 1024 * 1024,
+            endian: Endianness::Little,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Frame {
+    /// Always 0 when the config has no type id.
+    pub type_id: u16,
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    pub fn new<T: Serialize>(
+        type_id: u16,
+        value: &T,
+        endian: Endianness,
+    ) -> Result<Self, SerializeError> {
+        let mut payload = vec![0u8; 256];
+        loop {
+            match value.serialize(&mut payload, endian) {
+                Ok(size) => {
+                    payload.truncate(size);
+                    return Ok(Frame { type_id, payload });
+                }
+                Err(SerializeError::BufferOverflow) => payload.resize(payload.len() * 2, 0),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Decodes the payload, which must hold exactly one `T`.
+    pub fn decode<T: Deserialize>(&self, endian: Endianness) -> Result<T, DeserializeError> {
+        let (value, size) = T::deserialize(&self.payload, endian)?;
+        if size != self.payload.len() {
+            return Err(DeserializeError::Invalid);
+        }
+        Ok(value)
+    }
+}
+
+#[derive(Debug)]
+pub enum FrameError {
+    Io(io::Error),
+    /// The stream ended in the middle of a frame.
+    Truncated,
+    BadMagic,
+    /// A varint length that doesn't fit in a `u64`.
+    InvalidLength,
+    Oversized {
+        length: u64,
+        max: usize,
+    },
+    ChecksumMismatch {
+        expected: u32,
+        found: u32,
+    },
+    Serialize(SerializeError),
+    Deserialize(DeserializeError),
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::Io(e) => e.fmt(f),
+            FrameError::Truncated => write!(f, "Stream ended inside a frame"),
+            FrameError::BadMagic => write!(f, "Frame does not start with the expected magic"),
+            FrameError::InvalidLength => write!(f, "Invalid frame length"),
+            FrameError::Oversized { length, max } => write!(
+                f,
+                "Frame payload of {} bytes exceeds the limit of {}",
+                length, max
+            ),
+            FrameError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "Frame checksum mismatch: expected {:08x}, found {:08x}",
+                expected, found
+            ),
+            FrameError::Serialize(e) => e.fmt(f),
+            FrameError::Deserialize(e) => e.fmt(f),
+        }
+    }
+}
+
+impl error::Error for FrameError {}
+
+impl From<io::Error> for FrameError {
+    fn from(e: io::Error) -> Self {
+        FrameError::Io(e)
+    }
+}
+
+impl From<SerializeError> for FrameError {
+    fn from(e: SerializeError) -> Self {
+        FrameError::Serialize(e)
+    }
+}
+
+impl From<DeserializeError> for FrameError {
+    fn from(e: DeserializeError) -> Self {
+        FrameError::Deserialize(e)
+    }
+}
+
+impl Config {
+    /// Appends the encoded frame to `out`.
+    pub fn encode(&self, frame: &Frame, out: &mut Vec<u8>) -> Result<(), FrameError> {
+        let length = frame.payload.len();
+        let fits = match self.length {
+            Length::U16 => length <= u16::MAX as usize,
+            Length::U32 => length <= u32::MAX as usize,
+            Length::Varint => true,
+        };
+        if length > self.max_length || !fits {
+            return Err(FrameError::Oversized {
+                length: length as u64,
+                max: self.max_length,
+            });
+        }
+
+        out.extend_from_slice(self.magic);
+        let start = out.len();
+        if self.type_id {
+            self.put(out, &frame.type_id);
+        }
+        match self.length {
+            Length::U16 => self.put(out, &(length as u16)),
+            Length::U32 => self.put(out, &(length as u32)),
+            Length::Varint => {
+                let mut n = length as u64;
+                while n >= 0x80 {
+                    out.push(n as u8 | 0x80);
+                    n >>= 7;
+                }
+                out.push(n as u8);
+            }
+        }
+        out.extend_from_slice(&frame.payload);
+        if self.checksum {
+            let crc = crc32c(&out[start..]);
+            self.put(out, &crc);
+        }
+        Ok(())
+    }
+
+    /// Decodes the frame at the start of `buf`, returning it and the number of bytes it took,
+    /// or `None` if `buf` doesn't hold a whole frame yet. Oversized frames are reported as soon
+    /// as their length is read.
+    pub fn decode(&self, buf: &[u8]) -> Result<Option<(Frame, usize)>, FrameError> {
+        let magic = self.magic.len();
+        if !buf.starts_with(&self.magic[..magic.min(buf.len())]) {
+            return Err(FrameError::BadMagic);
+        }
+        if buf.len() < magic {
+            return Ok(None);
+        }
+
+        let mut offset = magic;
+        let mut type_id = 0;
+        if self.type_id {
+            match u16::deserialize(&buf[offset..], self.endian) {
+                Ok((id, size)) => {
+                    type_id = id;
+                    offset += size;
+                }
+                Err(_) => return Ok(None),
+            }
+        }
+
+        let length = match self.length {
+            Length::U16 => {
+                u16::deserialize(&buf[offset..], self.endian).map(|(n, s)| (n as u64, s))
+            }
+            Length::U32 => {
+                u32::deserialize(&buf[offset..], self.endian).map(|(n, s)| (n as u64, s))
+            }
+            Length::Varint => match read_varint(&buf[offset..]) {
+                Some(result) => Ok(result?),
+                None => return Ok(None),
+            },
+        };
+        let Ok((length, size)) = length else {
+            return Ok(None);
+        };
+        offset += size;
+        if length > self.max_length as u64 {
+            return Err(FrameError::Oversized {
+                length,
+                max: self.max_length,
+            });
+        }
+
+        let end = offset + length as usize;
+        let total = end + if self.checksum { 4 } else { 0 };
+        if buf.len() < total {
+            return Ok(None);
+        }
+        if self.checksum {
+            let (found, _) = u32::deserialize(&buf[end..], self.endian)?;
+            let expected = crc32c(&buf[magic..end]);
+            if found != expected {
+                return Err(FrameError::ChecksumMismatch { expected, found });
+            }
+        }
+
+        let frame = Frame {
+            type_id,
+            payload: buf[offset..end].to_vec(),
+        };
+        Ok(Some((frame, total)))
+    }
+
+    fn put<T: Serialize>(&self, out: &mut Vec<u8>, value: &T) {
+        let mut bytes = [0u8; 8];
+        let size = value
+            .serialize(&mut bytes, self.endian)
+            .expect("header fields fit in 8 bytes");
+        out.extend_from_slice(&bytes[..size]);
+    }
+}
+
+/// `None` when `buf` ends before the varint does.
+fn read_varint(buf: &[u8]) -> Option<Result<(u64, usize), FrameError>> {
+    let mut n = 0u64;
+    for (i, byte) in buf.iter().enumerate() {
+        if i == 10 || (i == 9 && *byte > 1) {
+            return Some(Err(FrameError::InvalidLength));
+        }
+        n |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some(Ok((n, i + 1)));
+        }
+    }
+    None
+}
+
+pub struct FrameWriter<W> {
+    inner: W,
+    config: Config,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> FrameWriter<W> {
+    pub fn new(inner: W, config: Config) -> Self {
+        FrameWriter {
+            inner,
+            config,
+            buf: Vec::new(),
+        }
+    }
+
+    pub fn write_frame(&mut self, frame: &Frame) -> Result<(), FrameError> {
+        self.buf.clear();
+        self.config.encode(frame, &mut self.buf)?;
+        self.inner.write_all(&self.buf)?;
+        Ok(())
+    }
+
+    /// Serializes `value` with the config's byte order and writes it as one frame.
+    pub fn write<T: Serialize>(&mut self, type_id: u16, value: &T) -> Result<(), FrameError> {
+        let frame = Frame::new(type_id, value, self.config.endian)?;
+        self.write_frame(&frame)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Reads frames from a stream. Bytes read before an error, including `WouldBlock`, are kept, so
+/// the next call picks up where the last one stopped.
+///
+/// A corrupt frame can't be skipped, since its length may be what is wrong, so it stays buffered
+/// and every later `read_frame` reports it again. Iterating stops after the first error.
+pub struct FrameReader<R> {
+    inner: R,
+    config: Config,
+    buf: Vec<u8>,
+    failed: bool,
+}
+
+impl<R: Read> FrameReader<R> {
+    pub fn new(inner: R, config: Config) -> Self {
+        FrameReader {
+            inner,
+            config,
+            buf: Vec::new(),
+            failed: false,
+        }
+    }
+
+    /// Reads the next frame, or `None` if the stream ended cleanly between frames.
+    pub fn read_frame(&mut self) -> Result<Option<Frame>, FrameError> {
+        let mut chunk = [0u8; 4096];
+        loop {
+            if let Some((frame, size)) = self.config.decode(&self.buf)? {
+                self.buf.drain(..size);
+                return Ok(Some(frame));
+            }
+
+            match self.inner.read(&mut chunk) {
+                Ok(0) if self.buf.is_empty() => return Ok(None),
+                Ok(0) => return Err(FrameError::Truncated),
+                Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Reads the next frame and decodes it with the config's byte order.
+    pub fn read<T: Deserialize>(&mut self) -> Result<Option<T>, FrameError> {
+        match self.read_frame()? {
+            Some(frame) => Ok(Some(frame.decode(self.config.endian)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Iterator for FrameReader<R> {
+    type Item = Result<Frame, FrameError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = self.read_frame().transpose();
+        self.failed = matches!(result, Some(Err(_)));
+        result
+    }
+}
@@ -0,0 +1,592 @@
+//! Converts dryb payloads to JSON and back, driven by a runtime schema or a `Schema` type.
+//!
+//! Structs become objects, maps with string keys objects and other maps arrays of
+//! `[key, value]` pairs, and enum variants follow serde's externally tagged layout: `"Unit"`,
+//! `{"Newtype": v}`, `{"Tuple": [..]}`, `{"Struct": {..}}`. A catch-all variant holding an
+//! unknown tag or body is written as `{"Name": {"tag": t, "body": bytes}}`, so it encodes back to
+//! the same bytes. NaN and the infinities, which JSON numbers can't hold, are written as the
+//! strings `"NaN"`, `"inf"` and `"-inf"`.
+
+use std::fmt;
+
+use serde_json::{Map, Number, Value as Json};
+
+use crate::schema::{Field, Primitive, Schema, Type, Variant, VariantKind};
+use crate::{DeserializeError, Endianness, SerializeError, Value};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Options {
+    pub bytes: Bytes,
+    pub int64: Int64,
+}
+
+/// How `[u8; N]` and `Vec<u8>` are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Bytes {
+    /// An array of numbers.
+    #[default]
+    Array,
+    Hex,
+    /// Standard base64 with padding.
+    Base64,
+}
+
+/// How `u64` and `i64` are written. Both forms are accepted when reading.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Int64 {
+    #[default]
+    Number,
+    /// Decimal strings, for readers that parse numbers as doubles.
+    String,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Deserialize(DeserializeError),
+    Serialize(SerializeError),
+    /// The JSON doesn't have the shape the schema describes.
+    Mismatch {
+        path: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Deserialize(e) => e.fmt(f),
+            Error::Serialize(e) => e.fmt(f),
+            Error::Mismatch { path, message } if path.is_empty() => f.write_str(message),
+            Error::Mismatch { path, message } => write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<DeserializeError> for Error {
+    fn from(e: DeserializeError) -> Self {
+        Error::Deserialize(e)
+    }
+}
+
+impl From<SerializeError> for Error {
+    fn from(e: SerializeError) -> Self {
+        Error::Serialize(e)
+    }
+}
+
+fn mismatch(path: &str, message: impl Into<String>) -> Error {
+    Error::Mismatch {
+        path: path.to_string(),
+        message: message.into(),
+    }
+}
+
+/// Decodes a `T` payload as JSON, returning it and the number of bytes read.
+pub fn to_json<T: Schema>(
+    buf: &[u8],
+    endian: Endianness,
+    options: &Options,
+) -> Result<(Json, usize), Error> {
+    decode(&T::schema(), buf, endian, options)
+}
+
+/// Encodes JSON as a `T` payload.
+pub fn from_json<T: Schema>(
+    json: &Json,
+    endian: Endianness,
+    options: &Options,
+) -> Result<Vec<u8>, Error> {
+    encode(&T::schema(), json, endian, options)
+}
+
+/// Decodes a payload of type `ty` as JSON, returning it and the number of bytes read.
+pub fn decode(
+    ty: &Type,
+    buf: &[u8],
+    endian: Endianness,
+    options: &Options,
+) -> Result<(Json, usize), Error> {
+    let (value, size) = Value::decode(ty, buf, endian)?;
+    Ok((value_to_json(ty, &value, options), size))
+}
+
+/// Encodes JSON as a payload of type `ty`.
+pub fn encode(
+    ty: &Type,
+    json: &Json,
+    endian: Endianness,
+    options: &Options,
+) -> Result<Vec<u8>, Error> {
+    let value = value_from_json(ty, json, options)?;
+    let mut buf = vec![0u8; 256];
+    loop {
+        match value.encode(ty, &mut buf, endian) {
+            Ok(size) => {
+                buf.truncate(size);
+                return Ok(buf);
+            }
+            Err(SerializeError::BufferOverflow) => buf.resize(buf.len() * 2, 0),
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+pub fn value_to_json(ty: &Type, value: &Value, options: &Options) -> Json {
+    match (ty, value) {
+        (_, Value::U8(v)) => Json::from(*v),
+        (_, Value::I8(v)) => Json::from(*v),
+        (_, Value::U16(v)) => Json::from(*v),
+        (_, Value::I16(v)) => Json::from(*v),
+        (_, Value::U32(v)) => Json::from(*v),
+        (_, Value::I32(v)) => Json::from(*v),
+        (_, Value::U64(v)) if options.int64 == Int64::String => Json::String(v.to_string()),
+        (_, Value::U64(v)) => Json::from(*v),
+        (_, Value::I64(v)) if options.int64 == Int64::String => Json::String(v.to_string()),
+        (_, Value::I64(v)) => Json::from(*v),
+        (_, Value::F32(v)) => float_to_json(*v as f64),
+        (_, Value::F64(v)) => float_to_json(*v),
+        (_, Value::Bool(v)) => Json::Bool(*v),
+        (_, Value::String(v)) => Json::String(v.clone()),
+        (Type::Option(item), Value::Option(Some(v))) => value_to_json(item, v, options),
+        (_, Value::Option(None)) => Json::Null,
+        (Type::Vec { item, .. } | Type::Array { item, .. }, Value::Seq(items)) => {
+            if is_byte(item) && options.bytes != Bytes::Array {
+                let bytes = items
+                    .iter()
+                    .map(|v| match v {
+                        Value::U8(b) => *b,
+                        _ => unreachable!("u8 items decode as u8"),
+                    })
+                    .collect::<Vec<_>>();
+                return bytes_to_json(&bytes, options);
+            }
+            Json::Array(
+                items
+                    .iter()
+                    .map(|v| value_to_json(item, v, options))
+                    .collect(),
+            )
+        }
+        (Type::Map { key, value, .. }, Value::Map(entries)) => {
+            if matches!(**key, Type::String) {
+                let object = entries
+                    .iter()
+                    .map(|(k, v)| match k {
+                        Value::String(k) => (k.clone(), value_to_json(value, v, options)),
+                        _ => unreachable!("string keys decode as strings"),
+                    })
+                    .collect();
+                Json::Object(object)
+            } else {
+                let pairs = entries
+                    .iter()
+                    .map(|(k, v)| {
+                        Json::Array(vec![
+                            value_to_json(key, k, options),
+                            value_to_json(value, v, options),
+                        ])
+                    })
+                    .collect();
+                Json::Array(pairs)
+            }
+        }
+        (
+            Type::Struct(s),
+            Value::Struct(fields) | Value::Versioned { fields, .. } | Value::Tagged { fields, .. },
+        ) => Json::Object(fields_to_json(&s.fields, fields, options)),
+        (
+            Type::Enum(e),
+            Value::Variant {
+                name,
+                tag,
+                fields,
+                rest,
+            },
+        ) => {
+            let Some(variant) = e.variants.iter().find(|v| v.name == *name) else {
+                return Json::String(name.clone());
+            };
+            let unknown = variant.other
+                && (!rest.is_empty() || !variant.captures_tag() && *tag != variant.tag);
+            let body = match variant.kind {
+                _ if unknown => {
+                    let mut object = Map::new();
+                    object.insert("tag".to_string(), Json::from(*tag));
+                    if !rest.is_empty() {
+                        object.insert("body".to_string(), bytes_to_json(rest, options));
+                    }
+                    Json::Object(object)
+                }
+                VariantKind::Unit => return Json::String(name.clone()),
+                VariantKind::Tuple if fields.len() == 1 => {
+                    value_to_json(&variant.fields[0].ty, &fields[0].1, options)
+                }
+                VariantKind::Tuple => Json::Array(
+                    variant
+                        .fields
+                        .iter()
+                        .zip(fields)
+                        .map(|(field, (_, v))| value_to_json(&field.ty, v, options))
+                        .collect(),
+                ),
+                VariantKind::Struct => {
+                    Json::Object(fields_to_json(&variant.fields, fields, options))
+                }
+            };
+            let mut object = Map::new();
+            object.insert(name.clone(), body);
+            Json::Object(object)
+        }
+        _ => Json::Null,
+    }
+}
+
+fn bytes_to_json(bytes: &[u8], options: &Options) -> Json {
+    match options.bytes {
+        Bytes::Array => Json::Array(bytes.iter().map(|b| Json::from(*b)).collect()),
+        Bytes::Hex => Json::String(to_hex(bytes)),
+        Bytes::Base64 => Json::String(to_base64(bytes)),
+    }
+}
+
+fn fields_to_json(
+    schema: &[Field],
+    fields: &[(String, Value)],
+    options: &Options,
+) -> Map<String, Json> {
+    fields
+        .iter()
+        .filter_map(|(name, value)| {
+            let field = schema.iter().find(|f| f.name == *name)?;
+            Some((name.clone(), value_to_json(&field.ty, value, options)))
+        })
+        .collect()
+}
+
+/// Converts JSON in the layout `value_to_json` produces back into a value of type `ty`.
+pub fn value_from_json(ty: &Type, json: &Json, options: &Options) -> Result<Value, Error> {
+    from_json_at(ty, json, options, "")
+}
+
+fn from_json_at(ty: &Type, json: &Json, options: &Options, path: &str) -> Result<Value, Error> {
+    let expected = |what: &str| mismatch(path, format!("expected {}, found {}", what, json));
+
+    match ty {
+        Type::Primitive(primitive) => {
+            primitive_from_json(*primitive, json).ok_or_else(|| expected(primitive.name()))
+        }
+        Type::Option(item) => match json {
+            Json::Null => Ok(Value::Option(None)),
+            json => Ok(Value::Option(Some(Box::new(from_json_at(
+                item, json, options, path,
+            )?)))),
+        },
+        Type::String => match json {
+            Json::String(s) => Ok(Value::String(s.clone())),
+            _ => Err(expected("a string")),
+        },
+        Type::Vec { item, .. } | Type::Array { item, .. } => {
+            let items = match json {
+                Json::Array(items) => items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| from_json_at(item, v, options, &format!("{}[{}]", path, i)))
+                    .collect::<Result<Vec<_>, _>>()?,
+                Json::String(s) if is_byte(item) => {
+                    let bytes = match options.bytes {
+                        Bytes::Hex => from_hex(s),
+                        Bytes::Base64 => from_base64(s),
+                        Bytes::Array => None,
+                    };
+                    bytes
+                        .ok_or_else(|| expected("a byte array"))?
+                        .into_iter()
+                        .map(Value::U8)
+                        .collect()
+                }
+                _ => return Err(expected("an array")),
+            };
+            if let Type::Array { len, .. } = ty {
+                if items.len() != *len as usize {
+                    return Err(expected(&format!("an array of {} items", len)));
+                }
+            }
+            Ok(Value::Seq(items))
+        }
+        Type::Map { key, value, .. } => {
+            let entries = match json {
+                Json::Object(object) => object
+                    .iter()
+                    .map(|(k, v)| {
+                        let key = from_json_at(key, &Json::String(k.clone()), options, path)?;
+                        let value_path = format!("{}[{:?}]", path, k);
+                        Ok((key, from_json_at(value, v, options, &value_path)?))
+                    })
+                    .collect::<Result<_, Error>>()?,
+                Json::Array(pairs) => pairs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, pair)| {
+                        let entry_path = format!("{}[{}]", path, i);
+                        match pair.as_array().map(Vec::as_slice) {
+                            Some([k, v]) => Ok((
+                                from_json_at(key, k, options, &entry_path)?,
+                                from_json_at(value, v, options, &entry_path)?,
+                            )),
+                            _ => Err(mismatch(&entry_path, "expected a [key, value] pair")),
+                        }
+                    })
+                    .collect::<Result<_, Error>>()?,
+                _ => return Err(expected("an object or an array of pairs")),
+            };
+            Ok(Value::Map(entries))
+        }
+        Type::Struct(s) => {
+            let Json::Object(object) = json else {
+                return Err(expected(&format!("a {} object", s.name)));
+            };
+            Ok(Value::Struct(fields_from_json(
+                &s.fields, object, options, path,
+            )?))
+        }
+        Type::Enum(e) => {
+            let (name, body) = match json {
+                Json::String(name) => (name, None),
+                Json::Object(object) if object.len() == 1 => {
+                    let (name, body) = object.iter().next().unwrap();
+                    (name, Some(body))
+                }
+                _ => return Err(expected(&format!("a {} variant", e.name))),
+            };
+            let variant =
+                e.variants.iter().find(|v| v.name == *name).ok_or_else(|| {
+                    mismatch(path, format!("{} has no variant `{}`", e.name, name))
+                })?;
+            let path = format!("{}::{}", path, name);
+            if let (true, Some(Json::Object(object))) = (variant.other, body) {
+                return unknown_variant_from_json(variant, object, options, &path);
+            }
+            let fields = match (variant.kind, body) {
+                (VariantKind::Unit, None) => Vec::new(),
+                (VariantKind::Tuple, Some(body)) if variant.fields.len() == 1 => {
+                    let field = &variant.fields[0];
+                    vec![(
+                        field.name.clone(),
+                        from_json_at(&field.ty, body, options, &path)?,
+                    )]
+                }
+                (VariantKind::Tuple, Some(Json::Array(items)))
+                    if items.len() == variant.fields.len() =>
+                {
+                    variant
+                        .fields
+                        .iter()
+                        .zip(items)
+                        .map(|(field, item)| {
+                            let field_path = format!("{}.{}", path, field.name);
+                            Ok((
+                                field.name.clone(),
+                                from_json_at(&field.ty, item, options, &field_path)?,
+                            ))
+                        })
+                        .collect::<Result<_, Error>>()?
+                }
+                (VariantKind::Struct, Some(Json::Object(object))) => {
+                    fields_from_json(&variant.fields, object, options, &path)?
+                }
+                _ => return Err(mismatch(&path, "unexpected variant body")),
+            };
+            let tag = match fields.first() {
+                Some((_, Value::U8(tag))) if variant.captures_tag() => *tag,
+                _ => variant.tag,
+            };
+
+            Ok(Value::Variant {
+                name: name.clone(),
+                tag,
+                fields,
+                rest: Vec::new(),
+            })
+        }
+    }
+}
+
+/// Reads the `{"tag": t, "body": bytes}` form of a catch-all variant.
+fn unknown_variant_from_json(
+    variant: &Variant,
+    object: &Map<String, Json>,
+    options: &Options,
+    path: &str,
+) -> Result<Value, Error> {
+    if let Some(unknown) = object.keys().find(|k| *k != "tag" && *k != "body") {
+        return Err(mismatch(path, format!("unknown field `{}`", unknown)));
+    }
+    let tag = object
+        .get("tag")
+        .and_then(Json::as_u64)
+        .and_then(|tag| u8::try_from(tag).ok())
+        .ok_or_else(|| mismatch(path, "expected a u8 tag"))?;
+    let rest = match object.get("body") {
+        Some(body) => bytes_from_json(body, options)
+            .ok_or_else(|| mismatch(path, format!("expected a byte array, found {}", body)))?,
+        None => Vec::new(),
+    };
+    let fields = if variant.captures_tag() {
+        vec![(variant.fields[0].name.clone(), Value::U8(tag))]
+    } else {
+        Vec::new()
+    };
+
+    Ok(Value::Variant {
+        name: variant.name.clone(),
+        tag,
+        fields,
+        rest,
+    })
+}
+
+fn bytes_from_json(json: &Json, options: &Options) -> Option<Vec<u8>> {
+    match (json, options.bytes) {
+        (Json::Array(items), _) => items
+            .iter()
+            .map(|b| u8::try_from(b.as_u64()?).ok())
+            .collect(),
+        (Json::String(s), Bytes::Hex) => from_hex(s),
+        (Json::String(s), Bytes::Base64) => from_base64(s),
+        _ => None,
+    }
+}
+
+/// Missing fields are left out, which is only accepted by tagged structs.
+fn fields_from_json(
+    schema: &[Field],
+    object: &Map<String, Json>,
+    options: &Options,
+    path: &str,
+) -> Result<Vec<(String, Value)>, Error> {
+    if let Some(unknown) = object
+        .keys()
+        .find(|k| !schema.iter().any(|f| f.name == **k))
+    {
+        return Err(mismatch(path, format!("unknown field `{}`", unknown)));
+    }
+
+    schema
+        .iter()
+        .filter_map(|field| {
+            let json = object.get(&field.name)?;
+            let field_path = if path.is_empty() {
+                field.name.clone()
+            } else {
+                format!("{}.{}", path, field.name)
+            };
+            Some(
+                from_json_at(&field.ty, json, options, &field_path)
+                    .map(|v| (field.name.clone(), v)),
+            )
+        })
+        .collect()
+}
+
+fn primitive_from_json(primitive: Primitive, json: &Json) -> Option<Value> {
+    Some(match primitive {
+        Primitive::U8 => Value::U8(json.as_u64()?.try_into().ok()?),
+        Primitive::I8 => Value::I8(json.as_i64()?.try_into().ok()?),
+        Primitive::U16 => Value::U16(json.as_u64()?.try_into().ok()?),
+        Primitive::I16 => Value::I16(json.as_i64()?.try_into().ok()?),
+        Primitive::U32 => Value::U32(json.as_u64()?.try_into().ok()?),
+        Primitive::I32 => Value::I32(json.as_i64()?.try_into().ok()?),
+        Primitive::U64 => Value::U64(match json {
+            Json::String(s) => s.parse().ok()?,
+            _ => json.as_u64()?,
+        }),
+        Primitive::I64 => Value::I64(match json {
+            Json::String(s) => s.parse().ok()?,
+            _ => json.as_i64()?,
+        }),
+        Primitive::F32 => Value::F32(float_from_json(json)? as f32),
+        Primitive::F64 => Value::F64(float_from_json(json)?),
+        Primitive::Bool => Value::Bool(json.as_bool()?),
+    })
+}
+
+fn float_to_json(v: f64) -> Json {
+    match Number::from_f64(v) {
+        Some(number) => Json::Number(number),
+        None if v.is_nan() => Json::String("NaN".to_string()),
+        None if v > 0.0 => Json::String("inf".to_string()),
+        None => Json::String("-inf".to_string()),
+    }
+}
+
+fn float_from_json(json: &Json) -> Option<f64> {
+    match json {
+        Json::String(s) => match s.as_str() {
+            "NaN" => Some(f64::NAN),
+            "inf" => Some(f64::INFINITY),
+            "-inf" => Some(f64::NEG_INFINITY),
+            _ => None,
+        },
+        _ => json.as_f64(),
+    }
+}
+
+fn is_byte(ty: &Type) -> bool {
+    matches!(ty, Type::Primitive(Primitive::U8))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn to_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn from_base64(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(s.len() / 4 * 3);
+    for chunk in s.as_bytes().chunks(4) {
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        if padding > 2 {
+            return None;
+        }
+        let mut n = 0u32;
+        for (i, c) in chunk[..4 - padding].iter().enumerate() {
+            let digit = BASE64.iter().position(|b| b == c)? as u32;
+            n |= digit << (18 - 6 * i);
+        }
+        out.extend_from_slice(&n.to_be_bytes()[1..4 - padding]);
+    }
+    Some(out)
+}
@@ -1,10 +1,37 @@
+#[cfg(feature = "async")]
+pub mod async_io;
+pub mod checksum;
+#[cfg(feature = "async")]
+pub mod codec;
+pub mod compat;
 mod deserialize;
+pub mod dump;
 mod endian;
 mod error;
+pub mod fingerprint;
+pub mod frame;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod record_log;
+pub mod schema;
+#[cfg(feature = "serde")]
+pub mod serde_format;
 mod serialize;
+mod value;
 
-pub use deserialize::Deserialize;
+pub use checksum::Checksummed;
+#[doc(hidden)]
+pub use deserialize::skip_length_prefixed;
+pub use deserialize::{fixed_size_sum, Deserialize, DeserializeBorrowed, Slots};
 pub use endian::Endianness;
 pub use error::{DeserializeError, SerializeError};
-pub use proto_dryb_derive::{Deserialize, Serialize};
+pub use fingerprint::Fingerprinted;
+pub use proto_dryb_derive::{Deserialize, Schema, Serialize};
+pub use schema::Schema;
 pub use serialize::Serialize;
+pub use value::Value;
+
+// Lets derived impls inside this crate use the `::proto_dryb` paths they emit for users.
+extern crate self as proto_dryb;
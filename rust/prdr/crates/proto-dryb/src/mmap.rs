@@ -0,0 +1,182 @@
+//! Random access to the records of a memory-mapped file. Records are found by index without
+//! reading the ones before them, decoded only when asked for, and can borrow strings and byte
+//! arrays straight from the mapping through `DeserializeBorrowed`.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::checksum::crc32c;
+use crate::frame::FrameError;
+use crate::record_log::{read_header, LogError, HEADER_SIZE, RECORD_OVERHEAD};
+use crate::{Deserialize, DeserializeBorrowed, DeserializeError, Endianness, Schema};
+
+enum Layout {
+    Fixed {
+        offset: usize,
+        size: usize,
+        count: usize,
+    },
+    /// File offset of each record's frame in a record log.
+    Log(Vec<u64>),
+}
+
+pub struct MmapRecords {
+    map: Mmap,
+    endian: Endianness,
+    layout: Layout,
+}
+
+impl MmapRecords {
+    /// Maps a file written by `RecordLog<S>`. The records are located up front by walking their
+    /// lengths, which touches one page per record but reads none of the payloads. A torn final
+    /// record is left out.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be truncated or modified while it is mapped. Appending is fine, but
+    /// records added after this call aren't seen.
+    pub unsafe fn open_log<S: Schema>(path: impl AsRef<Path>) -> Result<Self, LogError> {
+        let map = Mmap::map(&File::open(path)?)?;
+        let endian = read_header(&map, S::FINGERPRINT)?;
+
+        let mut offsets = Vec::new();
+        let mut offset = HEADER_SIZE;
+        while offset + RECORD_OVERHEAD <= map.len() as u64 {
+            let (length, _) = u32::deserialize(&map[offset as usize..], endian)?;
+            let size = length as u64 + RECORD_OVERHEAD;
+            if offset + size > map.len() as u64 {
+                break;
+            }
+            offsets.push(offset);
+            offset += size;
+        }
+
+        Ok(MmapRecords {
+            map,
+            endian,
+            layout: Layout::Log(offsets),
+        })
+    }
+
+    /// Maps a file of back-to-back `record_size`-byte records, after `header` bytes that are
+    /// skipped. Trailing bytes that don't make up a whole record are ignored.
+    ///
+    /// # Safety
+    ///
+    /// As for `open_log`.
+    pub unsafe fn open_fixed(
+        path: impl AsRef<Path>,
+        header: usize,
+        record_size: usize,
+        endian: Endianness,
+    ) -> Result<Self, LogError> {
+        if record_size == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "record size is zero").into());
+        }
+
+        let map = Mmap::map(&File::open(path)?)?;
+        let count = map.len().saturating_sub(header) / record_size;
+        Ok(MmapRecords {
+            map,
+            endian,
+            layout: Layout::Fixed {
+                offset: header,
+                size: record_size,
+                count,
+            },
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.layout {
+            Layout::Fixed { count, .. } => *count,
+            Layout::Log(offsets) => offsets.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn endian(&self) -> Endianness {
+        self.endian
+    }
+
+    /// Record `n`, still undecoded.
+    pub fn record(&self, n: usize) -> Option<Record<'_>> {
+        match &self.layout {
+            Layout::Fixed {
+                offset,
+                size,
+                count,
+            } => (n < *count).then(|| {
+                let start = offset + n * size;
+                Record {
+                    payload: &self.map[start..start + size],
+                    checksum: None,
+                    endian: self.endian,
+                }
+            }),
+            Layout::Log(offsets) => {
+                let start = *offsets.get(n)? as usize;
+                let (length, _) = u32::deserialize(&self.map[start..], self.endian).ok()?;
+                let end = start + 4 + length as usize;
+                Some(Record {
+                    payload: &self.map[start + 4..end],
+                    checksum: Some(&self.map[start..end + 4]),
+                    endian: self.endian,
+                })
+            }
+        }
+    }
+
+    /// Decodes record `n`, or returns `None` if there are no more than `n` records.
+    pub fn get<'a, T: DeserializeBorrowed<'a>>(&'a self, n: usize) -> Result<Option<T>, LogError> {
+        self.record(n).map(|record| record.decode()).transpose()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Record<'_>> + '_ {
+        (0..self.len()).filter_map(|n| self.record(n))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Record<'a> {
+    payload: &'a [u8],
+    /// The whole frame, for records that carry a checksum.
+    checksum: Option<&'a [u8]>,
+    endian: Endianness,
+}
+
+impl<'a> Record<'a> {
+    pub fn bytes(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// Checks the record's CRC-32C, if it has one.
+    pub fn verify(&self) -> Result<(), LogError> {
+        let Some(frame) = self.checksum else {
+            return Ok(());
+        };
+        let end = frame.len() - 4;
+        let (found, _) = u32::deserialize(&frame[end..], self.endian)?;
+        let expected = crc32c(&frame[..end]);
+        if found != expected {
+            return Err(FrameError::ChecksumMismatch { expected, found }.into());
+        }
+        Ok(())
+    }
+
+    /// Verifies the record and decodes it, requiring the value to take up the whole record.
+    pub fn decode<T: DeserializeBorrowed<'a>>(&self) -> Result<T, LogError> {
+        self.verify()?;
+        let (value, size) = T::deserialize_borrowed(self.payload, self.endian)?;
+        if size != self.payload.len() {
+            return Err(DeserializeError::Invalid.into());
+        }
+        Ok(value)
+    }
+}
@@ -0,0 +1,371 @@
+//! Append-only files of dryb records.
+//!
+//! A log is laid out as
+//!
+//! ```text
+//! magic "DRLG" | format version: u16 | endianness: u8 | schema fingerprint: u64 | records...
+//! ```
+//!
+//! with the header fields little-endian. Each record is a frame with a `u32` length and a
+//! CRC-32C trailer, written in the byte order recorded in the header. When a log is opened, a
+//! final record that was only partly written, or whose checksum fails, is cut off the file.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::{error, fmt};
+
+use crate::frame::{Config, Frame, FrameError, FrameReader, Length};
+use crate::{Deserialize, DeserializeError, Endianness, Schema, Serialize, SerializeError};
+
+const MAGIC: &[u8; 4] = b"DRLG";
+pub const FORMAT_VERSION: u16 = 1;
+pub(crate) const HEADER_SIZE: u64 = 15;
+/// The length before a record's payload and the checksum after it.
+pub(crate) const RECORD_OVERHEAD: u64 = 8;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    /// Byte order of new logs. An existing log keeps the one in its header.
+    pub endian: Endianness,
+    /// Remember the file offset of every `index_interval`th record, so `get` only has to skip
+    /// fewer than that many records. With `None` it scans from the start.
+    pub index_interval: Option<usize>,
+    /// Records with longer payloads are refused when appending and treated as corrupt when
+    /// reading.
+    pub max_record_length: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            endian: Endianness::Little,
+            index_interval: None,
+            max_record_length: 16 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LogError {
+    Io(io::Error),
+    /// The file doesn't start with a record log header.
+    BadHeader,
+    UnsupportedVersion(u16),
+    /// The log was written for a different schema.
+    SchemaMismatch {
+        expected: u64,
+        found: u64,
+    },
+    /// A record before the last one is damaged, which truncation can't repair.
+    Corrupt {
+        offset: u64,
+        error: FrameError,
+    },
+    Frame(FrameError),
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogError::Io(e) => e.fmt(f),
+            LogError::BadHeader => write!(f, "Not a record log"),
+            LogError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported record log version {}", version)
+            }
+            LogError::SchemaMismatch { expected, found } => write!(
+                f,
+                "Schema mismatch: expected fingerprint {:016x}, found {:016x}",
+                expected, found
+            ),
+            LogError::Corrupt { offset, error } => {
+                write!(f, "Corrupt record at offset {}: {}", offset, error)
+            }
+            LogError::Frame(e) => e.fmt(f),
+        }
+    }
+}
+
+impl error::Error for LogError {}
+
+impl From<io::Error> for LogError {
+    fn from(e: io::Error) -> Self {
+        LogError::Io(e)
+    }
+}
+
+impl From<FrameError> for LogError {
+    fn from(e: FrameError) -> Self {
+        LogError::Frame(e)
+    }
+}
+
+impl From<SerializeError> for LogError {
+    fn from(e: SerializeError) -> Self {
+        LogError::Frame(e.into())
+    }
+}
+
+impl From<DeserializeError> for LogError {
+    fn from(e: DeserializeError) -> Self {
+        LogError::Frame(e.into())
+    }
+}
+
+/// Checks a log header against the schema fingerprint `expected` and returns the log's byte
+/// order.
+pub(crate) fn read_header(header: &[u8], expected: u64) -> Result<Endianness, LogError> {
+    if header.len() < HEADER_SIZE as usize || &header[..4] != MAGIC {
+        return Err(LogError::BadHeader);
+    }
+    let version = u16::from_le_bytes([header[4], header[5]]);
+    if version != FORMAT_VERSION {
+        return Err(LogError::UnsupportedVersion(version));
+    }
+    let endian = match header[6] {
+        0 => Endianness::Little,
+        1 => Endianness::Big,
+        _ => return Err(LogError::BadHeader),
+    };
+    let found = u64::from_le_bytes(header[7..15].try_into().unwrap());
+    if found != expected {
+        return Err(LogError::SchemaMismatch { expected, found });
+    }
+    Ok(endian)
+}
+
+pub struct RecordLog<T> {
+    path: PathBuf,
+    file: File,
+    config: Config,
+    len: u64,
+    /// File offset just past the last record.
+    end: u64,
+    index_interval: Option<usize>,
+    index: Vec<u64>,
+    truncated: u64,
+    buf: Vec<u8>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Schema + Serialize + Deserialize> RecordLog<T> {
+    /// Creates an empty log, replacing any file at `path`.
+    pub fn create(path: impl AsRef<Path>, options: Options) -> Result<Self, LogError> {
+        let path = path.as_ref();
+        let endian = match options.endian {
+            Endianness::Native if cfg!(target_endian = "big") => Endianness::Big,
+            Endianness::Native => Endianness::Little,
+            endian => endian,
+        };
+
+        let mut header = Vec::with_capacity(HEADER_SIZE as usize);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        header.push(match endian {
+            Endianness::Big => 1,
+            _ => 0,
+        });
+        header.extend_from_slice(&T::FINGERPRINT.to_le_bytes());
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.write_all(&header)?;
+        Ok(Self::with_file(path, file, endian, &options))
+    }
+
+    /// Opens an existing log, checking its header and cutting off a torn final record.
+    pub fn open(path: impl AsRef<Path>, options: Options) -> Result<Self, LogError> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+
+        let mut header = [0u8; HEADER_SIZE as usize];
+        file.read_exact(&mut header).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => LogError::BadHeader,
+            _ => e.into(),
+        })?;
+        let endian = read_header(&header, T::FINGERPRINT)?;
+
+        let file_len = file.metadata()?.len();
+        let mut log = Self::with_file(path, file, endian, &options);
+        log.scan(file_len)?;
+        if log.end < file_len {
+            log.file.set_len(log.end)?;
+            log.truncated = file_len - log.end;
+        }
+        Ok(log)
+    }
+
+    fn with_file(path: &Path, file: File, endian: Endianness, options: &Options) -> Self {
+        RecordLog {
+            path: path.to_path_buf(),
+            file,
+            config: Config {
+                length: Length::U32,
+                checksum: true,
+                max_length: options.max_record_length,
+                endian,
+                ..Config::default()
+            },
+            len: 0,
+            end: HEADER_SIZE,
+            index_interval: options.index_interval.filter(|interval| *interval > 0),
+            index: Vec::new(),
+            truncated: 0,
+            buf: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Walks the records to count them and find where the last whole one ends.
+    fn scan(&mut self, file_len: u64) -> Result<(), LogError> {
+        let mut reader = BufReader::new(self.file.try_clone()?);
+        reader.seek(SeekFrom::Start(HEADER_SIZE))?;
+        let mut frame = Vec::new();
+        loop {
+            let remaining = file_len - self.end;
+            if remaining < RECORD_OVERHEAD {
+                return Ok(());
+            }
+            frame.resize(4, 0);
+            reader.read_exact(&mut frame)?;
+            let (length, _) = u32::deserialize(&frame, self.config.endian)?;
+            if length as u64 > self.config.max_length as u64 {
+                return Err(LogError::Corrupt {
+                    offset: self.end,
+                    error: FrameError::Oversized {
+                        length: length as u64,
+                        max: self.config.max_length,
+                    },
+                });
+            }
+            // With the length in bounds, a record running past the end leaves a tail shorter
+            // than one maximum-size record, as a torn append does.
+            let size = length as u64 + RECORD_OVERHEAD;
+            if size > remaining {
+                return Ok(());
+            }
+
+            frame.resize(size as usize, 0);
+            reader.read_exact(&mut frame[4..])?;
+            if let Err(error) = self.config.decode(&frame) {
+                if size == remaining {
+                    return Ok(());
+                }
+                return Err(LogError::Corrupt {
+                    offset: self.end,
+                    error,
+                });
+            }
+            self.push(size);
+        }
+    }
+
+    /// Records that a record of `size` bytes now ends the log.
+    fn push(&mut self, size: u64) {
+        if let Some(interval) = self.index_interval {
+            if self.len.is_multiple_of(interval as u64) {
+                self.index.push(self.end);
+            }
+        }
+        self.len += 1;
+        self.end += size;
+    }
+
+    /// Appends a record and returns its position in the log.
+    pub fn append(&mut self, value: &T) -> Result<u64, LogError> {
+        let frame = Frame::new(0, value, self.config.endian)?;
+        self.buf.clear();
+        self.config.encode(&frame, &mut self.buf)?;
+        self.file.seek(SeekFrom::Start(self.end))?;
+        self.file.write_all(&self.buf)?;
+        self.push(self.buf.len() as u64);
+        Ok(self.len - 1)
+    }
+
+    /// Flushes appended records to the disk.
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn endian(&self) -> Endianness {
+        self.config.endian
+    }
+
+    /// How many bytes of a torn final record `open` cut off.
+    pub fn truncated(&self) -> u64 {
+        self.truncated
+    }
+
+    /// Iterates over the records from the start.
+    pub fn iter(&self) -> Result<Iter<T>, LogError> {
+        self.iter_from(HEADER_SIZE, self.len)
+    }
+
+    /// Reads record `n`, or `None` if the log has no more than `n` records.
+    pub fn get(&self, n: u64) -> Result<Option<T>, LogError> {
+        if n >= self.len {
+            return Ok(None);
+        }
+
+        let (offset, skip) = match self.index_interval {
+            Some(interval) => {
+                let interval = interval as u64;
+                (self.index[(n / interval) as usize], n % interval)
+            }
+            None => (HEADER_SIZE, n),
+        };
+        let mut iter = self.iter_from(offset, skip + 1)?;
+        for _ in 0..skip {
+            iter.reader.read_frame()?;
+        }
+        iter.next().transpose()
+    }
+
+    /// Iterates over `count` records starting at file offset `offset`. The file is opened again,
+    /// so iterators don't share a cursor with each other or with `append`.
+    fn iter_from(&self, offset: u64, count: u64) -> Result<Iter<T>, LogError> {
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let reader = BufReader::new(file.take(self.end - offset));
+        Ok(Iter {
+            reader: FrameReader::new(reader, self.config.clone()),
+            remaining: count,
+            marker: PhantomData,
+        })
+    }
+}
+
+pub struct Iter<T> {
+    reader: FrameReader<BufReader<io::Take<File>>>,
+    remaining: u64,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Deserialize> Iterator for Iter<T> {
+    type Item = Result<T, LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        match self.reader.read() {
+            Ok(Some(value)) => Some(Ok(value)),
+            Ok(None) => Some(Err(FrameError::Truncated.into())),
+            Err(e) => Some(Err(e.into())),
+        }
+    }
+}
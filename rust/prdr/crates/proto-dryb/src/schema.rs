@@ -0,0 +1,386 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::endian::Endianness;
+use crate::fingerprint::{combine, hash};
+use crate::{Deserialize, DeserializeError, Serialize, SerializeError};
+
+/// Describes how a type is laid out on the wire.
+///
+/// `#[derive(Serialize)]` implements this too, so its field types need it as well. Recursive
+/// types and types with hand-written fields opt out with `#[dryb(no_schema)]`, and a type that
+/// only derives `Deserialize` adds `#[derive(Schema)]`.
+pub trait Schema {
+    /// Stable hash of the schema, equal to `fingerprint::of(&Self::schema())`.
+    const FINGERPRINT: u64;
+
+    fn schema() -> Type;
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(no_schema)]
+#[cfg_attr(feature = "async", dryb(async))]
+pub enum Type {
+    Primitive(Primitive),
+    Option(Box<Type>),
+    /// A `u32` count, then the items.
+    Vec {
+        item: Box<Type>,
+    },
+    /// A `u32` byte length, then UTF-8.
+    String,
+    Array {
+        len: u32,
+        item: Box<Type>,
+    },
+    Struct(Struct),
+    Enum(Enum),
+    /// A `u32` count, then the entries.
+    Map {
+        key: Box<Type>,
+        value: Box<Type>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "async", dryb(async))]
+pub enum Primitive {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+    Bool,
+}
+
+impl Primitive {
+    /// Encoded size in bytes.
+    pub fn width(self) -> usize {
+        match self {
+            Primitive::U8 | Primitive::I8 | Primitive::Bool => 1,
+            Primitive::U16 | Primitive::I16 => 2,
+            Primitive::U32 | Primitive::I32 | Primitive::F32 => 4,
+            Primitive::U64 | Primitive::I64 | Primitive::F64 => 8,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Primitive::U8 => "u8",
+            Primitive::I8 => "i8",
+            Primitive::U16 => "u16",
+            Primitive::I16 => "i16",
+            Primitive::U32 => "u32",
+            Primitive::I32 => "i32",
+            Primitive::U64 => "u64",
+            Primitive::I64 => "i64",
+            Primitive::F32 => "f32",
+            Primitive::F64 => "f64",
+            Primitive::Bool => "bool",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(no_schema)]
+#[cfg_attr(feature = "async", dryb(async))]
+pub struct Struct {
+    pub name: String,
+    pub endian: Option<Endianness>,
+    pub encoding: StructEncoding,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "async", dryb(async))]
+pub enum StructEncoding {
+    Positional,
+    /// `#[dryb(version = N)]`: a version and body length header precede the fields.
+    Versioned(u16),
+    /// `#[dryb(tagged)]`: every field carries an id and a length.
+    Tagged,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(no_schema)]
+#[cfg_attr(feature = "async", dryb(async))]
+pub struct Field {
+    /// The field name, or its position for tuple variants.
+    pub name: String,
+    pub ty: Type,
+    pub endian: Option<Endianness>,
+    pub since: Option<u16>,
+    pub id: Option<u16>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(no_schema)]
+#[cfg_attr(feature = "async", dryb(async))]
+pub struct Enum {
+    pub name: String,
+    pub endian: Option<Endianness>,
+    pub length_prefixed: bool,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(no_schema)]
+#[cfg_attr(feature = "async", dryb(async))]
+pub struct Variant {
+    pub name: String,
+    pub tag: u8,
+    pub kind: VariantKind,
+    pub fields: Vec<Field>,
+    /// Marked `#[dryb(other)]`: unknown tags decode into this variant. If it has a single
+    /// field, that field holds the unknown tag rather than encoded data.
+    pub other: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "async", dryb(async))]
+pub enum VariantKind {
+    Unit,
+    Tuple,
+    Struct,
+}
+
+impl Enum {
+    pub fn variant(&self, tag: u8) -> Option<&Variant> {
+        self.variants
+            .iter()
+            .find(|v| v.tag == tag && !v.captures_tag())
+            .or_else(|| self.variants.iter().find(|v| v.other))
+    }
+}
+
+impl Variant {
+    /// Whether this is an `Unknown(u8)` style catch-all that stores the tag itself.
+    pub fn captures_tag(&self) -> bool {
+        self.other && self.fields.len() == 1
+    }
+}
+
+impl Schema for u8 {
+    const FINGERPRINT: u64 = hash("u8");
+
+    fn schema() -> Type {
+        Type::Primitive(Primitive::U8)
+    }
+}
+
+impl Schema for i8 {
+    const FINGERPRINT: u64 = hash("i8");
+
+    fn schema() -> Type {
+        Type::Primitive(Primitive::I8)
+    }
+}
+
+impl Schema for u16 {
+    const FINGERPRINT: u64 = hash("u16");
+
+    fn schema() -> Type {
+        Type::Primitive(Primitive::U16)
+    }
+}
+
+impl Schema for i16 {
+    const FINGERPRINT: u64 = hash("i16");
+
+    fn schema() -> Type {
+        Type::Primitive(Primitive::I16)
+    }
+}
+
+impl Schema for u32 {
+    const FINGERPRINT: u64 = hash("u32");
+
+    fn schema() -> Type {
+        Type::Primitive(Primitive::U32)
+    }
+}
+
+impl Schema for i32 {
+    const FINGERPRINT: u64 = hash("i32");
+
+    fn schema() -> Type {
+        Type::Primitive(Primitive::I32)
+    }
+}
+
+impl Schema for u64 {
+    const FINGERPRINT: u64 = hash("u64");
+
+    fn schema() -> Type {
+        Type::Primitive(Primitive::U64)
+    }
+}
+
+impl Schema for i64 {
+    const FINGERPRINT: u64 = hash("i64");
+
+    fn schema() -> Type {
+        Type::Primitive(Primitive::I64)
+    }
+}
+
+impl Schema for f32 {
+    const FINGERPRINT: u64 = hash("f32");
+
+    fn schema() -> Type {
+        Type::Primitive(Primitive::F32)
+    }
+}
+
+impl Schema for f64 {
+    const FINGERPRINT: u64 = hash("f64");
+
+    fn schema() -> Type {
+        Type::Primitive(Primitive::F64)
+    }
+}
+
+impl Schema for bool {
+    const FINGERPRINT: u64 = hash("bool");
+
+    fn schema() -> Type {
+        Type::Primitive(Primitive::Bool)
+    }
+}
+
+impl<T: Schema> Schema for Option<T> {
+    const FINGERPRINT: u64 = combine(&[hash("option"), T::FINGERPRINT]);
+
+    fn schema() -> Type {
+        Type::Option(Box::new(T::schema()))
+    }
+}
+
+impl<T: Schema> Schema for Vec<T> {
+    const FINGERPRINT: u64 = combine(&[hash("vec"), hash("u32"), T::FINGERPRINT]);
+
+    fn schema() -> Type {
+        Type::Vec {
+            item: Box::new(T::schema()),
+        }
+    }
+}
+
+impl<K: Schema, V: Schema> Schema for HashMap<K, V> {
+    const FINGERPRINT: u64 = combine(&[hash("map"), hash("u32"), K::FINGERPRINT, V::FINGERPRINT]);
+
+    fn schema() -> Type {
+        Type::Map {
+            key: Box::new(K::schema()),
+            value: Box::new(V::schema()),
+        }
+    }
+}
+
+impl<K: Schema, V: Schema> Schema for BTreeMap<K, V> {
+    const FINGERPRINT: u64 = combine(&[hash("map"), hash("u32"), K::FINGERPRINT, V::FINGERPRINT]);
+
+    fn schema() -> Type {
+        Type::Map {
+            key: Box::new(K::schema()),
+            value: Box::new(V::schema()),
+        }
+    }
+}
+
+impl<T: Schema + ?Sized> Schema for Box<T> {
+    const FINGERPRINT: u64 = T::FINGERPRINT;
+
+    fn schema() -> Type {
+        T::schema()
+    }
+}
+
+impl<T: Schema + ?Sized> Schema for &T {
+    const FINGERPRINT: u64 = T::FINGERPRINT;
+
+    fn schema() -> Type {
+        T::schema()
+    }
+}
+
+impl<T: Schema> Schema for [T] {
+    const FINGERPRINT: u64 = Vec::<T>::FINGERPRINT;
+
+    fn schema() -> Type {
+        Vec::<T>::schema()
+    }
+}
+
+impl Schema for str {
+    const FINGERPRINT: u64 = String::FINGERPRINT;
+
+    fn schema() -> Type {
+        String::schema()
+    }
+}
+
+impl Schema for String {
+    const FINGERPRINT: u64 = combine(&[hash("string"), hash("u32")]);
+
+    fn schema() -> Type {
+        Type::String
+    }
+}
+
+impl<T: Schema, const N: usize> Schema for [T; N] {
+    const FINGERPRINT: u64 = combine(&[hash("array"), N as u64, T::FINGERPRINT]);
+
+    fn schema() -> Type {
+        Type::Array {
+            len: N as u32,
+            item: Box::new(T::schema()),
+        }
+    }
+}
+
+const SCHEMA_FILE_MAGIC: &[u8; 4] = b"DRYS";
+const SCHEMA_FILE_VERSION: u16 = 1;
+const SCHEMA_FILE_HEADER_SIZE: usize = 6;
+
+/// Encodes a schema tree as a schema file, for checking in or handing to other tools. These
+/// files are named `.drys`, after their magic, to keep them apart from `.dryb` sources.
+pub fn export(ty: &Type) -> Vec<u8> {
+    let mut buffer = vec![0u8; 256];
+    loop {
+        match ty.serialize(&mut buffer[SCHEMA_FILE_HEADER_SIZE..], Endianness::Little) {
+            Ok(size) => {
+                buffer.truncate(SCHEMA_FILE_HEADER_SIZE + size);
+                break;
+            }
+            Err(SerializeError::BufferOverflow) => buffer.resize(buffer.len() * 2, 0),
+            Err(error) => unreachable!("schema trees always encode: {}", error),
+        }
+    }
+
+    buffer[..4].copy_from_slice(SCHEMA_FILE_MAGIC);
+    buffer[4..SCHEMA_FILE_HEADER_SIZE].copy_from_slice(&SCHEMA_FILE_VERSION.to_le_bytes());
+    buffer
+}
+
+/// Decodes a schema file written by [`export`].
+pub fn import(bytes: &[u8]) -> Result<Type, DeserializeError> {
+    if bytes.len() < SCHEMA_FILE_HEADER_SIZE
+        || &bytes[..4] != SCHEMA_FILE_MAGIC
+        || bytes[4..SCHEMA_FILE_HEADER_SIZE] != SCHEMA_FILE_VERSION.to_le_bytes()
+    {
+        return Err(DeserializeError::Invalid);
+    }
+
+    let body = &bytes[SCHEMA_FILE_HEADER_SIZE..];
+    let (ty, size) = Type::deserialize(body, Endianness::Little)?;
+    if size != body.len() {
+        return Err(DeserializeError::Invalid);
+    }
+
+    Ok(ty)
+}
@@ -0,0 +1,677 @@
+//! The dryb wire format as a serde data format, for types that derive `serde::Serialize` and
+//! `serde::Deserialize` instead of the proto-dryb traits.
+//!
+//! A type encodes to the same bytes as the equivalent positional proto-dryb type: sequences,
+//! strings and maps get a u32 count prefix, tuples and arrays none, options a 0/1 flag and enum
+//! variants a u8 tag holding their index. Versioned, tagged and length-prefixed layouts have no
+//! serde counterpart.
+
+use std::fmt;
+
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize};
+
+use crate::{DeserializeError, Endianness, SerializeError};
+
+const LENGTH_SIZE: usize = 4;
+
+#[derive(Debug)]
+pub enum Error {
+    Serialize(SerializeError),
+    Deserialize(DeserializeError),
+    /// A serde data model construct with no dryb encoding.
+    Unsupported(&'static str),
+    Custom(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Serialize(e) => e.fmt(f),
+            Error::Deserialize(e) => e.fmt(f),
+            Error::Unsupported(what) => write!(f, "{} cannot be encoded as dryb", what),
+            Error::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<SerializeError> for Error {
+    fn from(e: SerializeError) -> Self {
+        Error::Serialize(e)
+    }
+}
+
+impl From<DeserializeError> for Error {
+    fn from(e: DeserializeError) -> Self {
+        Error::Deserialize(e)
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+/// Encodes `value` into `buf`, returning the number of bytes written.
+pub fn to_slice<T: Serialize + ?Sized>(
+    value: &T,
+    buf: &mut [u8],
+    endian: Endianness,
+) -> Result<usize, Error> {
+    let mut serializer = Serializer::new(buf, endian);
+    value.serialize(&mut serializer)?;
+    Ok(serializer.offset)
+}
+
+/// Encodes `value` into a new vector.
+pub fn to_vec<T: Serialize + ?Sized>(value: &T, endian: Endianness) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![0u8; 256];
+    loop {
+        match to_slice(value, &mut buf, endian) {
+            Ok(size) => {
+                buf.truncate(size);
+                return Ok(buf);
+            }
+            Err(Error::Serialize(SerializeError::BufferOverflow)) => buf.resize(buf.len() * 2, 0),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Decodes a `T` from the start of `buf`, returning it and the number of bytes read.
+pub fn from_slice<'de, T: de::Deserialize<'de>>(
+    buf: &'de [u8],
+    endian: Endianness,
+) -> Result<(T, usize), Error> {
+    let mut deserializer = Deserializer::new(buf, endian);
+    let value = T::deserialize(&mut deserializer)?;
+    Ok((value, deserializer.offset))
+}
+
+pub struct Serializer<'a> {
+    buf: &'a mut [u8],
+    offset: usize,
+    endian: Endianness,
+}
+
+impl<'a> Serializer<'a> {
+    pub fn new(buf: &'a mut [u8], endian: Endianness) -> Self {
+        Serializer {
+            buf,
+            offset: 0,
+            endian,
+        }
+    }
+
+    /// Bytes written so far.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn put<T: crate::Serialize>(&mut self, value: T) -> Result<(), Error> {
+        self.offset += value.serialize(&mut self.buf[self.offset..], self.endian)?;
+        Ok(())
+    }
+
+    fn put_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let end = self.offset + LENGTH_SIZE + bytes.len();
+        if end > self.buf.len() {
+            return Err(SerializeError::BufferOverflow.into());
+        }
+        self.put(bytes.len() as u32)?;
+        self.buf[self.offset..end].copy_from_slice(bytes);
+        self.offset = end;
+        Ok(())
+    }
+
+    fn put_tag(&mut self, index: u32) -> Result<(), Error> {
+        let tag = u8::try_from(index)
+            .map_err(|_| Error::Unsupported("an enum with over 256 variants"))?;
+        self.put(tag)
+    }
+
+    /// Reserves a u32 count prefix, filled in once the items have been written.
+    fn counted(&mut self) -> Result<Counted<'_, 'a>, Error> {
+        let start = self.offset;
+        self.put(0u32)?;
+        Ok(Counted {
+            serializer: self,
+            start,
+            count: 0,
+        })
+    }
+}
+
+/// A sequence or map whose count prefix is written when it ends.
+pub struct Counted<'s, 'a> {
+    serializer: &'s mut Serializer<'a>,
+    start: usize,
+    count: u32,
+}
+
+impl Counted<'_, '_> {
+    fn finish(self) -> Result<(), Error> {
+        let s = self.serializer;
+        crate::Serialize::serialize(&self.count, &mut s.buf[self.start..], s.endian)?;
+        Ok(())
+    }
+}
+
+impl<'s, 'a> ser::Serializer for &'s mut Serializer<'a> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Counted<'s, 'a>;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Counted<'s, 'a>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.put(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.put(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.put(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.put(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        self.put(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.put(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.put(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.put(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        self.put(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.put(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.put(v)
+    }
+
+    fn serialize_char(self, _: char) -> Result<(), Error> {
+        Err(Error::Unsupported("char"))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.put_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.put_bytes(v)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.put(0u8)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        self.put(1u8)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        variant_index: u32,
+        _: &'static str,
+    ) -> Result<(), Error> {
+        self.put_tag(variant_index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        variant_index: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.put_tag(variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Counted<'s, 'a>, Error> {
+        self.counted()
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        variant_index: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, Error> {
+        self.put_tag(variant_index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Counted<'s, 'a>, Error> {
+        self.counted()
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        variant_index: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self, Error> {
+        self.put_tag(variant_index)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl ser::SerializeSeq for Counted<'_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.count += 1;
+        value.serialize(&mut *self.serializer)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for Counted<'_, '_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.count += 1;
+        key.serialize(&mut *self.serializer)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.serializer)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+/// Tuples, structs and their variants are their fields back to back.
+macro_rules! impl_fields {
+    ($($trait:ident :: $method:ident ($($key:ty)?)),*) => {
+        $(
+            impl ser::$trait for &mut Serializer<'_> {
+                type Ok = ();
+                type Error = Error;
+
+                fn $method<T: Serialize + ?Sized>(
+                    &mut self,
+                    $(_: $key,)?
+                    value: &T,
+                ) -> Result<(), Error> {
+                    value.serialize(&mut **self)
+                }
+
+                fn end(self) -> Result<(), Error> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_fields!(
+    SerializeTuple::serialize_element(),
+    SerializeTupleStruct::serialize_field(),
+    SerializeTupleVariant::serialize_field(),
+    SerializeStruct::serialize_field(&'static str),
+    SerializeStructVariant::serialize_field(&'static str)
+);
+
+pub struct Deserializer<'de> {
+    buf: &'de [u8],
+    offset: usize,
+    endian: Endianness,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(buf: &'de [u8], endian: Endianness) -> Self {
+        Deserializer {
+            buf,
+            offset: 0,
+            endian,
+        }
+    }
+
+    /// Bytes read so far.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn take<T: crate::Deserialize>(&mut self) -> Result<T, Error> {
+        let (value, size) = T::deserialize(&self.buf[self.offset..], self.endian)?;
+        self.offset += size;
+        Ok(value)
+    }
+
+    fn take_bytes(&mut self) -> Result<&'de [u8], Error> {
+        let len = self.take::<u32>()? as usize;
+        let bytes = self
+            .buf
+            .get(self.offset..self.offset + len)
+            .ok_or(DeserializeError::Invalid)?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn take_str(&mut self) -> Result<&'de str, Error> {
+        std::str::from_utf8(self.take_bytes()?).map_err(|_| DeserializeError::Invalid.into())
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::Unsupported("a self-describing type"))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bool(self.take()?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i8(self.take()?)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i16(self.take()?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i32(self.take()?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i64(self.take()?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u8(self.take()?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u16(self.take()?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u32(self.take()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(self.take()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f32(self.take()?)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f64(self.take()?)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::Unsupported("char"))
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_str(self.take_str()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_bytes(self.take_bytes()?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.take::<u8>()? {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            _ => Err(DeserializeError::Invalid.into()),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let remaining = self.take::<u32>()? as usize;
+        visitor.visit_seq(Items {
+            deserializer: self,
+            remaining,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(Items {
+            deserializer: self,
+            remaining: len,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let remaining = self.take::<u32>()? as usize;
+        visitor.visit_map(Items {
+            deserializer: self,
+            remaining,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::Unsupported("an identifier"))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(Error::Unsupported("skipping an unknown value"))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// The remaining items of a sequence, tuple, struct or map.
+struct Items<'a, 'de> {
+    deserializer: &'a mut Deserializer<'de>,
+    remaining: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for Items<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        // Counts come from the payload, so don't let them drive allocations.
+        Some(self.remaining.min(4096))
+    }
+}
+
+impl<'de> de::MapAccess<'de> for Items<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(&mut *self.deserializer)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining.min(4096))
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let tag = self.take::<u8>()? as u32;
+        let value = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(tag))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
@@ -1,8 +1,59 @@
+use std::collections::{BTreeMap, HashMap};
+
 use crate::endian::Endianness;
 use crate::error::SerializeError;
 
 pub trait Serialize {
     fn serialize(&self, buffer: &mut [u8], endian: Endianness) -> Result<usize, SerializeError>;
+
+    /// Serializes `values` one after another, as `Vec` and arrays lay out their elements. The
+    /// primitives override this to convert the whole slice in one pass.
+    fn serialize_slice(
+        values: &[Self],
+        buffer: &mut [u8],
+        endian: Endianness,
+    ) -> Result<usize, SerializeError>
+    where
+        Self: Sized,
+    {
+        let mut offset = 0;
+        for value in values {
+            offset += value.serialize(&mut buffer[offset..], endian)?;
+        }
+        Ok(offset)
+    }
+}
+
+/// `serialize_slice` for a multi-byte primitive: one bounds check, then a loop over the whole
+/// slice that the compiler can vectorise.
+macro_rules! serialize_slice {
+    () => {
+        fn serialize_slice(
+            values: &[Self],
+            buffer: &mut [u8],
+            endian: Endianness,
+        ) -> Result<usize, SerializeError> {
+            let width = std::mem::size_of::<Self>();
+            let size = values.len() * width;
+            if buffer.len() < size {
+                return Err(SerializeError::BufferOverflow);
+            }
+
+            let chunks = buffer[..size].chunks_exact_mut(width).zip(values);
+            match endian {
+                Endianness::Little => {
+                    chunks.for_each(|(chunk, value)| chunk.copy_from_slice(&value.to_le_bytes()))
+                }
+                Endianness::Big => {
+                    chunks.for_each(|(chunk, value)| chunk.copy_from_slice(&value.to_be_bytes()))
+                }
+                Endianness::Native => {
+                    chunks.for_each(|(chunk, value)| chunk.copy_from_slice(&value.to_ne_bytes()))
+                }
+            }
+            Ok(size)
+        }
+    };
 }
 
 impl Serialize for u8 {
@@ -15,6 +66,19 @@ impl Serialize for u8 {
 
         Ok(1)
     }
+
+    fn serialize_slice(
+        values: &[Self],
+        buffer: &mut [u8],
+        _: Endianness,
+    ) -> Result<usize, SerializeError> {
+        if buffer.len() < values.len() {
+            return Err(SerializeError::BufferOverflow);
+        }
+
+        buffer[..values.len()].copy_from_slice(values);
+        Ok(values.len())
+    }
 }
 
 impl Serialize for i8 {
@@ -27,6 +91,22 @@ impl Serialize for i8 {
 
         Ok(1)
     }
+
+    fn serialize_slice(
+        values: &[Self],
+        buffer: &mut [u8],
+        _: Endianness,
+    ) -> Result<usize, SerializeError> {
+        if buffer.len() < values.len() {
+            return Err(SerializeError::BufferOverflow);
+        }
+
+        // SAFETY: `i8` has the size and alignment of `u8`, and every bit pattern is a valid `u8`.
+        let bytes =
+            unsafe { std::slice::from_raw_parts(values.as_ptr().cast::<u8>(), values.len()) };
+        buffer[..values.len()].copy_from_slice(bytes);
+        Ok(values.len())
+    }
 }
 
 impl Serialize for u16 {
@@ -38,10 +118,13 @@ impl Serialize for u16 {
         match endian {
             Endianness::Little => buffer[..2].copy_from_slice(&self.to_le_bytes()),
             Endianness::Big => buffer[..2].copy_from_slice(&self.to_be_bytes()),
+            Endianness::Native => buffer[..2].copy_from_slice(&self.to_ne_bytes()),
         }
 
         Ok(2)
     }
+
+    serialize_slice!();
 }
 
 impl Serialize for i16 {
@@ -53,10 +136,13 @@ impl Serialize for i16 {
         match endian {
             Endianness::Little => buffer[..2].copy_from_slice(&self.to_le_bytes()),
             Endianness::Big => buffer[..2].copy_from_slice(&self.to_be_bytes()),
+            Endianness::Native => buffer[..2].copy_from_slice(&self.to_ne_bytes()),
         }
 
         Ok(2)
     }
+
+    serialize_slice!();
 }
 
 impl Serialize for u32 {
@@ -68,10 +154,13 @@ impl Serialize for u32 {
         match endian {
             Endianness::Little => buffer[..4].copy_from_slice(&self.to_le_bytes()),
             Endianness::Big => buffer[..4].copy_from_slice(&self.to_be_bytes()),
+            Endianness::Native => buffer[..4].copy_from_slice(&self.to_ne_bytes()),
         }
 
         Ok(4)
     }
+
+    serialize_slice!();
 }
 
 impl Serialize for i32 {
@@ -83,10 +172,13 @@ impl Serialize for i32 {
         match endian {
             Endianness::Little => buffer[..4].copy_from_slice(&self.to_le_bytes()),
             Endianness::Big => buffer[..4].copy_from_slice(&self.to_be_bytes()),
+            Endianness::Native => buffer[..4].copy_from_slice(&self.to_ne_bytes()),
         }
 
         Ok(4)
     }
+
+    serialize_slice!();
 }
 
 impl Serialize for u64 {
@@ -98,10 +190,13 @@ impl Serialize for u64 {
         match endian {
             Endianness::Little => buffer[..8].copy_from_slice(&self.to_le_bytes()),
             Endianness::Big => buffer[..8].copy_from_slice(&self.to_be_bytes()),
+            Endianness::Native => buffer[..8].copy_from_slice(&self.to_ne_bytes()),
         }
 
         Ok(8)
     }
+
+    serialize_slice!();
 }
 
 impl Serialize for i64 {
@@ -113,10 +208,13 @@ impl Serialize for i64 {
         match endian {
             Endianness::Little => buffer[..8].copy_from_slice(&self.to_le_bytes()),
             Endianness::Big => buffer[..8].copy_from_slice(&self.to_be_bytes()),
+            Endianness::Native => buffer[..8].copy_from_slice(&self.to_ne_bytes()),
         }
 
         Ok(8)
     }
+
+    serialize_slice!();
 }
 
 impl Serialize for f32 {
@@ -128,10 +226,13 @@ impl Serialize for f32 {
         match endian {
             Endianness::Little => buffer[..4].copy_from_slice(&self.to_le_bytes()),
             Endianness::Big => buffer[..4].copy_from_slice(&self.to_be_bytes()),
+            Endianness::Native => buffer[..4].copy_from_slice(&self.to_ne_bytes()),
         }
 
         Ok(4)
     }
+
+    serialize_slice!();
 }
 
 impl Serialize for f64 {
@@ -143,10 +244,13 @@ impl Serialize for f64 {
         match endian {
             Endianness::Little => buffer[..8].copy_from_slice(&self.to_le_bytes()),
             Endianness::Big => buffer[..8].copy_from_slice(&self.to_be_bytes()),
+            Endianness::Native => buffer[..8].copy_from_slice(&self.to_ne_bytes()),
         }
 
         Ok(8)
     }
+
+    serialize_slice!();
 }
 
 impl Serialize for bool {
@@ -183,37 +287,73 @@ impl<T: Serialize> Serialize for Option<T> {
     }
 }
 
-const VEC_LENGTH_SIZE: usize = 4;
-impl<T: Serialize> Serialize for Vec<T> {
+impl<T: Serialize> Serialize for [T] {
     // TODO: think about max size of Vec
     fn serialize(&self, buf: &mut [u8], endian: Endianness) -> Result<usize, SerializeError> {
         let len = self.len() as u32;
-        let len_size = len.serialize(&mut buf[..VEC_LENGTH_SIZE], endian)?;
+        let len_size = len.serialize(buf, endian)?;
 
-        let mut offset = len_size;
-        for item in self {
-            let used = item.serialize(&mut buf[offset..], endian)?;
-            offset += used;
+        Ok(len_size + T::serialize_slice(self, &mut buf[len_size..], endian)?)
+    }
+}
+
+impl<T: Serialize> Serialize for Vec<T> {
+    fn serialize(&self, buf: &mut [u8], endian: Endianness) -> Result<usize, SerializeError> {
+        self.as_slice().serialize(buf, endian)
+    }
+}
+
+impl<K: Serialize, V: Serialize> Serialize for HashMap<K, V> {
+    fn serialize(&self, buf: &mut [u8], endian: Endianness) -> Result<usize, SerializeError> {
+        let mut offset = (self.len() as u32).serialize(buf, endian)?;
+        for (key, value) in self {
+            offset += key.serialize(&mut buf[offset..], endian)?;
+            offset += value.serialize(&mut buf[offset..], endian)?;
         }
 
         Ok(offset)
     }
 }
 
+impl<K: Serialize, V: Serialize> Serialize for BTreeMap<K, V> {
+    fn serialize(&self, buf: &mut [u8], endian: Endianness) -> Result<usize, SerializeError> {
+        let mut offset = (self.len() as u32).serialize(buf, endian)?;
+        for (key, value) in self {
+            offset += key.serialize(&mut buf[offset..], endian)?;
+            offset += value.serialize(&mut buf[offset..], endian)?;
+        }
+
+        Ok(offset)
+    }
+}
+
+impl<T: Serialize + ?Sized> Serialize for Box<T> {
+    fn serialize(&self, buf: &mut [u8], endian: Endianness) -> Result<usize, SerializeError> {
+        self.as_ref().serialize(buf, endian)
+    }
+}
+
+impl<T: Serialize + ?Sized> Serialize for &T {
+    fn serialize(&self, buf: &mut [u8], endian: Endianness) -> Result<usize, SerializeError> {
+        (**self).serialize(buf, endian)
+    }
+}
+
+impl Serialize for str {
+    fn serialize(&self, buf: &mut [u8], endian: Endianness) -> Result<usize, SerializeError> {
+        self.as_bytes().serialize(buf, endian)
+    }
+}
+
 impl Serialize for String {
     fn serialize(&self, buf: &mut [u8], endian: Endianness) -> Result<usize, SerializeError> {
-        self.as_bytes().to_vec().serialize(buf, endian) // TODO remove vec allocation
+        self.as_str().serialize(buf, endian)
     }
 }
 
 impl<T: Serialize, const N: usize> Serialize for [T; N] {
     fn serialize(&self, buffer: &mut [u8], endian: Endianness) -> Result<usize, SerializeError> {
-        let mut total_size = 0;
-        for item in self.iter() {
-            let size = item.serialize(&mut buffer[total_size..], endian)?;
-            total_size += size;
-        }
-        Ok(total_size)
+        T::serialize_slice(self, buffer, endian)
     }
 }
 
@@ -0,0 +1,654 @@
+use crate::deserialize::Deserialize;
+use crate::endian::Endianness;
+use crate::error::{DeserializeError, SerializeError};
+use crate::schema::{Enum, Field, Primitive, Struct, StructEncoding, Type};
+use crate::serialize::Serialize;
+
+const LENGTH_SIZE: usize = 4;
+const VERSION_HEADER_SIZE: usize = 6;
+const FIELD_HEADER_SIZE: usize = 6;
+const VARIANT_HEADER_SIZE: usize = 5;
+
+/// A decoded payload whose shape is only known at runtime, through a [`Type`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    Bool(bool),
+    Option(Option<Box<Value>>),
+    /// A `Vec` or a fixed-size array.
+    Seq(Vec<Value>),
+    String(String),
+    /// Entries in the order they appear on the wire.
+    Map(Vec<(Value, Value)>),
+    /// Fields by name. A tagged struct encodes only the fields listed.
+    Struct(Vec<(String, Value)>),
+    /// A versioned struct as it was read, which encodes back at the same version.
+    Versioned {
+        version: u16,
+        /// Fields by name, leaving out those newer than `version`.
+        fields: Vec<(String, Value)>,
+        /// The bytes after the fields the schema knows, added by a newer version.
+        rest: Vec<u8>,
+    },
+    /// A tagged struct as it was read, which encodes back with its entries in the same order.
+    Tagged {
+        /// Fields by name, leaving out those missing from the payload.
+        fields: Vec<(String, Value)>,
+        /// The ids of the entries in wire order, with the bytes of those the schema doesn't
+        /// know.
+        entries: Vec<(u16, Option<Vec<u8>>)>,
+    },
+    Variant {
+        name: String,
+        /// The tag read from the wire, which differs from the schema's for catch-all variants.
+        tag: u8,
+        fields: Vec<(String, Value)>,
+        /// The bytes of a length-prefixed body after the fields the schema knows: all of it
+        /// for an unknown variant.
+        rest: Vec<u8>,
+    },
+}
+
+impl Value {
+    /// Decodes a value laid out as `ty` describes, like `Deserialize::deserialize`.
+    pub fn decode(
+        ty: &Type,
+        buf: &[u8],
+        endian: Endianness,
+    ) -> Result<(Value, usize), DeserializeError> {
+        match ty {
+            Type::Primitive(primitive) => decode_primitive(*primitive, buf, endian),
+            Type::Option(item) => {
+                if buf.is_empty() {
+                    return Err(DeserializeError::Invalid);
+                }
+
+                match buf[0] {
+                    0 => Ok((Value::Option(None), 1)),
+                    1 => {
+                        let (value, size) = Value::decode(item, &buf[1..], endian)?;
+                        Ok((Value::Option(Some(Box::new(value))), size + 1))
+                    }
+                    _ => Err(DeserializeError::Invalid),
+                }
+            }
+            Type::Vec { item } => {
+                let (length, mut offset) = decode_length(buf, endian)?;
+                let mut items = Vec::new();
+                for _ in 0..length {
+                    let (value, size) = Value::decode(item, &buf[offset..], endian)?;
+                    items.push(value);
+                    offset += size;
+                }
+
+                Ok((Value::Seq(items), offset))
+            }
+            Type::String => {
+                let (length, offset) = decode_length(buf, endian)?;
+                let end = offset + length;
+                if buf.len() < end {
+                    return Err(DeserializeError::Invalid);
+                }
+
+                let string = std::str::from_utf8(&buf[offset..end])
+                    .map_err(|_| DeserializeError::Invalid)?;
+                Ok((Value::String(string.to_string()), end))
+            }
+            Type::Array { len, item } => {
+                let mut items = Vec::new();
+                let mut offset = 0;
+                for _ in 0..*len {
+                    let (value, size) = Value::decode(item, &buf[offset..], endian)?;
+                    items.push(value);
+                    offset += size;
+                }
+
+                Ok((Value::Seq(items), offset))
+            }
+            Type::Map { key, value } => {
+                let (length, mut offset) = decode_length(buf, endian)?;
+                let mut entries = Vec::new();
+                for _ in 0..length {
+                    let (k, size) = Value::decode(key, &buf[offset..], endian)?;
+                    offset += size;
+                    let (v, size) = Value::decode(value, &buf[offset..], endian)?;
+                    offset += size;
+                    entries.push((k, v));
+                }
+
+                Ok((Value::Map(entries), offset))
+            }
+            Type::Struct(s) => decode_struct(s, buf, endian),
+            Type::Enum(e) => decode_enum(e, buf, endian),
+        }
+    }
+
+    /// Encodes the value as `ty` describes. A value decoded with the same schema encodes
+    /// back to the bytes it was decoded from.
+    pub fn encode(
+        &self,
+        ty: &Type,
+        buf: &mut [u8],
+        endian: Endianness,
+    ) -> Result<usize, SerializeError> {
+        match (ty, self) {
+            (Type::Primitive(primitive), _) => encode_primitive(*primitive, self, buf, endian),
+            (Type::Option(item), Value::Option(value)) => {
+                if buf.is_empty() {
+                    return Err(SerializeError::BufferOverflow);
+                }
+
+                match value {
+                    Some(value) => {
+                        let size = value.encode(item, &mut buf[1..], endian)?;
+                        buf[0] = 1;
+                        Ok(size + 1)
+                    }
+                    None => {
+                        buf[0] = 0;
+                        Ok(1)
+                    }
+                }
+            }
+            (Type::Vec { item }, Value::Seq(items)) => {
+                let mut offset = encode_length(items.len(), buf, endian)?;
+                for value in items {
+                    offset += value.encode(item, &mut buf[offset..], endian)?;
+                }
+
+                Ok(offset)
+            }
+            (Type::String, Value::String(string)) => {
+                let offset = encode_length(string.len(), buf, endian)?;
+                let end = offset + string.len();
+                if buf.len() < end {
+                    return Err(SerializeError::BufferOverflow);
+                }
+
+                buf[offset..end].copy_from_slice(string.as_bytes());
+                Ok(end)
+            }
+            (Type::Array { len, item }, Value::Seq(items)) => {
+                if items.len() != *len as usize {
+                    return Err(SerializeError::ValueMismatch);
+                }
+
+                let mut offset = 0;
+                for value in items {
+                    offset += value.encode(item, &mut buf[offset..], endian)?;
+                }
+
+                Ok(offset)
+            }
+            (Type::Map { key, value }, Value::Map(entries)) => {
+                let mut offset = encode_length(entries.len(), buf, endian)?;
+                for (k, v) in entries {
+                    offset += k.encode(key, &mut buf[offset..], endian)?;
+                    offset += v.encode(value, &mut buf[offset..], endian)?;
+                }
+
+                Ok(offset)
+            }
+            (Type::Struct(s), Value::Struct(fields)) => encode_struct(s, fields, buf, endian),
+            (
+                Type::Struct(s),
+                Value::Versioned {
+                    version,
+                    fields,
+                    rest,
+                },
+            ) => encode_versioned(s, *version, fields, rest, buf, endian),
+            (Type::Struct(s), Value::Tagged { fields, entries }) => {
+                encode_tagged(s, fields, entries, buf, endian)
+            }
+            (
+                Type::Enum(e),
+                Value::Variant {
+                    name,
+                    tag,
+                    fields,
+                    rest,
+                },
+            ) => encode_enum(e, name, *tag, fields, rest, buf, endian),
+            _ => Err(SerializeError::ValueMismatch),
+        }
+    }
+
+    /// Looks up a field of a struct or enum variant by name.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Struct(fields)
+            | Value::Versioned { fields, .. }
+            | Value::Tagged { fields, .. }
+            | Value::Variant { fields, .. } => fields
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+fn decode_primitive(
+    primitive: Primitive,
+    buf: &[u8],
+    endian: Endianness,
+) -> Result<(Value, usize), DeserializeError> {
+    match primitive {
+        Primitive::U8 => u8::deserialize(buf, endian).map(|(v, size)| (Value::U8(v), size)),
+        Primitive::I8 => i8::deserialize(buf, endian).map(|(v, size)| (Value::I8(v), size)),
+        Primitive::U16 => u16::deserialize(buf, endian).map(|(v, size)| (Value::U16(v), size)),
+        Primitive::I16 => i16::deserialize(buf, endian).map(|(v, size)| (Value::I16(v), size)),
+        Primitive::U32 => u32::deserialize(buf, endian).map(|(v, size)| (Value::U32(v), size)),
+        Primitive::I32 => i32::deserialize(buf, endian).map(|(v, size)| (Value::I32(v), size)),
+        Primitive::U64 => u64::deserialize(buf, endian).map(|(v, size)| (Value::U64(v), size)),
+        Primitive::I64 => i64::deserialize(buf, endian).map(|(v, size)| (Value::I64(v), size)),
+        Primitive::F32 => f32::deserialize(buf, endian).map(|(v, size)| (Value::F32(v), size)),
+        Primitive::F64 => f64::deserialize(buf, endian).map(|(v, size)| (Value::F64(v), size)),
+        Primitive::Bool => bool::deserialize(buf, endian).map(|(v, size)| (Value::Bool(v), size)),
+    }
+}
+
+fn encode_primitive(
+    primitive: Primitive,
+    value: &Value,
+    buf: &mut [u8],
+    endian: Endianness,
+) -> Result<usize, SerializeError> {
+    match (primitive, value) {
+        (Primitive::U8, Value::U8(v)) => v.serialize(buf, endian),
+        (Primitive::I8, Value::I8(v)) => v.serialize(buf, endian),
+        (Primitive::U16, Value::U16(v)) => v.serialize(buf, endian),
+        (Primitive::I16, Value::I16(v)) => v.serialize(buf, endian),
+        (Primitive::U32, Value::U32(v)) => v.serialize(buf, endian),
+        (Primitive::I32, Value::I32(v)) => v.serialize(buf, endian),
+        (Primitive::U64, Value::U64(v)) => v.serialize(buf, endian),
+        (Primitive::I64, Value::I64(v)) => v.serialize(buf, endian),
+        (Primitive::F32, Value::F32(v)) => v.serialize(buf, endian),
+        (Primitive::F64, Value::F64(v)) => v.serialize(buf, endian),
+        (Primitive::Bool, Value::Bool(v)) => v.serialize(buf, endian),
+        _ => Err(SerializeError::ValueMismatch),
+    }
+}
+
+/// Reads the `u32` count or byte length in front of a collection.
+fn decode_length(buf: &[u8], endian: Endianness) -> Result<(usize, usize), DeserializeError> {
+    let (length, size) = u32::deserialize(buf, endian)?;
+    Ok((length as usize, size))
+}
+
+fn encode_length(
+    length: usize,
+    buf: &mut [u8],
+    endian: Endianness,
+) -> Result<usize, SerializeError> {
+    u32::try_from(length)
+        .map_err(|_| SerializeError::ValueMismatch)?
+        .serialize(buf, endian)
+}
+
+fn decode_field(
+    field: &Field,
+    buf: &[u8],
+    endian: Endianness,
+) -> Result<(Value, usize), DeserializeError> {
+    Value::decode(&field.ty, buf, field.endian.unwrap_or(endian))
+}
+
+fn decode_fields(
+    fields: &[Field],
+    buf: &[u8],
+    endian: Endianness,
+) -> Result<(Vec<(String, Value)>, usize), DeserializeError> {
+    let mut values = Vec::with_capacity(fields.len());
+    let mut offset = 0;
+    for field in fields {
+        let (value, size) = decode_field(field, &buf[offset..], endian)?;
+        values.push((field.name.clone(), value));
+        offset += size;
+    }
+
+    Ok((values, offset))
+}
+
+fn decode_struct(
+    s: &Struct,
+    buf: &[u8],
+    endian: Endianness,
+) -> Result<(Value, usize), DeserializeError> {
+    let endian = s.endian.unwrap_or(endian);
+    match s.encoding {
+        StructEncoding::Positional => {
+            let (values, size) = decode_fields(&s.fields, buf, endian)?;
+            Ok((Value::Struct(values), size))
+        }
+        StructEncoding::Versioned(_) => {
+            let (version, _) = u16::deserialize(buf, endian)?;
+            let (length, _) = u32::deserialize(&buf[2..], endian)?;
+            let end = VERSION_HEADER_SIZE + length as usize;
+            if buf.len() < end {
+                return Err(DeserializeError::Invalid);
+            }
+
+            let buf = &buf[..end];
+            let mut values = Vec::with_capacity(s.fields.len());
+            let mut offset = VERSION_HEADER_SIZE;
+            for field in &s.fields {
+                if field.since.is_some_and(|since| version < since) {
+                    continue;
+                }
+                let (value, size) = decode_field(field, &buf[offset..], endian)?;
+                values.push((field.name.clone(), value));
+                offset += size;
+            }
+
+            Ok((
+                Value::Versioned {
+                    version,
+                    fields: values,
+                    rest: buf[offset..].to_vec(),
+                },
+                end,
+            ))
+        }
+        StructEncoding::Tagged => {
+            let (length, _) = u32::deserialize(buf, endian)?;
+            let end = LENGTH_SIZE + length as usize;
+            if buf.len() < end {
+                return Err(DeserializeError::Invalid);
+            }
+
+            let buf = &buf[..end];
+            let mut values = vec![None; s.fields.len()];
+            let mut entries = Vec::new();
+            let mut offset = LENGTH_SIZE;
+            while offset < end {
+                let (id, _) = u16::deserialize(&buf[offset..], endian)?;
+                let (length, _) = u32::deserialize(&buf[offset + 2..], endian)?;
+                offset += FIELD_HEADER_SIZE;
+                let field_end = offset + length as usize;
+                if field_end > end {
+                    return Err(DeserializeError::Invalid);
+                }
+
+                match s.fields.iter().position(|f| f.id == Some(id)) {
+                    Some(index) => {
+                        let field = &s.fields[index];
+                        let (value, _) = decode_field(field, &buf[offset..field_end], endian)?;
+                        values[index] = Some((field.name.clone(), value));
+                        entries.push((id, None));
+                    }
+                    None => entries.push((id, Some(buf[offset..field_end].to_vec()))),
+                }
+                offset = field_end;
+            }
+
+            Ok((
+                Value::Tagged {
+                    fields: values.into_iter().flatten().collect(),
+                    entries,
+                },
+                end,
+            ))
+        }
+    }
+}
+
+fn find<'a>(values: &'a [(String, Value)], field: &Field) -> Option<&'a Value> {
+    values
+        .iter()
+        .find(|(name, _)| *name == field.name)
+        .map(|(_, value)| value)
+}
+
+fn encode_field(
+    field: &Field,
+    value: &Value,
+    buf: &mut [u8],
+    endian: Endianness,
+) -> Result<usize, SerializeError> {
+    value.encode(&field.ty, buf, field.endian.unwrap_or(endian))
+}
+
+fn encode_fields<'a>(
+    fields: impl IntoIterator<Item = &'a Field>,
+    values: &[(String, Value)],
+    buf: &mut [u8],
+    endian: Endianness,
+) -> Result<usize, SerializeError> {
+    let mut offset = 0;
+    for field in fields {
+        let value = find(values, field).ok_or(SerializeError::ValueMismatch)?;
+        offset += encode_field(field, value, &mut buf[offset..], endian)?;
+    }
+
+    Ok(offset)
+}
+
+fn encode_struct(
+    s: &Struct,
+    values: &[(String, Value)],
+    buf: &mut [u8],
+    endian: Endianness,
+) -> Result<usize, SerializeError> {
+    let endian = s.endian.unwrap_or(endian);
+    match s.encoding {
+        StructEncoding::Positional => encode_fields(&s.fields, values, buf, endian),
+        StructEncoding::Versioned(version) => {
+            encode_versioned(s, version, values, &[], buf, endian)
+        }
+        StructEncoding::Tagged => encode_tagged(s, values, &[], buf, endian),
+    }
+}
+
+/// Encodes a tagged struct, writing `entries` in their order first, the unknown ones as they
+/// were read, and then the known fields they don't list.
+fn encode_tagged(
+    s: &Struct,
+    values: &[(String, Value)],
+    entries: &[(u16, Option<Vec<u8>>)],
+    buf: &mut [u8],
+    endian: Endianness,
+) -> Result<usize, SerializeError> {
+    if s.encoding != StructEncoding::Tagged {
+        return Err(SerializeError::ValueMismatch);
+    }
+    let endian = s.endian.unwrap_or(endian);
+    if buf.len() < LENGTH_SIZE {
+        return Err(SerializeError::BufferOverflow);
+    }
+
+    let mut offset = LENGTH_SIZE;
+    for (id, bytes) in entries {
+        let buf = &mut buf[offset..];
+        offset += match bytes {
+            Some(bytes) => write_entry(*id, buf, endian, |buf| write_bytes(bytes, buf))?,
+            None => {
+                let field = s
+                    .fields
+                    .iter()
+                    .find(|field| field.id == Some(*id))
+                    .ok_or(SerializeError::ValueMismatch)?;
+                let Some(value) = find(values, field) else {
+                    continue;
+                };
+                write_entry(*id, buf, endian, |buf| {
+                    encode_field(field, value, buf, endian)
+                })?
+            }
+        };
+    }
+    for field in &s.fields {
+        let Some(value) = find(values, field) else {
+            continue;
+        };
+        let id = field.id.ok_or(SerializeError::ValueMismatch)?;
+        if entries.iter().any(|(entry, _)| *entry == id) {
+            continue;
+        }
+        offset += write_entry(id, &mut buf[offset..], endian, |buf| {
+            encode_field(field, value, buf, endian)
+        })?;
+    }
+    ((offset - LENGTH_SIZE) as u32).serialize(buf, endian)?;
+    Ok(offset)
+}
+
+/// Writes one entry of a tagged struct: its id and length, then the body `write` puts after
+/// them.
+fn write_entry(
+    id: u16,
+    buf: &mut [u8],
+    endian: Endianness,
+    write: impl FnOnce(&mut [u8]) -> Result<usize, SerializeError>,
+) -> Result<usize, SerializeError> {
+    if buf.len() < FIELD_HEADER_SIZE {
+        return Err(SerializeError::BufferOverflow);
+    }
+
+    id.serialize(buf, endian)?;
+    let size = write(&mut buf[FIELD_HEADER_SIZE..])?;
+    (size as u32).serialize(&mut buf[2..], endian)?;
+    Ok(FIELD_HEADER_SIZE + size)
+}
+
+/// Encodes a versioned struct at `version`, leaving out the fields added after it and
+/// following the known fields with `rest`.
+fn encode_versioned(
+    s: &Struct,
+    version: u16,
+    values: &[(String, Value)],
+    rest: &[u8],
+    buf: &mut [u8],
+    endian: Endianness,
+) -> Result<usize, SerializeError> {
+    if !matches!(s.encoding, StructEncoding::Versioned(_)) {
+        return Err(SerializeError::ValueMismatch);
+    }
+    let endian = s.endian.unwrap_or(endian);
+    if buf.len() < VERSION_HEADER_SIZE {
+        return Err(SerializeError::BufferOverflow);
+    }
+
+    let fields = s
+        .fields
+        .iter()
+        .filter(|field| field.since.is_none_or(|since| since <= version));
+    let mut offset = VERSION_HEADER_SIZE
+        + encode_fields(fields, values, &mut buf[VERSION_HEADER_SIZE..], endian)?;
+    offset += write_bytes(rest, &mut buf[offset..])?;
+    version.serialize(buf, endian)?;
+    ((offset - VERSION_HEADER_SIZE) as u32).serialize(&mut buf[2..], endian)?;
+    Ok(offset)
+}
+
+fn write_bytes(bytes: &[u8], buf: &mut [u8]) -> Result<usize, SerializeError> {
+    if buf.len() < bytes.len() {
+        return Err(SerializeError::BufferOverflow);
+    }
+
+    buf[..bytes.len()].copy_from_slice(bytes);
+    Ok(bytes.len())
+}
+
+fn decode_enum(
+    e: &Enum,
+    buf: &[u8],
+    endian: Endianness,
+) -> Result<(Value, usize), DeserializeError> {
+    let endian = e.endian.unwrap_or(endian);
+    if buf.is_empty() {
+        return Err(DeserializeError::Invalid);
+    }
+
+    let tag = buf[0];
+    let (buf, start) = if e.length_prefixed {
+        let (length, _) = u32::deserialize(&buf[1..], endian)?;
+        let end = VARIANT_HEADER_SIZE + length as usize;
+        if buf.len() < end {
+            return Err(DeserializeError::Invalid);
+        }
+        (&buf[..end], VARIANT_HEADER_SIZE)
+    } else {
+        (buf, 1)
+    };
+
+    let variant = e.variant(tag).ok_or(DeserializeError::Invalid)?;
+    let (fields, size) = if variant.captures_tag() {
+        (vec![(variant.fields[0].name.clone(), Value::U8(tag))], 0)
+    } else if variant.tag == tag {
+        decode_fields(&variant.fields, &buf[start..], endian)?
+    } else {
+        (Vec::new(), 0)
+    };
+    let end = if e.length_prefixed {
+        buf.len()
+    } else {
+        start + size
+    };
+
+    Ok((
+        Value::Variant {
+            name: variant.name.clone(),
+            tag,
+            fields,
+            rest: buf[start + size..end].to_vec(),
+        },
+        end,
+    ))
+}
+
+fn encode_enum(
+    e: &Enum,
+    name: &str,
+    tag: u8,
+    values: &[(String, Value)],
+    rest: &[u8],
+    buf: &mut [u8],
+    endian: Endianness,
+) -> Result<usize, SerializeError> {
+    let endian = e.endian.unwrap_or(endian);
+    let variant = e
+        .variants
+        .iter()
+        .find(|v| v.name == name)
+        .ok_or(SerializeError::ValueMismatch)?;
+    // A catch-all writes the tag it was read with, as long as that tag still decodes into it.
+    let tag = if variant.other { tag } else { variant.tag };
+    if e.variant(tag).is_none_or(|v| v.name != name) {
+        return Err(SerializeError::KnownTag(tag));
+    }
+    if !rest.is_empty() && !e.length_prefixed {
+        return Err(SerializeError::ValueMismatch);
+    }
+    let start = if e.length_prefixed {
+        VARIANT_HEADER_SIZE
+    } else {
+        1
+    };
+    if buf.len() < start {
+        return Err(SerializeError::BufferOverflow);
+    }
+
+    buf[0] = tag;
+    let mut size = if variant.captures_tag() || tag != variant.tag {
+        0
+    } else {
+        encode_fields(&variant.fields, values, &mut buf[start..], endian)?
+    };
+    size += write_bytes(rest, &mut buf[start + size..])?;
+    if e.length_prefixed {
+        (size as u32).serialize(&mut buf[1..], endian)?;
+    }
+
+    Ok(start + size)
+}
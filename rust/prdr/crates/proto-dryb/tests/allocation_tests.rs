@@ -0,0 +1,85 @@
+//! Counts allocations with a global allocator, which is why this is a test binary of its own.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use proto_dryb::{Deserialize, DeserializeError, Endianness, Serialize, SerializeError};
+
+/// Counts the allocations made on each thread, so other tests running alongside don't add to them.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Origin {
+    ip: String,
+    port: u16,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Log {
+    origin: Origin,
+    backup: Option<Origin>,
+    ray_id: String,
+    tags: Vec<String>,
+}
+
+fn log(i: u16) -> Log {
+    Log {
+        origin: Origin {
+            ip: format!("10.0.0.{}", i),
+            port: 8000 + i,
+        },
+        backup: Some(Origin {
+            ip: "backup".to_string(),
+            port: i,
+        }),
+        ray_id: format!("ray-{}", i),
+        tags: vec!["a".to_string(), format!("tag-{}", i)],
+    }
+}
+
+#[test]
+fn test_in_place_skips_allocations() {
+    let mut buffer = [0u8; 256];
+    let size = log(2).serialize(&mut buffer, Endianness::Little).unwrap();
+    let bytes = &buffer[..size];
+
+    let mut value = None;
+    let fresh = allocations(|| {
+        value = Some(Log::deserialize(bytes, Endianness::Little).unwrap().0);
+    });
+    assert_eq!(value, Some(log(2)));
+    assert_eq!(fresh, 6);
+
+    let mut target = log(1);
+    let in_place = allocations(|| {
+        target
+            .deserialize_in_place(bytes, Endianness::Little)
+            .unwrap();
+    });
+    assert_eq!(in_place, 0);
+    assert_eq!(target, log(2));
+}
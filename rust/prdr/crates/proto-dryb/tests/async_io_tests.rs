@@ -0,0 +1,295 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use proto_dryb::async_io::{AsyncDeserialize, AsyncSerialize, AsyncWrite};
+use proto_dryb::schema::Type;
+use proto_dryb::{
+    Deserialize, DeserializeError, Endianness, Fingerprinted, Schema, Serialize, SerializeError,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(endian = "big", async)]
+enum Sample {
+    Empty,
+    Scalar(#[dryb(endian = "little")] u32),
+    #[dryb(tag = 9)]
+    Pair {
+        left: i16,
+        right: Option<String>,
+    },
+    #[dryb(other)]
+    Unknown(u8),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(length_prefixed, async)]
+enum Command {
+    Stop,
+    Seek(u64),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(version = 2, async)]
+struct Header {
+    id: u32,
+    #[dryb(since = 2)]
+    name: String,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[dryb(tagged, async)]
+struct Extra {
+    #[dryb(id = 1)]
+    flags: [u8; 3],
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(async)]
+struct Batch {
+    header: Header,
+    extra: Extra,
+    command: Command,
+    samples: Vec<Sample>,
+    totals: BTreeMap<String, f64>,
+    #[dryb(endian = "big")]
+    checksum: u64,
+    payload: Vec<u8>,
+}
+
+fn batch() -> Batch {
+    Batch {
+        header: Header {
+            id: 7,
+            name: "batch".to_string(),
+        },
+        extra: Extra { flags: [1, 2, 3] },
+        command: Command::Seek(1 << 40),
+        samples: vec![
+            Sample::Empty,
+            Sample::Scalar(0xdeadbeef),
+            Sample::Pair {
+                left: -5,
+                right: Some("r".to_string()),
+            },
+            Sample::Unknown(200),
+        ],
+        totals: BTreeMap::from([("a".to_string(), 0.5), ("b".to_string(), -1.0)]),
+        checksum: 0x0102030405060708,
+        payload: (0..100_000).map(|i| i as u8).collect(),
+    }
+}
+
+async fn to_bytes<T: AsyncSerialize>(value: &T, endian: Endianness) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let size = value.serialize_async(&mut bytes, endian).await.unwrap();
+    assert_eq!(size, bytes.len());
+    bytes
+}
+
+fn sync_bytes<T: Serialize>(value: &T, endian: Endianness) -> Vec<u8> {
+    let mut buffer = vec![0u8; 1 << 20];
+    let size = value.serialize(&mut buffer, endian).unwrap();
+    buffer.truncate(size);
+    buffer
+}
+
+/// Checks the async impls write what the sync ones do and read it back.
+async fn assert_round_trip<T>(value: &T)
+where
+    T: AsyncSerialize + AsyncDeserialize + Serialize + PartialEq + Debug,
+{
+    for endian in [Endianness::Little, Endianness::Big] {
+        let bytes = to_bytes(value, endian).await;
+        assert_eq!(bytes, sync_bytes(value, endian), "{:?}", value);
+
+        let (decoded, size) = T::deserialize_async(&mut &bytes[..], endian).await.unwrap();
+        assert_eq!((&decoded, size), (value, bytes.len()));
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_async_matches_sync_layout() {
+    assert_round_trip(&batch()).await;
+    assert_round_trip(&Sample::Pair {
+        left: 1,
+        right: None,
+    })
+    .await;
+    assert_round_trip(&Command::Stop).await;
+    assert_round_trip(&[true, false, true]).await;
+    assert_round_trip(&Box::new(-3i64)).await;
+    assert_round_trip(&HashMap::from([(1u16, b'x')])).await;
+    assert_round_trip(&Fingerprinted(Header {
+        id: 1,
+        name: String::new(),
+    }))
+    .await;
+    assert_round_trip(&Type::Option(Box::new(Vec::<String>::schema()))).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_streams_through_a_small_pipe() {
+    let (mut writer, mut reader) = tokio::io::duplex(64);
+
+    let sender = tokio::spawn(async move {
+        let batch = batch();
+        batch
+            .serialize_async(&mut writer, Endianness::Big)
+            .await
+            .unwrap();
+        batch
+            .samples
+            .serialize_async(&mut writer, Endianness::Big)
+            .await
+            .unwrap()
+    });
+
+    let (decoded, _) = Batch::deserialize_async(&mut reader, Endianness::Big)
+        .await
+        .unwrap();
+    assert_eq!(decoded, batch());
+    let (samples, size) = Vec::<Sample>::deserialize_async(&mut reader, Endianness::Big)
+        .await
+        .unwrap();
+    assert_eq!(samples, batch().samples);
+    assert_eq!(sender.await.unwrap(), size);
+}
+
+/// Records each write it is handed.
+#[derive(Default)]
+struct CountingWriter {
+    bytes: Vec<u8>,
+    writes: usize,
+}
+
+impl AsyncWrite for CountingWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.writes += 1;
+        self.bytes.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_primitive_runs_are_written_in_chunks() {
+    let payload: Vec<u8> = (0..100_000).map(|i| i as u8).collect();
+    let mut writer = CountingWriter::default();
+    payload
+        .serialize_async(&mut writer, Endianness::Little)
+        .await
+        .unwrap();
+    assert_eq!(writer.bytes, sync_bytes(&payload, Endianness::Little));
+    assert_eq!(writer.writes, 1 + 100_000usize.div_ceil(512));
+
+    let values = [-1.5f64; 100];
+    let mut writer = CountingWriter::default();
+    values
+        .serialize_async(&mut writer, Endianness::Big)
+        .await
+        .unwrap();
+    assert_eq!(writer.bytes, sync_bytes(&values, Endianness::Big));
+    assert_eq!(writer.writes, 2);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_async_errors() {
+    let bytes = to_bytes(&batch(), Endianness::Little).await;
+    let err = Batch::deserialize_async(&mut &bytes[..bytes.len() - 1], Endianness::Little)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    let err = Sample::Unknown(9)
+        .serialize_async(&mut Vec::new(), Endianness::Little)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(matches!(
+        err.into_inner().unwrap().downcast_ref::<SerializeError>(),
+        Some(SerializeError::KnownTag(9))
+    ));
+
+    let err = Option::<u8>::deserialize_async(&mut &[2u8, 0][..], Endianness::Little)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(matches!(
+        err.into_inner().unwrap().downcast_ref::<DeserializeError>(),
+        Some(DeserializeError::Invalid)
+    ));
+
+    let err = String::deserialize_async(&mut &[1u8, 0, 0, 0, 0xff][..], Endianness::Little)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let bytes = to_bytes(&Fingerprinted(1u8), Endianness::Little).await;
+    let err = Fingerprinted::<u16>::deserialize_async(&mut &bytes[..], Endianness::Little)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.into_inner().unwrap().downcast_ref::<DeserializeError>(),
+        Some(DeserializeError::SchemaMismatch { .. })
+    ));
+
+    // A huge count doesn't allocate up front; the stream just runs out.
+    let err =
+        Vec::<u64>::deserialize_async(&mut &[0xffu8, 0xff, 0xff, 0xff][..], Endianness::Little)
+            .await
+            .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+/// Implements only the sync traits, by hand.
+#[derive(Debug, PartialEq)]
+struct Celsius(i16);
+
+impl Serialize for Celsius {
+    fn serialize(&self, buffer: &mut [u8], endian: Endianness) -> Result<usize, SerializeError> {
+        self.0.serialize(buffer, endian)
+    }
+}
+
+impl Deserialize for Celsius {
+    fn deserialize(buf: &[u8], endian: Endianness) -> Result<(Self, usize), DeserializeError> {
+        let (value, size) = i16::deserialize(buf, endian)?;
+        Ok((Celsius(value), size))
+    }
+}
+
+#[test]
+fn test_async_impls_are_opt_in() {
+    // Without `#[dryb(async)]` the derive doesn't ask `Celsius` for async impls.
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[dryb(no_schema)]
+    struct Reading {
+        sensor: u8,
+        temperature: Celsius,
+    }
+
+    let reading = Reading {
+        sensor: 3,
+        temperature: Celsius(-40),
+    };
+    let bytes = sync_bytes(&reading, Endianness::Big);
+    assert_eq!(bytes, [3, 0xff, 0xd8]);
+    assert_eq!(
+        Reading::deserialize(&bytes, Endianness::Big).unwrap(),
+        (reading, 3)
+    );
+}
@@ -0,0 +1,135 @@
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use proto_dryb::{Deserialize, DeserializeError, Endianness, Serialize, SerializeError, Slots};
+
+const ENDIANS: [Endianness; 3] = [Endianness::Little, Endianness::Big, Endianness::Native];
+
+/// The encoding of `values` written one element at a time.
+fn elementwise<T: Serialize>(values: &[T], endian: Endianness) -> Vec<u8> {
+    let mut buffer = vec![0u8; 8 * values.len() + 4];
+    let mut offset = 0;
+    for value in values {
+        offset += value.serialize(&mut buffer[offset..], endian).unwrap();
+    }
+    buffer.truncate(offset);
+    buffer
+}
+
+fn assert_bulk<T: Serialize + Deserialize + PartialEq + Debug + Copy, const N: usize>(
+    values: [T; N],
+) {
+    for endian in ENDIANS {
+        let expected = elementwise(&values, endian);
+
+        let mut buffer = vec![0u8; expected.len()];
+        assert_eq!(
+            values.serialize(&mut buffer, endian).unwrap(),
+            N * size_of::<T>()
+        );
+        assert_eq!(buffer, expected);
+        assert_eq!(
+            <[T; N]>::deserialize(&buffer, endian).unwrap(),
+            (values, buffer.len())
+        );
+
+        let vec = values.to_vec();
+        let mut buffer = vec![0u8; expected.len() + 4];
+        let size = vec.serialize(&mut buffer, endian).unwrap();
+        assert_eq!(&buffer[4..size], &expected[..]);
+        assert_eq!(Vec::<T>::deserialize(&buffer, endian).unwrap(), (vec, size));
+
+        assert!(matches!(
+            values.serialize(&mut vec![0u8; expected.len() - 1], endian),
+            Err(SerializeError::BufferOverflow)
+        ));
+        assert!(matches!(
+            <[T; N]>::deserialize(&expected[..expected.len() - 1], endian),
+            Err(DeserializeError::Invalid)
+        ));
+    }
+}
+
+#[test]
+fn test_bulk_paths_match_elementwise_encoding() {
+    assert_bulk([0u8, 1, 127, 128, 255]);
+    assert_bulk([0i8, -1, 127, -128]);
+    assert_bulk([1u16, 0x0102, u16::MAX]);
+    assert_bulk([-1i16, i16::MIN, 300]);
+    assert_bulk([1u32, 0x01020304, u32::MAX]);
+    assert_bulk([-1i32, i32::MIN, 70000]);
+    assert_bulk([1u64, 0x0102030405060708, u64::MAX]);
+    assert_bulk([-1i64, i64::MIN]);
+    assert_bulk([0.5f32, -1.25, f32::MAX]);
+    assert_bulk([0.5f64, -1.25, f64::MIN_POSITIVE]);
+}
+
+#[test]
+fn test_bulk_vec_lengths() {
+    // A length the payload can't hold is refused before anything is allocated.
+    let huge = [0xffu8, 0xff, 0xff, 0xff, 1, 2, 3, 4];
+    assert!(matches!(
+        Vec::<u64>::deserialize(&huge, Endianness::Little),
+        Err(DeserializeError::Invalid)
+    ));
+    assert!(Vec::<u8>::deserialize(&huge, Endianness::Little).is_err());
+
+    let empty = Vec::<u32>::new();
+    let mut buffer = [0u8; 4];
+    assert_eq!(empty.serialize(&mut buffer, Endianness::Big).unwrap(), 4);
+    assert_eq!(
+        Vec::<u32>::deserialize(&buffer, Endianness::Big).unwrap(),
+        (empty, 4)
+    );
+
+    let mut target = vec![9u16; 10];
+    let bytes = [2u8, 0, 0, 0, 1, 0, 2, 0];
+    assert_eq!(
+        target
+            .deserialize_in_place(&bytes, Endianness::Little)
+            .unwrap(),
+        8
+    );
+    assert_eq!(target, [1, 2]);
+}
+
+thread_local! {
+    static LIVE: Rc<()> = Rc::new(());
+}
+
+/// A one-byte value whose bulk path stops after the first slot but still reports success.
+struct ShortRun(#[allow(dead_code)] Rc<()>);
+
+impl Deserialize for ShortRun {
+    const FIXED_SIZE: Option<usize> = Some(1);
+
+    fn deserialize(_: &[u8], _: Endianness) -> Result<(Self, usize), DeserializeError> {
+        Ok((ShortRun(LIVE.with(Rc::clone)), 1))
+    }
+
+    fn deserialize_slots(
+        slots: &mut Slots<'_, Self>,
+        buf: &[u8],
+        endian: Endianness,
+    ) -> Result<usize, DeserializeError> {
+        let (value, size) = Self::deserialize(buf, endian)?;
+        slots.push(value);
+        Ok(size)
+    }
+}
+
+#[test]
+fn test_bulk_path_must_fill_every_slot() {
+    let bytes = [3u8, 0, 0, 0, 1, 2, 3];
+    assert!(matches!(
+        Vec::<ShortRun>::deserialize(&bytes, Endianness::Little),
+        Err(DeserializeError::Invalid)
+    ));
+    assert!(matches!(
+        <[ShortRun; 3]>::deserialize(&bytes[4..], Endianness::Little),
+        Err(DeserializeError::Invalid)
+    ));
+
+    // The values that were written are dropped with the error, not leaked.
+    assert_eq!(LIVE.with(Rc::strong_count), 1);
+}
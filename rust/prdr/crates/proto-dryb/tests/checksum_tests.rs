@@ -0,0 +1,122 @@
+use std::io;
+
+use proto_dryb::async_io::{AsyncDeserialize, AsyncSerialize};
+use proto_dryb::checksum::{crc32c, xxhash64, Crc32c, XxHash64};
+use proto_dryb::{
+    Checksummed, Deserialize, DeserializeError, Endianness, Serialize, SerializeError,
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(async)]
+struct Entry {
+    id: u32,
+    message: String,
+    tags: Vec<u16>,
+}
+
+fn entry() -> Entry {
+    Entry {
+        id: 42,
+        message: "disk is flaky".to_string(),
+        tags: vec![1, 2, 3],
+    }
+}
+
+fn to_bytes<T: Serialize>(value: &T, endian: Endianness) -> Vec<u8> {
+    let mut buffer = vec![0u8; 1024];
+    let size = value.serialize(&mut buffer, endian).unwrap();
+    buffer.truncate(size);
+    buffer
+}
+
+#[test]
+fn test_known_vectors() {
+    assert_eq!(crc32c(b""), 0);
+    assert_eq!(crc32c(b"123456789"), 0xe3069283);
+
+    assert_eq!(xxhash64(b"", 0), 0xef46db3751d8e999);
+    assert_eq!(xxhash64(b"a", 0), 0xd24ec4f1a98c6e5b);
+    assert_eq!(xxhash64(b"abc", 0), 0x44bc2cf5ad770999);
+    assert_eq!(xxhash64(b"abc", 1), 0xbea9ca8199328908);
+    assert_eq!(
+        xxhash64(b"Nobody inspects the spammish repetition", 0),
+        0xfbcea83c8a378bf1
+    );
+}
+
+#[test]
+fn test_round_trip_and_layout() {
+    for endian in [Endianness::Little, Endianness::Big] {
+        let payload = to_bytes(&entry(), endian);
+
+        let bytes = to_bytes(&Checksummed::<_, Crc32c>::new(entry()), endian);
+        assert_eq!(bytes.len(), 4 + payload.len() + 4);
+        assert_eq!(&bytes[4..4 + payload.len()], &payload[..]);
+        let (crc, _) = u32::deserialize(&bytes[4 + payload.len()..], endian).unwrap();
+        assert_eq!(crc, crc32c(&payload));
+        let (decoded, size) = Checksummed::<Entry>::deserialize(&bytes, endian).unwrap();
+        assert_eq!((decoded.into_inner(), size), (entry(), bytes.len()));
+
+        let bytes = to_bytes(&Checksummed::<_, XxHash64>::new(entry()), endian);
+        assert_eq!(bytes.len(), 4 + payload.len() + 8);
+        let (decoded, size) = Checksummed::<Entry, XxHash64>::deserialize(&bytes, endian).unwrap();
+        assert_eq!((decoded.0, size), (entry(), bytes.len()));
+    }
+}
+
+#[test]
+fn test_corruption_is_detected() {
+    let bytes = to_bytes(
+        &Checksummed::<_, XxHash64>::new(entry()),
+        Endianness::Little,
+    );
+    let mut corrupted = bytes.clone();
+    corrupted[6] ^= 0x10;
+    match Checksummed::<Entry, XxHash64>::deserialize(&corrupted, Endianness::Little) {
+        Err(DeserializeError::ChecksumMismatch { expected, found }) => {
+            assert_eq!(expected, xxhash64(&corrupted[4..bytes.len() - 8], 0));
+            assert_eq!(found, xxhash64(&bytes[4..bytes.len() - 8], 0));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let bytes = to_bytes(&Checksummed::<_, Crc32c>::new(entry()), Endianness::Big);
+    let mut corrupted = bytes.clone();
+    *corrupted.last_mut().unwrap() ^= 1;
+    assert!(matches!(
+        Checksummed::<Entry>::deserialize(&corrupted, Endianness::Big),
+        Err(DeserializeError::ChecksumMismatch { .. })
+    ));
+
+    assert!(matches!(
+        Checksummed::<Entry>::deserialize(&bytes[..bytes.len() - 5], Endianness::Big),
+        Err(DeserializeError::Invalid)
+    ));
+    assert!(matches!(
+        Checksummed::<_, Crc32c>::new(entry()).serialize(&mut [0u8; 10], Endianness::Big),
+        Err(SerializeError::BufferOverflow)
+    ));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_async_round_trip() {
+    let value = Checksummed::<_, XxHash64>::new(entry());
+    let mut bytes = Vec::new();
+    let size = value
+        .serialize_async(&mut bytes, Endianness::Big)
+        .await
+        .unwrap();
+    assert_eq!(bytes, to_bytes(&value, Endianness::Big));
+
+    let (decoded, read) =
+        Checksummed::<Entry, XxHash64>::deserialize_async(&mut &bytes[..], Endianness::Big)
+            .await
+            .unwrap();
+    assert_eq!((decoded, read), (value, size));
+
+    bytes[5] ^= 1;
+    let err = Checksummed::<Entry, XxHash64>::deserialize_async(&mut &bytes[..], Endianness::Big)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
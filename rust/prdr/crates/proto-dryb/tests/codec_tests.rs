@@ -0,0 +1,120 @@
+use bytes::BytesMut;
+use futures::{SinkExt, StreamExt};
+use tokio::io::AsyncWriteExt;
+use tokio_util::codec::{Decoder, Encoder, Framed, FramedRead};
+
+use proto_dryb::codec::{DrybCodec, FrameCodec};
+use proto_dryb::frame::{Config, Frame, FrameError, Length};
+use proto_dryb::{Deserialize, DeserializeError, Endianness, Serialize, SerializeError};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum Request {
+    Ping(u32),
+    Put { key: String, value: Vec<u8> },
+}
+
+fn config() -> Config {
+    Config {
+        length: Length::Varint,
+        magic: b"DB",
+        checksum: true,
+        endian: Endianness::Big,
+        ..Config::default()
+    }
+}
+
+fn requests() -> Vec<Request> {
+    vec![
+        Request::Ping(1),
+        Request::Put {
+            key: "k".repeat(300),
+            value: vec![7; 1000],
+        },
+        Request::Ping(2),
+    ]
+}
+
+#[test]
+fn test_decoder_waits_for_whole_frames() {
+    let mut codec = DrybCodec::<Request>::new(config());
+    let mut encoded = BytesMut::new();
+    for request in requests() {
+        codec.encode(request, &mut encoded).unwrap();
+    }
+
+    let mut src = BytesMut::new();
+    let mut decoded = Vec::new();
+    for byte in encoded {
+        src.extend_from_slice(&[byte]);
+        if let Some(request) = codec.decode(&mut src).unwrap() {
+            decoded.push(request);
+        }
+    }
+    assert_eq!(decoded, requests());
+    assert!(src.is_empty());
+    assert!(codec.decode(&mut src).unwrap().is_none());
+}
+
+#[test]
+fn test_codec_errors() {
+    let mut codec = DrybCodec::<Request>::new(Config {
+        max_length: 16,
+        ..config()
+    });
+    let mut dst = BytesMut::new();
+    assert!(matches!(
+        codec.encode(requests().remove(1), &mut dst),
+        Err(FrameError::Oversized { max: 16, .. })
+    ));
+
+    let mut src = BytesMut::from(&b"DB\xff\x01"[..]);
+    assert!(matches!(
+        codec.decode(&mut src),
+        Err(FrameError::Oversized { length: 255, .. })
+    ));
+
+    let mut frames = FrameCodec::new(config());
+    let mut src = BytesMut::new();
+    let frame = Frame {
+        type_id: 0,
+        payload: vec![9],
+    };
+    frames.encode(frame, &mut src).unwrap();
+    assert!(matches!(
+        codec.decode(&mut src),
+        Err(FrameError::Deserialize(DeserializeError::Invalid))
+    ));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_framed_over_duplex() {
+    let (client, server) = tokio::io::duplex(64);
+    let mut client = Framed::new(client, DrybCodec::<Request>::new(config()));
+    let mut server = Framed::new(server, DrybCodec::<Request>::new(config()));
+
+    let sender = tokio::spawn(async move {
+        for request in requests() {
+            client.send(request).await.unwrap();
+        }
+        client.next().await.unwrap().unwrap()
+    });
+
+    let mut received = Vec::new();
+    for _ in 0..3 {
+        received.push(server.next().await.unwrap().unwrap());
+    }
+    server.send(Request::Ping(99)).await.unwrap();
+
+    assert_eq!(received, requests());
+    assert_eq!(sender.await.unwrap(), Request::Ping(99));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_stream_ending_inside_a_frame() {
+    let (mut writer, reader) = tokio::io::duplex(64);
+    let mut frames = FramedRead::new(reader, FrameCodec::new(config()));
+
+    writer.write_all(b"DB\x03\x01").await.unwrap();
+    drop(writer);
+    assert!(matches!(frames.next().await, Some(Err(FrameError::Io(_)))));
+}
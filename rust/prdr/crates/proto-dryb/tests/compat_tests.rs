@@ -0,0 +1,235 @@
+use proto_dryb::compat::{self, Change, Direction, Incompatibility};
+use proto_dryb::schema::{self, Primitive, Type};
+use proto_dryb::{Deserialize, DeserializeError, Endianness, Schema, Serialize, SerializeError};
+
+mod v1 {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Log {
+        pub timestamp: i64,
+        pub status: u32,
+        pub message: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub enum Event {
+        Start,
+        Progress(u8),
+        Done,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[dryb(version = 1)]
+    pub struct Versioned {
+        pub id: u32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[dryb(tagged)]
+    pub struct Tagged {
+        #[dryb(id = 1)]
+        pub id: u32,
+        #[dryb(id = 2)]
+        pub name: String,
+    }
+}
+
+mod v2 {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Log {
+        pub status: u32,
+        pub timestamp: i64,
+        pub message: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct WiderLog {
+        pub timestamp: i64,
+        pub status: u64,
+        pub message: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub enum Event {
+        Start,
+        Done,
+        Progress(u8),
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub enum FewerEvents {
+        Start,
+        Progress(u8),
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[dryb(version = 2)]
+    pub struct Versioned {
+        pub id: u32,
+        #[dryb(since = 2)]
+        pub name: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[dryb(tagged)]
+    pub struct Tagged {
+        #[dryb(id = 2)]
+        pub name: String,
+        #[dryb(id = 3)]
+        pub flags: u8,
+    }
+}
+
+#[test]
+fn test_identical_schemas_are_compatible() {
+    assert!(compat::check(&v1::Log::schema(), &v1::Log::schema()).is_empty());
+    assert!(compat::check(&v1::Event::schema(), &v1::Event::schema()).is_empty());
+}
+
+#[test]
+fn test_reordered_and_widened_fields() {
+    let found = compat::check(&v1::Log::schema(), &v2::Log::schema());
+    assert_eq!(
+        found,
+        vec![
+            Incompatibility {
+                path: "Log".to_string(),
+                change: Change::FieldReordered {
+                    name: "timestamp".to_string(),
+                    old: 0,
+                    new: 1,
+                },
+                direction: Direction::Both,
+            },
+            Incompatibility {
+                path: "Log".to_string(),
+                change: Change::FieldReordered {
+                    name: "status".to_string(),
+                    old: 1,
+                    new: 0,
+                },
+                direction: Direction::Both,
+            },
+        ]
+    );
+
+    let found = compat::check(&v1::Log::schema(), &v2::WiderLog::schema());
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].path, "Log.status");
+    assert_eq!(
+        found[0].change,
+        Change::PrimitiveChanged {
+            old: Primitive::U32,
+            new: Primitive::U64,
+        }
+    );
+    assert_eq!(
+        found[0].to_string(),
+        "Log.status: u32 (4 bytes) changed to u64 (8 bytes) (breaks old and new readers)"
+    );
+}
+
+#[test]
+fn test_enum_tag_changes_and_removed_variants() {
+    let found = compat::check(&v1::Event::schema(), &v2::Event::schema());
+    let changes = found.iter().map(|i| &i.change).collect::<Vec<_>>();
+    assert_eq!(
+        changes,
+        vec![
+            &Change::VariantTagChanged {
+                name: "Progress".to_string(),
+                old: 1,
+                new: 2,
+            },
+            &Change::VariantTagChanged {
+                name: "Done".to_string(),
+                old: 2,
+                new: 1,
+            },
+        ]
+    );
+
+    let found = compat::check(&v1::Event::schema(), &v2::FewerEvents::schema());
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].change, Change::VariantRemoved("Done".to_string()));
+    assert_eq!(found[0].direction, Direction::Backward);
+
+    let found = compat::check(&v2::FewerEvents::schema(), &v1::Event::schema());
+    assert_eq!(found[0].change, Change::VariantAdded("Done".to_string()));
+    assert_eq!(found[0].direction, Direction::Forward);
+}
+
+#[test]
+fn test_evolvable_encodings() {
+    assert!(compat::check(&v1::Versioned::schema(), &v2::Versioned::schema()).is_empty());
+    let found = compat::check(&v2::Versioned::schema(), &v1::Versioned::schema());
+    let changes = found.iter().map(|i| &i.change).collect::<Vec<_>>();
+    assert_eq!(
+        changes,
+        vec![
+            &Change::VersionDecreased { old: 2, new: 1 },
+            &Change::FieldRemoved("name".to_string()),
+        ]
+    );
+
+    assert!(compat::check(&v1::Tagged::schema(), &v2::Tagged::schema()).is_empty());
+    let found = compat::check(&v1::Log::schema(), &v1::Versioned::schema());
+    assert_eq!(found.len(), 1);
+    assert!(matches!(found[0].change, Change::EncodingChanged { .. }));
+}
+
+#[test]
+fn test_schema_file_roundtrip() {
+    let exported = schema::export(&v2::Versioned::schema());
+    assert_eq!(&exported[..4], b"DRYS");
+    assert_eq!(schema::import(&exported).unwrap(), v2::Versioned::schema());
+
+    assert!(schema::import(&exported[..exported.len() - 1]).is_err());
+    assert!(schema::import(b"not a schema").is_err());
+}
+
+#[test]
+fn test_assert_compatible_against_baseline() {
+    let baseline = std::env::temp_dir().join(format!("dryb-compat-{}.drys", std::process::id()));
+    let _ = std::fs::remove_file(&baseline);
+
+    let result = std::panic::catch_unwind(|| compat::assert_compatible::<v1::Log>(&baseline));
+    assert!(result.is_err());
+    assert!(!baseline.exists());
+
+    std::fs::write(&baseline, schema::export(&v1::Log::schema())).unwrap();
+    compat::assert_compatible::<v1::Log>(&baseline);
+
+    let result = std::panic::catch_unwind(|| compat::assert_compatible::<v2::WiderLog>(&baseline));
+    std::fs::remove_file(&baseline).unwrap();
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_nested_type_changes() {
+    let old = Type::Vec {
+        item: Box::new(Option::<[u8; 4]>::schema()),
+    };
+    let new = Type::Vec {
+        item: Box::new(Option::<[u8; 8]>::schema()),
+    };
+    let found = compat::check(&old, &new);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].path, "[]?");
+    assert_eq!(
+        found[0].change,
+        Change::ArrayLengthChanged { old: 4, new: 8 }
+    );
+
+    let found = compat::check(&String::schema(), &Vec::<u8>::schema());
+    assert_eq!(
+        found[0].change,
+        Change::TypeChanged {
+            old: "string".to_string(),
+            new: "vec".to_string(),
+        }
+    );
+}
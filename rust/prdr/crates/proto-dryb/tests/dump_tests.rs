@@ -0,0 +1,105 @@
+use proto_dryb::dump::{dump, dump_value, Style};
+use proto_dryb::{Deserialize, DeserializeError, Endianness, Schema, Serialize, SerializeError};
+
+#[derive(Serialize, Deserialize)]
+#[dryb(endian = "big")]
+struct Header {
+    length: u16,
+    #[dryb(endian = "little")]
+    flags: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Status {
+    Ok,
+    Moved(String),
+}
+
+#[derive(Serialize, Deserialize)]
+struct Packet {
+    header: Header,
+    statuses: Vec<Status>,
+    checksum: Option<u8>,
+}
+
+fn packet() -> Packet {
+    Packet {
+        header: Header {
+            length: 300,
+            flags: 1,
+        },
+        statuses: vec![Status::Ok, Status::Moved("/a".to_string())],
+        checksum: Some(7),
+    }
+}
+
+#[test]
+fn test_dump_annotates_fields() {
+    let expected = "\
+000000     20  Packet
+000000      6    header: Header
+000000      2      header.length: 300          01 2c
+000002      4      header.flags: 1             01 00 00 00
+000006     12    statuses: 2 items
+000006      4      [length: 2]                 02 00 00 00
+00000a      1      statuses[0]: Status::Ok
+00000a      1        [tag: 0]                  00
+00000b      7      statuses[1]: Status::Moved
+00000b      1        [tag: 1]                  01
+00000c      6        statuses[1].0: \"/a\"       02 00 00 00 2f 61
+000012      1    [checksum: Some]              01
+000013      1    checksum: 7                   07";
+
+    assert_eq!(
+        dump_value(&packet(), Endianness::Little, Style::Plain),
+        expected
+    );
+}
+
+#[test]
+fn test_dump_points_at_the_failure() {
+    let mut buffer = [0u8; 64];
+    let size = packet().serialize(&mut buffer, Endianness::Little).unwrap();
+    buffer[11] = 9;
+
+    let output = dump(
+        &Packet::schema(),
+        &buffer[..size],
+        Endianness::Little,
+        Style::Plain,
+    );
+    assert!(output.contains("00000b      ?      statuses[1]: Status\n"));
+    let last = output.lines().last().unwrap();
+    assert!(
+        last.starts_with("00000b      ?        error: statuses[1]: unknown variant tag 9"),
+        "{}",
+        output
+    );
+
+    let output = dump(
+        &Packet::schema(),
+        &buffer[..4],
+        Endianness::Little,
+        Style::Plain,
+    );
+    assert!(output.contains("error: header.flags: expected 4 bytes for a u32"));
+}
+
+#[test]
+fn test_dump_trailing_bytes_and_colour() {
+    let output = dump(
+        &u16::schema(),
+        &[1, 0, 0xff],
+        Endianness::Little,
+        Style::Plain,
+    );
+    assert_eq!(
+        output,
+        "\
+000000      2  value: 1          01 00
+000002      1  [trailing bytes]  ff"
+    );
+
+    let output = dump(&u16::schema(), &[1, 0], Endianness::Big, Style::Ansi);
+    assert!(output.contains("\x1b[32mvalue: 256\x1b[0m"));
+}
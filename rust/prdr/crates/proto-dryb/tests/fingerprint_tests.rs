@@ -0,0 +1,125 @@
+use proto_dryb::{
+    fingerprint, Deserialize, DeserializeError, Endianness, Fingerprinted, Schema, Serialize,
+    SerializeError,
+};
+
+mod v1 {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Log {
+        pub timestamp: i64,
+        pub status: u32,
+    }
+}
+
+mod v2 {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Log {
+        pub timestamp: i64,
+        pub status: u64,
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(endian = "big", length_prefixed)]
+enum Event {
+    Start,
+    Progress(u8, #[dryb(endian = "little")] u16),
+    Done {
+        logs: Vec<v1::Log>,
+    },
+    #[dryb(other)]
+    Unknown(u8),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(version = 2)]
+struct Versioned {
+    event: Option<Event>,
+    #[dryb(since = 2)]
+    name: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(tagged)]
+struct Tagged {
+    #[dryb(id = 3)]
+    samples: [i16; 4],
+}
+
+const LOG_FINGERPRINT: u64 = v1::Log::FINGERPRINT;
+
+#[test]
+fn test_fingerprint_matches_schema_tree() {
+    assert_eq!(u8::FINGERPRINT, fingerprint::of(&u8::schema()));
+    assert_eq!(
+        Vec::<Option<String>>::FINGERPRINT,
+        fingerprint::of(&Vec::<Option<String>>::schema())
+    );
+    assert_eq!(
+        <[f32; 3]>::FINGERPRINT,
+        fingerprint::of(&<[f32; 3]>::schema())
+    );
+    assert_eq!(
+        std::collections::HashMap::<u16, String>::FINGERPRINT,
+        fingerprint::of(&std::collections::HashMap::<u16, String>::schema())
+    );
+    assert_eq!(LOG_FINGERPRINT, fingerprint::of(&v1::Log::schema()));
+    assert_eq!(Event::FINGERPRINT, fingerprint::of(&Event::schema()));
+    assert_eq!(
+        Versioned::FINGERPRINT,
+        fingerprint::of(&Versioned::schema())
+    );
+    assert_eq!(Tagged::FINGERPRINT, fingerprint::of(&Tagged::schema()));
+}
+
+#[test]
+fn test_fingerprint_distinguishes_types() {
+    assert_ne!(v1::Log::FINGERPRINT, v2::Log::FINGERPRINT);
+    assert_ne!(u32::FINGERPRINT, i32::FINGERPRINT);
+    assert_ne!(<[u8; 3]>::FINGERPRINT, <[u8; 4]>::FINGERPRINT);
+    assert_ne!(Vec::<u8>::FINGERPRINT, String::FINGERPRINT);
+    assert_ne!(Option::<u8>::FINGERPRINT, u8::FINGERPRINT);
+}
+
+#[test]
+fn test_fingerprint_is_stable() {
+    assert_eq!(u8::FINGERPRINT, 0x08c48207b56753d8);
+}
+
+#[test]
+fn test_fingerprinted_roundtrip() {
+    let log = Fingerprinted(v1::Log {
+        timestamp: 1,
+        status: 200,
+    });
+    let mut buffer = [0u8; 64];
+    let size = log.serialize(&mut buffer, Endianness::Little).unwrap();
+    assert_eq!(&buffer[..8], &v1::Log::FINGERPRINT.to_le_bytes());
+
+    let (decoded, read) =
+        Fingerprinted::<v1::Log>::deserialize(&buffer[..size], Endianness::Little).unwrap();
+    assert_eq!(decoded, log);
+    assert_eq!(read, size);
+}
+
+#[test]
+fn test_fingerprinted_rejects_mismatched_schema() {
+    let log = Fingerprinted(v1::Log {
+        timestamp: 1,
+        status: 200,
+    });
+    let mut buffer = [0u8; 64];
+    let size = log.serialize(&mut buffer, Endianness::Big).unwrap();
+
+    match Fingerprinted::<v2::Log>::deserialize(&buffer[..size], Endianness::Big) {
+        Err(DeserializeError::SchemaMismatch { expected, found }) => {
+            assert_eq!(expected, v2::Log::FINGERPRINT);
+            assert_eq!(found, v1::Log::FINGERPRINT);
+        }
+        other => panic!("expected a schema mismatch, got {:?}", other),
+    }
+}
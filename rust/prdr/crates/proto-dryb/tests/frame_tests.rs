@@ -0,0 +1,224 @@
+use std::io::{self, Read};
+
+use proto_dryb::frame::{Config, Frame, FrameError, FrameReader, FrameWriter, Length};
+use proto_dryb::{Deserialize, DeserializeError, Endianness, Serialize, SerializeError};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Log {
+    timestamp: i64,
+    level: u8,
+    message: String,
+}
+
+fn logs() -> Vec<Log> {
+    (0..20)
+        .map(|i| Log {
+            timestamp: 1_700_000_000 + i,
+            level: i as u8 % 4,
+            message: "x".repeat(i as usize * 50),
+        })
+        .collect()
+}
+
+/// Hands out at most `step` bytes per read, failing with `WouldBlock` every other call.
+struct Trickle {
+    bytes: Vec<u8>,
+    position: usize,
+    step: usize,
+    block: bool,
+}
+
+impl Read for Trickle {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.block = !self.block;
+        if self.block {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        let n = self
+            .step
+            .min(buf.len())
+            .min(self.bytes.len() - self.position);
+        buf[..n].copy_from_slice(&self.bytes[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_frame_layout() {
+    let config = Config {
+        length: Length::Varint,
+        magic: b"DR",
+        type_id: true,
+        checksum: true,
+        ..Config::default()
+    };
+    let frame = Frame {
+        type_id: 7,
+        payload: vec![1, 2, 3],
+    };
+    let mut bytes = Vec::new();
+    config.encode(&frame, &mut bytes).unwrap();
+    assert_eq!(
+        bytes,
+        [b'D', b'R', 0x07, 0x00, 0x03, 1, 2, 3, 0x7a, 0x58, 0x15, 0x22]
+    );
+    assert_eq!(config.decode(&bytes).unwrap(), Some((frame, bytes.len())));
+    assert_eq!(config.decode(&bytes[..11]).unwrap(), None);
+}
+
+#[test]
+fn test_frames_survive_partial_reads() {
+    for length in [Length::U16, Length::U32, Length::Varint] {
+        let config = Config {
+            length,
+            magic: b"\xd7\x0b",
+            type_id: true,
+            checksum: true,
+            endian: Endianness::Big,
+            ..Config::default()
+        };
+        let mut writer = FrameWriter::new(Vec::new(), config.clone());
+        for (i, log) in logs().iter().enumerate() {
+            writer.write(i as u16, log).unwrap();
+        }
+
+        let mut reader = FrameReader::new(
+            Trickle {
+                bytes: writer.into_inner(),
+                position: 0,
+                step: 7,
+                block: false,
+            },
+            config.clone(),
+        );
+        let mut read = Vec::new();
+        loop {
+            match reader.read_frame() {
+                Ok(Some(frame)) => {
+                    assert_eq!(frame.type_id as usize, read.len());
+                    read.push(frame.decode::<Log>(config.endian).unwrap());
+                }
+                Ok(None) => break,
+                Err(FrameError::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => panic!("{}", e),
+            }
+        }
+        assert_eq!(read, logs());
+    }
+}
+
+#[test]
+fn test_reader_iterates_and_decodes() {
+    let mut writer = FrameWriter::new(Vec::new(), Config::default());
+    for log in logs() {
+        writer.write(0, &log).unwrap();
+    }
+    let bytes = writer.into_inner();
+
+    let frames = FrameReader::new(&bytes[..], Config::default())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(frames.len(), 20);
+    assert!(frames.iter().all(|f| f.type_id == 0));
+
+    let mut reader = FrameReader::new(&bytes[..], Config::default());
+    assert_eq!(reader.read::<Log>().unwrap(), Some(logs().remove(0)));
+}
+
+#[test]
+fn test_reader_iteration_stops_after_error() {
+    let config = Config {
+        magic: b"DRYB",
+        ..Config::default()
+    };
+    let mut writer = FrameWriter::new(Vec::new(), config.clone());
+    writer.write(0, &logs()[0]).unwrap();
+    let mut bytes = writer.into_inner();
+    bytes.extend_from_slice(b"DRYX\x00\x00\x00\x00");
+    bytes.extend_from_slice(&bytes.clone()[..bytes.len() - 8]);
+
+    let results = FrameReader::new(&bytes[..], config.clone()).collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(FrameError::BadMagic)));
+
+    // Reading directly keeps reporting the frame that can't be decoded.
+    let mut reader = FrameReader::new(&bytes[..], config);
+    reader.read_frame().unwrap();
+    assert!(matches!(reader.read_frame(), Err(FrameError::BadMagic)));
+    assert!(matches!(reader.read_frame(), Err(FrameError::BadMagic)));
+}
+
+#[test]
+fn test_frame_errors() {
+    let config = Config {
+        max_length: 64,
+        checksum: true,
+        ..Config::default()
+    };
+    let log = &logs()[3];
+
+    let mut writer = FrameWriter::new(Vec::new(), config.clone());
+    assert!(matches!(
+        writer.write(0, log),
+        Err(FrameError::Oversized {
+            length: 163,
+            max: 64
+        })
+    ));
+    assert!(writer.get_ref().is_empty());
+
+    // The length is checked before the payload arrives.
+    let mut reader = FrameReader::new(&[0x00, 0x10, 0x00, 0x00][..], config.clone());
+    assert!(matches!(
+        reader.read_frame(),
+        Err(FrameError::Oversized {
+            length: 4096,
+            max: 64
+        })
+    ));
+
+    let mut bytes = Vec::new();
+    config
+        .encode(
+            &Frame::new(0, &logs()[0], config.endian).unwrap(),
+            &mut bytes,
+        )
+        .unwrap();
+    let mut corrupt = bytes.clone();
+    corrupt[6] ^= 1;
+    let mut reader = FrameReader::new(&corrupt[..], config.clone());
+    assert!(matches!(
+        reader.read_frame(),
+        Err(FrameError::ChecksumMismatch { .. })
+    ));
+
+    let mut reader = FrameReader::new(&bytes[..bytes.len() - 1], config.clone());
+    assert!(matches!(reader.read_frame(), Err(FrameError::Truncated)));
+
+    let magic = Config {
+        magic: b"DRYB",
+        ..Config::default()
+    };
+    let mut reader = FrameReader::new(&b"DRYX\x00\x00\x00\x00"[..], magic);
+    assert!(matches!(reader.read_frame(), Err(FrameError::BadMagic)));
+
+    let varint = Config {
+        length: Length::Varint,
+        ..Config::default()
+    };
+    assert!(matches!(
+        varint.decode(&[0xff; 11]),
+        Err(FrameError::InvalidLength)
+    ));
+
+    let frame = Frame {
+        type_id: 0,
+        payload: vec![1, 2, 3],
+    };
+    assert!(matches!(
+        frame.decode::<u16>(Endianness::Little),
+        Err(DeserializeError::Invalid)
+    ));
+}
@@ -0,0 +1,162 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+
+use proto_dryb::{
+    Checksummed, Deserialize, DeserializeError, Endianness, Fingerprinted, Serialize,
+    SerializeError,
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Origin {
+    ip: String,
+    #[dryb(endian = "big")]
+    port: u16,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[dryb(version = 2)]
+struct Http {
+    status: u32,
+    #[dryb(since = 2)]
+    user_agent: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum CacheStatus {
+    Hit,
+    Miss(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Log {
+    origin: Origin,
+    http: Http,
+    cache_status: CacheStatus,
+    ray_id: String,
+    backup: Option<Origin>,
+    tags: Vec<String>,
+    counters: HashMap<String, u64>,
+    sorted: BTreeMap<u8, String>,
+    samples: [Box<u32>; 2],
+}
+
+fn log(i: u32) -> Log {
+    Log {
+        origin: Origin {
+            ip: format!("10.0.0.{}", i),
+            port: 8000 + i as u16,
+        },
+        http: Http {
+            status: 200 + i,
+            user_agent: "agent".repeat(i as usize + 1),
+        },
+        cache_status: if i.is_multiple_of(2) {
+            CacheStatus::Hit
+        } else {
+            CacheStatus::Miss(format!("miss-{}", i))
+        },
+        ray_id: format!("ray-{}", i),
+        backup: (i > 0).then(|| Origin {
+            ip: "backup".to_string(),
+            port: i as u16,
+        }),
+        tags: (0..i).map(|t| t.to_string()).collect(),
+        counters: HashMap::from([(format!("c{}", i), i as u64)]),
+        sorted: BTreeMap::from([(i as u8, "s".to_string())]),
+        samples: [Box::new(i), Box::new(i * 2)],
+    }
+}
+
+fn to_bytes<T: Serialize>(value: &T, endian: Endianness) -> Vec<u8> {
+    let mut buffer = vec![0u8; 4096];
+    let size = value.serialize(&mut buffer, endian).unwrap();
+    buffer.truncate(size);
+    buffer
+}
+
+fn assert_in_place<T: Serialize + Deserialize + PartialEq + Debug>(target: &mut T, value: &T) {
+    for endian in [Endianness::Little, Endianness::Big] {
+        let bytes = to_bytes(value, endian);
+        assert_eq!(
+            target.deserialize_in_place(&bytes, endian).unwrap(),
+            bytes.len()
+        );
+        assert_eq!(target, value);
+    }
+}
+
+#[test]
+fn test_in_place_matches_deserialize() {
+    let mut target = log(0);
+    for i in [3, 1, 4, 0, 5] {
+        assert_in_place(&mut target, &log(i));
+    }
+
+    assert_in_place(&mut Some(1u8), &None);
+    assert_in_place(&mut None, &Some("x".to_string()));
+    assert_in_place(&mut vec![1u16, 2, 3], &vec![4]);
+    assert_in_place(&mut Fingerprinted(log(1)), &Fingerprinted(log(2)));
+    assert_in_place(
+        &mut Checksummed::<_>::new(log(1)),
+        &Checksummed::new(log(2)),
+    );
+}
+
+#[test]
+fn test_in_place_reuses_allocations() {
+    let mut target = log(5);
+    let ray_id = target.ray_id.as_ptr();
+    let agent = target.http.user_agent.as_ptr();
+    let tags = target.tags.as_ptr();
+    let first_tag = target.tags[0].as_ptr();
+    let sample = &*target.samples[1] as *const u32;
+
+    let bytes = to_bytes(&log(3), Endianness::Little);
+    target
+        .deserialize_in_place(&bytes, Endianness::Little)
+        .unwrap();
+    assert_eq!(target, log(3));
+    assert_eq!(target.ray_id.as_ptr(), ray_id);
+    assert_eq!(target.http.user_agent.as_ptr(), agent);
+    assert_eq!(target.tags.as_ptr(), tags);
+    assert_eq!(target.tags[0].as_ptr(), first_tag);
+    assert_eq!(&*target.samples[1] as *const u32, sample);
+}
+
+#[test]
+fn test_in_place_versions_and_errors() {
+    // Fields newer than the encoded version are reset, as `deserialize` would leave them.
+    let mut bytes = to_bytes(
+        &Http {
+            status: 1,
+            user_agent: String::new(),
+        },
+        Endianness::Little,
+    );
+    bytes[0] = 1;
+    let mut target = Http {
+        status: 7,
+        user_agent: "old".to_string(),
+    };
+    target
+        .deserialize_in_place(&bytes, Endianness::Little)
+        .unwrap();
+    assert_eq!(
+        target,
+        Http::deserialize(&bytes, Endianness::Little).unwrap().0
+    );
+    assert_eq!(target.user_agent, "");
+
+    let bytes = to_bytes(&log(2), Endianness::Little);
+    let mut target = log(1);
+    assert!(matches!(
+        target.deserialize_in_place(&bytes[..bytes.len() - 1], Endianness::Little),
+        Err(DeserializeError::Invalid)
+    ));
+
+    let mut text = "keep".to_string();
+    assert!(text
+        .deserialize_in_place(&[1, 0, 0, 0, 0xff], Endianness::Little)
+        .is_err());
+    assert_eq!(text, "keep");
+}
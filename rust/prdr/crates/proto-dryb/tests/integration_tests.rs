@@ -1,4 +1,9 @@
-use proto_dryb::{Deserialize, Endianness, Serialize};
+#![allow(clippy::approx_constant)]
+
+use std::collections::{BTreeMap, HashMap};
+
+use proto_dryb::dump::{dump_value, Style};
+use proto_dryb::{Deserialize, Endianness, Schema, Serialize};
 
 #[test]
 fn test_primitives() {
@@ -59,6 +64,47 @@ fn test_string() {
     }
 }
 
+#[test]
+fn test_borrowed_types_encode_like_owned() {
+    fn to_bytes<T: Serialize + ?Sized>(value: &T, endian: Endianness) -> Vec<u8> {
+        let mut buffer = [0u8; 64];
+        let size = value.serialize(&mut buffer, endian).unwrap();
+        buffer[..size].to_vec()
+    }
+
+    for endian in [Endianness::Little, Endianness::Big] {
+        let owned = to_bytes(&"🦀 Rust".to_string(), endian);
+        assert_eq!(to_bytes("🦀 Rust", endian), owned);
+        assert_eq!(to_bytes(&"🦀 Rust", endian), owned);
+        assert_eq!(to_bytes(&Box::<str>::from("🦀 Rust"), endian), owned);
+
+        let owned = to_bytes(&vec![1u16, 2, 3], endian);
+        assert_eq!(to_bytes(&[1u16, 2, 3][..], endian), owned);
+        assert_eq!(to_bytes(&&[1u16, 2, 3][..], endian), owned);
+        assert_eq!(to_bytes(&vec![&1u16, &2, &3], endian), owned);
+
+        assert_eq!(
+            to_bytes(b"ab".as_slice(), endian),
+            to_bytes(&b"ab".to_vec(), endian)
+        );
+    }
+    assert_eq!(to_bytes("ab", Endianness::Big), [0, 0, 0, 2, b'a', b'b']);
+    assert!("abc".serialize(&mut [0u8; 6], Endianness::Little).is_err());
+}
+
+#[test]
+fn test_map() {
+    let endianness = [Endianness::Little, Endianness::Big];
+    for &endian in &endianness {
+        let map = HashMap::from([(1u16, "one".to_string()), (2u16, "two".to_string())]);
+        test_roundtrip(map, endian);
+        test_roundtrip(
+            BTreeMap::from([(-1i64, vec![true]), (7i64, vec![])]),
+            endian,
+        );
+    }
+}
+
 #[test]
 fn test_array() {
     let arr = [1, 2, 3, 4, 5];
@@ -80,7 +126,7 @@ fn test_empty_array() {
     test_roundtrip(empty_arr, Endianness::Big);
 }
 
-fn test_roundtrip<T: Serialize + Deserialize + PartialEq + std::fmt::Debug>(
+fn test_roundtrip<T: Schema + Serialize + Deserialize + PartialEq + std::fmt::Debug>(
     value: T,
     endian: Endianness,
 ) {
@@ -88,17 +134,25 @@ fn test_roundtrip<T: Serialize + Deserialize + PartialEq + std::fmt::Debug>(
     let serialized_size = value
         .serialize(&mut buffer, endian)
         .expect("Serialization failed");
+    let dump = || dump_value(&value, endian, Style::Plain);
     let (deserialized_value, deserialized_size) =
-        T::deserialize(&buffer[..serialized_size], endian).expect("Deserialization failed");
+        T::deserialize(&buffer[..serialized_size], endian)
+            .unwrap_or_else(|e| panic!("Deserialization failed: {}\n{}", e, dump()));
 
     assert_eq!(
-        value, deserialized_value,
-        "Roundtrip failed for {:?} with {:?} endianness",
-        value, endian
+        value,
+        deserialized_value,
+        "Roundtrip failed for {:?} with {:?} endianness\n{}",
+        value,
+        endian,
+        dump()
     );
     assert_eq!(
-        serialized_size, deserialized_size,
-        "Serialized and deserialized sizes don't match for {:?} with {:?} endianness",
-        value, endian
+        serialized_size,
+        deserialized_size,
+        "Serialized and deserialized sizes don't match for {:?} with {:?} endianness\n{}",
+        value,
+        endian,
+        dump()
     );
 }
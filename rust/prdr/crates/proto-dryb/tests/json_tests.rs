@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+
+use proto_dryb::json::{self, Bytes, Error, Int64, Options};
+use proto_dryb::{Deserialize, DeserializeError, Endianness, Serialize, SerializeError};
+use serde_json::json;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Event {
+    Idle,
+    Moved(i16, i16),
+    Renamed(String),
+    Crashed { code: u32, dump: Vec<u8> },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Record {
+    id: u64,
+    offset: i64,
+    key: [u8; 4],
+    note: Option<String>,
+    events: Vec<Event>,
+    counts: BTreeMap<u16, bool>,
+}
+
+fn record() -> Record {
+    Record {
+        id: u64::MAX,
+        offset: -3,
+        key: [0xde, 0xad, 0xbe, 0xef],
+        note: None,
+        events: vec![
+            Event::Idle,
+            Event::Moved(1, -1),
+            Event::Renamed("x".to_string()),
+            Event::Crashed {
+                code: 7,
+                dump: b"hello".to_vec(),
+            },
+        ],
+        counts: BTreeMap::from([(2, true)]),
+    }
+}
+
+fn serialize(record: &Record, endian: Endianness) -> Vec<u8> {
+    let mut buffer = [0u8; 256];
+    let size = record.serialize(&mut buffer, endian).unwrap();
+    buffer[..size].to_vec()
+}
+
+#[test]
+fn test_json_round_trip() {
+    let bytes = serialize(&record(), Endianness::Big);
+    let (value, size) =
+        json::to_json::<Record>(&bytes, Endianness::Big, &Options::default()).unwrap();
+    assert_eq!(size, bytes.len());
+    assert_eq!(
+        value,
+        json!({
+            "id": u64::MAX,
+            "offset": -3,
+            "key": [0xde, 0xad, 0xbe, 0xef],
+            "note": null,
+            "events": [
+                "Idle",
+                {"Moved": [1, -1]},
+                {"Renamed": "x"},
+                {"Crashed": {"code": 7, "dump": [104, 101, 108, 108, 111]}},
+            ],
+            "counts": [[2, true]],
+        })
+    );
+
+    let encoded = json::from_json::<Record>(&value, Endianness::Big, &Options::default()).unwrap();
+    assert_eq!(encoded, bytes);
+}
+
+#[test]
+fn test_json_options() {
+    let bytes = serialize(&record(), Endianness::Little);
+    let hex = Options {
+        bytes: Bytes::Hex,
+        int64: Int64::String,
+    };
+    let (value, _) = json::to_json::<Record>(&bytes, Endianness::Little, &hex).unwrap();
+    assert_eq!(value["id"], json!("18446744073709551615"));
+    assert_eq!(value["offset"], json!("-3"));
+    assert_eq!(value["key"], json!("deadbeef"));
+    assert_eq!(value["events"][3]["Crashed"]["dump"], json!("68656c6c6f"));
+    assert_eq!(
+        json::from_json::<Record>(&value, Endianness::Little, &hex).unwrap(),
+        bytes
+    );
+
+    let base64 = Options {
+        bytes: Bytes::Base64,
+        ..Options::default()
+    };
+    let (value, _) = json::to_json::<Record>(&bytes, Endianness::Little, &base64).unwrap();
+    assert_eq!(value["id"], json!(u64::MAX));
+    assert_eq!(value["key"], json!("3q2+7w=="));
+    assert_eq!(value["events"][3]["Crashed"]["dump"], json!("aGVsbG8="));
+    assert_eq!(
+        json::from_json::<Record>(&value, Endianness::Little, &base64).unwrap(),
+        bytes
+    );
+
+    // Arrays and numbers are read whatever the options say.
+    let (plain, _) =
+        json::to_json::<Record>(&bytes, Endianness::Little, &Options::default()).unwrap();
+    assert_eq!(
+        json::from_json::<Record>(&plain, Endianness::Little, &hex).unwrap(),
+        bytes
+    );
+}
+
+#[test]
+fn test_json_keeps_unknown_variants() {
+    #[derive(Serialize, Deserialize)]
+    #[dryb(length_prefixed)]
+    enum Known {
+        Idle,
+        #[dryb(other)]
+        Unknown(u8),
+    }
+
+    #[derive(Serialize, Deserialize)]
+    enum Plan {
+        Free,
+        #[dryb(other)]
+        Other,
+    }
+
+    let hex = Options {
+        bytes: Bytes::Hex,
+        ..Options::default()
+    };
+    let mut buffer = [0u8; 64];
+    let size = Event::Renamed("x".to_string())
+        .serialize(&mut buffer[..], Endianness::Little)
+        .unwrap();
+    let mut bytes = vec![2, 0, 0, 0, 0];
+    bytes[1] = size as u8;
+    bytes.extend_from_slice(&buffer[..size]);
+
+    let (value, _) = json::to_json::<Known>(&bytes, Endianness::Little, &hex).unwrap();
+    assert_eq!(
+        value,
+        json!({"Unknown": {"tag": 2, "body": "020100000078"}})
+    );
+    assert_eq!(
+        json::from_json::<Known>(&value, Endianness::Little, &hex).unwrap(),
+        bytes
+    );
+
+    let (value, _) = json::to_json::<Plan>(&[9], Endianness::Little, &hex).unwrap();
+    assert_eq!(value, json!({"Other": {"tag": 9}}));
+    assert_eq!(
+        json::from_json::<Plan>(&value, Endianness::Little, &hex).unwrap(),
+        [9]
+    );
+    assert_eq!(
+        json::to_json::<Plan>(&[1], Endianness::Little, &hex)
+            .unwrap()
+            .0,
+        json!("Other")
+    );
+}
+
+#[test]
+fn test_json_non_finite_floats() {
+    let options = Options::default();
+    let values = vec![f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -0.5];
+    let mut buffer = [0u8; 64];
+    let size = values.serialize(&mut buffer, Endianness::Little).unwrap();
+
+    let (value, _) =
+        json::to_json::<Vec<f64>>(&buffer[..size], Endianness::Little, &options).unwrap();
+    assert_eq!(value, json!(["NaN", "inf", "-inf", -0.5]));
+    assert_eq!(
+        json::from_json::<Vec<f64>>(&value, Endianness::Little, &options).unwrap(),
+        &buffer[..size]
+    );
+
+    let encoded = json::from_json::<f32>(&json!("-inf"), Endianness::Little, &options).unwrap();
+    assert_eq!(
+        f32::deserialize(&encoded, Endianness::Little).unwrap().0,
+        f32::NEG_INFINITY
+    );
+    assert!(json::from_json::<f64>(&json!("nan"), Endianness::Little, &options).is_err());
+}
+
+#[test]
+fn test_json_errors() {
+    let options = Options::default();
+    let mut value = json!({
+        "id": 1, "offset": 0, "key": [0, 0, 0, 0], "note": null,
+        "events": [{"Gone": 1}], "counts": [],
+    });
+    let err = json::from_json::<Record>(&value, Endianness::Little, &options).unwrap_err();
+    assert_eq!(err.to_string(), "events[0]: Event has no variant `Gone`");
+
+    value["events"] = json!([]);
+    value["key"] = json!([0, 0, 0]);
+    let err = json::from_json::<Record>(&value, Endianness::Little, &options).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "key: expected an array of 4 items, found [0,0,0]"
+    );
+
+    value["key"] = json!("00000000");
+    let err = json::from_json::<Record>(&value, Endianness::Little, &options).unwrap_err();
+    assert!(matches!(err, Error::Mismatch { ref path, .. } if path == "key"));
+
+    let hex = Options {
+        bytes: Bytes::Hex,
+        ..options
+    };
+    json::from_json::<Record>(&value, Endianness::Little, &hex).unwrap();
+    value["key"] = json!("0000zz00");
+    assert!(json::from_json::<Record>(&value, Endianness::Little, &hex).is_err());
+    value["key"] = json!("00+f0000");
+    assert!(json::from_json::<Record>(&value, Endianness::Little, &hex).is_err());
+
+    assert!(matches!(
+        json::to_json::<Record>(&[1, 2], Endianness::Little, &options),
+        Err(Error::Deserialize(DeserializeError::Invalid))
+    ));
+}
@@ -0,0 +1,165 @@
+use std::fs;
+use std::path::PathBuf;
+
+use proto_dryb::frame::FrameError;
+use proto_dryb::mmap::MmapRecords;
+use proto_dryb::record_log::{LogError, Options, RecordLog};
+use proto_dryb::{
+    Deserialize, DeserializeBorrowed, DeserializeError, Endianness, Serialize, SerializeError,
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Log {
+    timestamp: i64,
+    ray_id: String,
+    body: Vec<u8>,
+}
+
+/// `Log` with its string and bytes borrowed from the buffer.
+#[derive(Debug, PartialEq)]
+struct LogRef<'a> {
+    timestamp: i64,
+    ray_id: &'a str,
+    body: &'a [u8],
+}
+
+impl<'de> DeserializeBorrowed<'de> for LogRef<'de> {
+    fn deserialize_borrowed(
+        buf: &'de [u8],
+        endian: Endianness,
+    ) -> Result<(Self, usize), DeserializeError> {
+        let (timestamp, mut offset) = i64::deserialize(buf, endian)?;
+        let (ray_id, size) = <&str>::deserialize_borrowed(&buf[offset..], endian)?;
+        offset += size;
+        let (body, size) = <&[u8]>::deserialize_borrowed(&buf[offset..], endian)?;
+        offset += size;
+        Ok((
+            LogRef {
+                timestamp,
+                ray_id,
+                body,
+            },
+            offset,
+        ))
+    }
+}
+
+fn log(i: usize) -> Log {
+    Log {
+        timestamp: i as i64,
+        ray_id: format!("ray-{}", i),
+        body: vec![i as u8; i % 5],
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("proto-dryb-mmap-{}-{}", name, std::process::id()))
+}
+
+#[test]
+fn test_borrowed_primitives() {
+    let buf = [3u8, 0, 0, 0, b'a', b'b', b'c', 0xff];
+    let (s, size) = <&str>::deserialize_borrowed(&buf, Endianness::Little).unwrap();
+    assert_eq!((s, size), ("abc", 7));
+    let (bytes, _) = <&[u8]>::deserialize_borrowed(&buf, Endianness::Little).unwrap();
+    assert_eq!(bytes, b"abc");
+    assert!(std::ptr::eq(bytes.as_ptr(), buf[4..].as_ptr()));
+
+    let (n, _) = u32::deserialize_borrowed(&buf, Endianness::Big).unwrap();
+    assert_eq!(n, 0x03000000);
+
+    assert!(<&str>::deserialize_borrowed(&buf[..6], Endianness::Little).is_err());
+    let invalid = [1u8, 0, 0, 0, 0xff];
+    assert!(matches!(
+        <&str>::deserialize_borrowed(&invalid, Endianness::Little),
+        Err(DeserializeError::Invalid)
+    ));
+}
+
+#[test]
+fn test_random_access_to_record_log() {
+    let path = temp_path("log");
+    let options = Options {
+        endian: Endianness::Big,
+        ..Options::default()
+    };
+    let mut records = RecordLog::<Log>::create(&path, options).unwrap();
+    for i in 0..100 {
+        records.append(&log(i)).unwrap();
+    }
+    drop(records);
+    // A torn final record is left out.
+    let mut bytes = fs::read(&path).unwrap();
+    bytes.extend_from_slice(&[0, 0, 0, 9, 1]);
+    fs::write(&path, &bytes).unwrap();
+
+    let map = unsafe { MmapRecords::open_log::<Log>(&path) }.unwrap();
+    assert_eq!((map.len(), map.endian()), (100, Endianness::Big));
+    assert_eq!(
+        map.get::<LogRef>(42).unwrap(),
+        Some(LogRef {
+            timestamp: 42,
+            ray_id: "ray-42",
+            body: &[42, 42],
+        })
+    );
+    assert_eq!(map.get::<Log>(99).unwrap(), Some(log(99)));
+    assert_eq!(map.get::<Log>(100).unwrap(), None);
+    let hits = map
+        .iter()
+        .filter(|record| record.decode::<LogRef>().unwrap().ray_id.ends_with('7'))
+        .count();
+    assert_eq!(hits, 10);
+
+    assert!(matches!(
+        unsafe { MmapRecords::open_log::<LogWrapper>(&path) },
+        Err(LogError::SchemaMismatch { .. })
+    ));
+    drop(map);
+
+    // Damage shows up when the record is decoded.
+    let len = bytes.len();
+    bytes[len - 10] ^= 1;
+    fs::write(&path, &bytes).unwrap();
+    let map = unsafe { MmapRecords::open_log::<Log>(&path) }.unwrap();
+    assert!(map.get::<LogRef>(98).is_ok());
+    assert!(matches!(
+        map.get::<LogRef>(99),
+        Err(LogError::Frame(FrameError::ChecksumMismatch { .. }))
+    ));
+    fs::remove_file(&path).unwrap();
+}
+
+#[derive(Serialize, Deserialize)]
+struct LogWrapper {
+    log: Log,
+}
+
+#[test]
+fn test_fixed_size_records() {
+    let path = temp_path("fixed");
+    let mut bytes = b"HDR".to_vec();
+    for i in 0..10u32 {
+        let mut record = [0u8; 8];
+        [i, i * i]
+            .serialize(&mut record, Endianness::Little)
+            .unwrap();
+        bytes.extend_from_slice(&record);
+    }
+    bytes.push(0);
+    fs::write(&path, &bytes).unwrap();
+
+    let map = unsafe { MmapRecords::open_fixed(&path, 3, 8, Endianness::Little) }.unwrap();
+    assert_eq!(map.len(), 10);
+    assert_eq!(map.get::<[u32; 2]>(7).unwrap(), Some([7, 49]));
+    assert_eq!(map.record(3).unwrap().bytes(), &[3, 0, 0, 0, 9, 0, 0, 0]);
+    assert!(map.record(10).is_none());
+    assert!(matches!(
+        map.get::<u32>(0),
+        Err(LogError::Frame(FrameError::Deserialize(
+            DeserializeError::Invalid
+        )))
+    ));
+    assert!(unsafe { MmapRecords::open_fixed(&path, 0, 0, Endianness::Little) }.is_err());
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,190 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use proto_dryb::frame::FrameError;
+use proto_dryb::record_log::{LogError, Options, RecordLog};
+use proto_dryb::{Deserialize, DeserializeError, Endianness, Serialize, SerializeError};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum CacheStatus {
+    Hit,
+    Miss,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Log {
+    timestamp: i64,
+    zone_id: u32,
+    cache_status: CacheStatus,
+    ray_id: String,
+    bytes_dlv: u64,
+}
+
+fn log(i: u64) -> Log {
+    Log {
+        timestamp: 1_700_000_000 + i as i64,
+        zone_id: i as u32 * 7,
+        cache_status: if i.is_multiple_of(3) {
+            CacheStatus::Miss
+        } else {
+            CacheStatus::Hit
+        },
+        ray_id: format!("ray-{}", i),
+        bytes_dlv: i * 1000,
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("proto-dryb-{}-{}.drlg", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn test_append_iterate_and_seek() {
+    let path = temp_path("seek");
+    for index_interval in [None, Some(4)] {
+        let options = Options {
+            endian: Endianness::Big,
+            index_interval,
+            ..Options::default()
+        };
+        let mut records = RecordLog::<Log>::create(&path, options.clone()).unwrap();
+        for i in 0..10 {
+            assert_eq!(records.append(&log(i)).unwrap(), i);
+        }
+        records.sync().unwrap();
+        assert_eq!(records.len(), 10);
+
+        let all = records
+            .iter()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(all, (0..10).map(log).collect::<Vec<_>>());
+        assert_eq!(records.get(9).unwrap(), Some(log(9)));
+        drop(records);
+
+        // Reopening keeps the file's byte order whatever the options ask for.
+        let mut records = RecordLog::<Log>::open(
+            &path,
+            Options {
+                endian: Endianness::Little,
+                ..options
+            },
+        )
+        .unwrap();
+        assert_eq!((records.len(), records.endian()), (10, Endianness::Big));
+        assert_eq!(records.truncated(), 0);
+        records.append(&log(10)).unwrap();
+        for i in [0, 3, 4, 5, 8, 10] {
+            assert_eq!(records.get(i).unwrap(), Some(log(i)));
+        }
+        assert_eq!(records.get(11).unwrap(), None);
+    }
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_torn_final_record_is_cut_off() {
+    let path = temp_path("torn");
+    let mut records = RecordLog::<Log>::create(&path, Options::default()).unwrap();
+    for i in 0..3 {
+        records.append(&log(i)).unwrap();
+    }
+    drop(records);
+    let whole = fs::metadata(&path).unwrap().len();
+
+    // Half of a fourth record.
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(&[40, 0, 0, 0, 1, 2, 3]).unwrap();
+    drop(file);
+
+    let mut records = RecordLog::<Log>::open(&path, Options::default()).unwrap();
+    assert_eq!((records.len(), records.truncated()), (3, 7));
+    assert_eq!(fs::metadata(&path).unwrap().len(), whole);
+    records.append(&log(3)).unwrap();
+    drop(records);
+
+    // A final record of the right length whose bytes never made it to the disk.
+    let mut bytes = fs::read(&path).unwrap();
+    let len = bytes.len();
+    bytes[len - 10..].fill(0);
+    fs::write(&path, &bytes).unwrap();
+    let records = RecordLog::<Log>::open(&path, Options::default()).unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(
+        records.iter().unwrap().map(Result::unwrap).last(),
+        Some(log(2))
+    );
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_record_log_errors() {
+    let path = temp_path("errors");
+    fs::write(&path, b"not a log at all").unwrap();
+    assert!(matches!(
+        RecordLog::<Log>::open(&path, Options::default()),
+        Err(LogError::BadHeader)
+    ));
+
+    let mut records = RecordLog::<Log>::create(&path, Options::default()).unwrap();
+    records.append(&log(0)).unwrap();
+    records.append(&log(1)).unwrap();
+    drop(records);
+    assert!(matches!(
+        RecordLog::<CacheStatus>::open(&path, Options::default()),
+        Err(LogError::SchemaMismatch { .. })
+    ));
+
+    let mut bytes = fs::read(&path).unwrap();
+    bytes[4] = 9;
+    fs::write(&path, &bytes).unwrap();
+    assert!(matches!(
+        RecordLog::<Log>::open(&path, Options::default()),
+        Err(LogError::UnsupportedVersion(9))
+    ));
+
+    // Damage to a record other than the last can't be repaired by truncating.
+    bytes[4] = 1;
+    bytes[20] ^= 0xff;
+    fs::write(&path, &bytes).unwrap();
+    assert!(matches!(
+        RecordLog::<Log>::open(&path, Options::default()),
+        Err(LogError::Corrupt {
+            offset: 15,
+            error: FrameError::ChecksumMismatch { .. }
+        })
+    ));
+
+    // Nor can a damaged length that points past the end, which mustn't cut off what follows.
+    bytes[20] ^= 0xff;
+    bytes[15..19].copy_from_slice(&[0xff; 4]);
+    fs::write(&path, &bytes).unwrap();
+    assert!(matches!(
+        RecordLog::<Log>::open(&path, Options::default()),
+        Err(LogError::Corrupt {
+            offset: 15,
+            error: FrameError::Oversized { .. }
+        })
+    ));
+    assert_eq!(fs::read(&path).unwrap(), bytes);
+
+    let mut records = RecordLog::<Log>::create(
+        &path,
+        Options {
+            max_record_length: 8,
+            ..Options::default()
+        },
+    )
+    .unwrap();
+    assert!(matches!(
+        records.append(&log(0)),
+        Err(LogError::Frame(FrameError::Oversized { .. }))
+    ));
+    assert!(records.is_empty());
+    fs::remove_file(&path).unwrap();
+}
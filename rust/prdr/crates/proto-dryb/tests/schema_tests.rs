@@ -0,0 +1,206 @@
+use proto_dryb::schema::{
+    Enum, Field, Primitive, Struct, StructEncoding, Type, Variant, VariantKind,
+};
+use proto_dryb::{Deserialize, DeserializeError, Endianness, Schema, Serialize, SerializeError};
+
+#[derive(Serialize, Deserialize)]
+#[dryb(endian = "big")]
+struct Header {
+    length: u16,
+    #[dryb(endian = "little")]
+    flags: [u8; 2],
+}
+
+#[derive(Serialize, Deserialize)]
+#[dryb(length_prefixed)]
+enum Status {
+    Ok,
+    Moved(String),
+    Failed {
+        code: u32,
+    },
+    #[dryb(other)]
+    Unknown(u8),
+}
+
+#[derive(Serialize, Deserialize)]
+#[dryb(version = 2)]
+struct Record {
+    header: Header,
+    #[dryb(since = 2)]
+    statuses: Vec<Option<Status>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[dryb(tagged)]
+struct Tagged {
+    #[dryb(id = 9)]
+    value: i64,
+}
+
+fn field(name: &str, ty: Type) -> Field {
+    Field {
+        name: name.to_string(),
+        ty,
+        endian: None,
+        since: None,
+        id: None,
+    }
+}
+
+#[test]
+fn test_builtin_schemas() {
+    assert_eq!(u8::schema(), Type::Primitive(Primitive::U8));
+    assert_eq!(f64::schema(), Type::Primitive(Primitive::F64));
+    assert_eq!(bool::schema(), Type::Primitive(Primitive::Bool));
+    assert_eq!(
+        Option::<i16>::schema(),
+        Type::Option(Box::new(Type::Primitive(Primitive::I16)))
+    );
+    assert_eq!(
+        Vec::<u32>::schema(),
+        Type::Vec {
+            item: Box::new(Type::Primitive(Primitive::U32)),
+        }
+    );
+    assert_eq!(String::schema(), Type::String);
+    assert_eq!(
+        <[i8; 3]>::schema(),
+        Type::Array {
+            len: 3,
+            item: Box::new(Type::Primitive(Primitive::I8)),
+        }
+    );
+    assert_eq!(str::schema(), String::schema());
+    assert_eq!(<&str>::FINGERPRINT, String::FINGERPRINT);
+    assert_eq!(<Box<str>>::FINGERPRINT, String::FINGERPRINT);
+    assert_eq!(<[u32]>::schema(), Vec::<u32>::schema());
+    assert_eq!(<&[u32]>::FINGERPRINT, Vec::<u32>::FINGERPRINT);
+    assert_eq!(Primitive::U64.width(), 8);
+    assert_eq!(Primitive::Bool.width(), 1);
+}
+
+#[test]
+fn test_derived_struct_schema() {
+    let Type::Struct(header) = Header::schema() else {
+        panic!("expected a struct schema");
+    };
+    assert_eq!(
+        header,
+        Struct {
+            name: "Header".to_string(),
+            endian: Some(Endianness::Big),
+            encoding: StructEncoding::Positional,
+            fields: vec![
+                field("length", Type::Primitive(Primitive::U16)),
+                Field {
+                    endian: Some(Endianness::Little),
+                    ..field("flags", <[u8; 2]>::schema())
+                },
+            ],
+        }
+    );
+
+    let Type::Struct(record) = Record::schema() else {
+        panic!("expected a struct schema");
+    };
+    assert_eq!(record.encoding, StructEncoding::Versioned(2));
+    assert_eq!(record.fields[0].since, None);
+    assert_eq!(record.fields[1].since, Some(2));
+    assert_eq!(record.fields[1].ty, Vec::<Option<Status>>::schema());
+
+    let Type::Struct(tagged) = Tagged::schema() else {
+        panic!("expected a struct schema");
+    };
+    assert_eq!(tagged.encoding, StructEncoding::Tagged);
+    assert_eq!(tagged.fields[0].id, Some(9));
+}
+
+#[test]
+fn test_derived_enum_schema() {
+    assert_eq!(
+        Status::schema(),
+        Type::Enum(Enum {
+            name: "Status".to_string(),
+            endian: None,
+            length_prefixed: true,
+            variants: vec![
+                Variant {
+                    name: "Ok".to_string(),
+                    tag: 0,
+                    kind: VariantKind::Unit,
+                    fields: vec![],
+                    other: false,
+                },
+                Variant {
+                    name: "Moved".to_string(),
+                    tag: 1,
+                    kind: VariantKind::Tuple,
+                    fields: vec![field("0", String::schema())],
+                    other: false,
+                },
+                Variant {
+                    name: "Failed".to_string(),
+                    tag: 2,
+                    kind: VariantKind::Struct,
+                    fields: vec![field("code", u32::schema())],
+                    other: false,
+                },
+                Variant {
+                    name: "Unknown".to_string(),
+                    tag: 3,
+                    kind: VariantKind::Tuple,
+                    fields: vec![field("0", u8::schema())],
+                    other: true,
+                },
+            ],
+        })
+    );
+}
+
+#[test]
+fn test_enum_variant_lookup() {
+    let Type::Enum(status) = Status::schema() else {
+        panic!("expected an enum schema");
+    };
+    assert_eq!(status.variant(1).unwrap().name, "Moved");
+    assert_eq!(status.variant(3).unwrap().name, "Unknown");
+    assert_eq!(status.variant(200).unwrap().name, "Unknown");
+    assert!(status.variant(200).unwrap().captures_tag());
+}
+
+#[test]
+fn test_schema_opt_out() {
+    // A recursive type can't have a derived `Schema`, so it opts out and writes its own.
+    #[derive(Serialize, Deserialize)]
+    #[dryb(no_schema)]
+    struct Node {
+        value: u8,
+        children: Vec<Node>,
+    }
+
+    impl Schema for Node {
+        const FINGERPRINT: u64 = 0;
+
+        fn schema() -> Type {
+            Type::Primitive(Primitive::U8)
+        }
+    }
+
+    // A type that only derives `Deserialize` derives `Schema` separately.
+    #[derive(Deserialize, Schema)]
+    struct Incoming {
+        value: u8,
+    }
+
+    assert_eq!(Node::schema(), Type::Primitive(Primitive::U8));
+    assert_eq!(
+        Incoming::schema(),
+        Type::Struct(Struct {
+            name: "Incoming".to_string(),
+            endian: None,
+            encoding: StructEncoding::Positional,
+            fields: vec![field("value", u8::schema())],
+        })
+    );
+}
@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use proto_dryb::serde_format::{from_slice, to_slice, to_vec, Error};
+use proto_dryb::{DeserializeError, Endianness, SerializeError};
+
+/// Types using the proto-dryb derives.
+mod native {
+    use std::collections::BTreeMap;
+
+    use proto_dryb::{Deserialize, DeserializeError, Endianness, Serialize, SerializeError};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    pub enum Shape {
+        Empty,
+        Circle(f32),
+        Rect(u16, u16),
+        Polygon { points: Vec<[i32; 2]> },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Drawing {
+        pub id: u64,
+        pub title: String,
+        pub visible: bool,
+        pub layer: Option<i8>,
+        pub shapes: Vec<Shape>,
+        pub tags: BTreeMap<String, u32>,
+    }
+}
+
+/// The same types deriving serde's traits instead.
+mod bridged {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    pub enum Shape {
+        Empty,
+        Circle(f32),
+        Rect(u16, u16),
+        Polygon { points: Vec<[i32; 2]> },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Drawing {
+        pub id: u64,
+        pub title: String,
+        pub visible: bool,
+        pub layer: Option<i8>,
+        pub shapes: Vec<Shape>,
+        pub tags: BTreeMap<String, u32>,
+    }
+}
+
+fn native_drawing() -> native::Drawing {
+    native::Drawing {
+        id: 0x0102030405060708,
+        title: "plan".to_string(),
+        visible: true,
+        layer: Some(-2),
+        shapes: vec![
+            native::Shape::Empty,
+            native::Shape::Circle(1.5),
+            native::Shape::Rect(3, 4),
+            native::Shape::Polygon {
+                points: vec![[0, 0], [5, -5]],
+            },
+        ],
+        tags: BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 2)]),
+    }
+}
+
+fn bridged_drawing() -> bridged::Drawing {
+    bridged::Drawing {
+        id: 0x0102030405060708,
+        title: "plan".to_string(),
+        visible: true,
+        layer: Some(-2),
+        shapes: vec![
+            bridged::Shape::Empty,
+            bridged::Shape::Circle(1.5),
+            bridged::Shape::Rect(3, 4),
+            bridged::Shape::Polygon {
+                points: vec![[0, 0], [5, -5]],
+            },
+        ],
+        tags: BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 2)]),
+    }
+}
+
+/// Checks both paths write the same bytes and each reads what the other wrote.
+fn assert_agree<N, B>(native: &N, bridged: &B)
+where
+    N: proto_dryb::Serialize + proto_dryb::Deserialize + PartialEq + Debug,
+    B: serde::Serialize + for<'de> serde::Deserialize<'de> + PartialEq + Debug,
+{
+    for endian in [Endianness::Little, Endianness::Big] {
+        let mut buffer = [0u8; 512];
+        let size = native.serialize(&mut buffer, endian).unwrap();
+        let bytes = to_vec(bridged, endian).unwrap();
+        assert_eq!(bytes, &buffer[..size], "{:?} with {:?}", bridged, endian);
+
+        let (decoded, read) = from_slice::<B>(&buffer[..size], endian).unwrap();
+        assert_eq!((&decoded, read), (bridged, size));
+        let (decoded, read) = N::deserialize(&bytes, endian).unwrap();
+        assert_eq!((&decoded, read), (native, size));
+    }
+}
+
+#[test]
+fn test_serde_matches_native_layout() {
+    assert_agree(&native_drawing(), &bridged_drawing());
+    assert_agree(&native::Shape::Rect(1, 2), &bridged::Shape::Rect(1, 2));
+    assert_agree(&Some(vec![true, false]), &Some(vec![true, false]));
+    assert_agree(&[7u64; 3], &[7u64; 3]);
+    assert_agree(&"héllo".to_string(), &"héllo".to_string());
+    assert_agree(&Vec::<u8>::new(), &Vec::<u8>::new());
+}
+
+#[test]
+fn test_serde_borrows_strings() {
+    #[derive(serde::Deserialize)]
+    struct Borrowed<'a> {
+        name: &'a str,
+        count: u16,
+    }
+
+    let mut buffer = [0u8; 32];
+    let size = to_slice(&("abc", 9u16), &mut buffer, Endianness::Little).unwrap();
+    let (borrowed, _) = from_slice::<Borrowed>(&buffer[..size], Endianness::Little).unwrap();
+    assert_eq!((borrowed.name, borrowed.count), ("abc", 9));
+}
+
+#[test]
+fn test_serde_errors() {
+    let mut buffer = [0u8; 4];
+    assert!(matches!(
+        to_slice("too long", &mut buffer, Endianness::Little),
+        Err(Error::Serialize(SerializeError::BufferOverflow))
+    ));
+    assert!(matches!(
+        to_slice(&'x', &mut buffer, Endianness::Little),
+        Err(Error::Unsupported("char"))
+    ));
+    assert!(matches!(
+        from_slice::<Option<u8>>(&[2, 0], Endianness::Little),
+        Err(Error::Deserialize(DeserializeError::Invalid))
+    ));
+    assert!(matches!(
+        from_slice::<String>(&[9, 0, 0, 0, b'a'], Endianness::Little),
+        Err(Error::Deserialize(DeserializeError::Invalid))
+    ));
+    assert!(from_slice::<bridged::Shape>(&[9], Endianness::Little).is_err());
+}
@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+
+use proto_dryb::checksum::XxHash64;
+use proto_dryb::{
+    Checksummed, Deserialize, DeserializeError, Endianness, Fingerprinted, Serialize,
+    SerializeError,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Point {
+    x: i32,
+    #[dryb(endian = "big")]
+    y: i32,
+    flags: [u8; 3],
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Empty,
+    Circle {
+        center: Point,
+        radius: f64,
+    },
+    Label(String),
+    #[dryb(other)]
+    Unknown(u8),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(length_prefixed)]
+enum Command {
+    Stop,
+    Draw(Vec<Shape>),
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[dryb(version = 2)]
+struct Header {
+    id: u32,
+    #[dryb(since = 2)]
+    name: String,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[dryb(tagged)]
+struct Extra {
+    #[dryb(id = 1)]
+    note: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Scene {
+    header: Header,
+    extra: Extra,
+    shapes: Vec<Shape>,
+    points: Vec<Point>,
+    command: Command,
+    names: HashMap<u16, String>,
+    weights: BTreeMap<u8, f32>,
+    boxed: Box<Option<u64>>,
+}
+
+fn scene() -> Scene {
+    let point = Point {
+        x: -1,
+        y: 2,
+        flags: [1, 2, 3],
+    };
+    Scene {
+        header: Header {
+            id: 9,
+            name: "scene".to_string(),
+        },
+        extra: Extra {
+            note: Some("n".to_string()),
+        },
+        shapes: vec![
+            Shape::Empty,
+            Shape::Circle {
+                center: Point { x: 5, ..point },
+                radius: 1.5,
+            },
+            Shape::Label("label".to_string()),
+            Shape::Unknown(77),
+        ],
+        points: vec![
+            Point {
+                x: 0,
+                y: 0,
+                flags: [0; 3],
+            },
+            point,
+        ],
+        command: Command::Draw(vec![Shape::Label("x".to_string())]),
+        names: HashMap::from([(1, "one".to_string()), (2, "two".to_string())]),
+        weights: BTreeMap::from([(1, 0.5), (2, 0.25)]),
+        boxed: Box::new(Some(3)),
+    }
+}
+
+fn assert_skips<T: Serialize + Deserialize + Debug>(value: &T) {
+    for endian in [Endianness::Little, Endianness::Big] {
+        let mut buffer = vec![0u8; 4096];
+        let size = value.serialize(&mut buffer, endian).unwrap();
+        buffer[size] = 0xee;
+        assert_eq!(
+            T::skip(&buffer[..size + 1], endian).unwrap(),
+            size,
+            "{:?}",
+            value
+        );
+        if let Some(fixed) = T::FIXED_SIZE {
+            assert_eq!(fixed, size, "{:?}", value);
+        }
+        assert!(T::skip(&buffer[..size - 1], endian).is_err(), "{:?}", value);
+    }
+}
+
+#[test]
+fn test_skip_matches_encoded_size() {
+    assert_skips(&scene());
+    assert_skips(&Shape::Circle {
+        center: Point {
+            x: 1,
+            y: 2,
+            flags: [3; 3],
+        },
+        radius: 0.0,
+    });
+    assert_skips(&Command::Stop);
+    assert_skips(&Header::default());
+    assert_skips(&vec![[1u16, 2], [3, 4]]);
+    assert_skips(&vec![vec!["a".to_string()], vec![]]);
+    assert_skips(&Fingerprinted(scene()));
+    assert_skips(&Checksummed::<_, XxHash64>::new(Point {
+        x: 1,
+        y: 1,
+        flags: [1; 3],
+    }));
+}
+
+#[test]
+fn test_fixed_sizes() {
+    assert_eq!(u8::FIXED_SIZE, Some(1));
+    assert_eq!(f64::FIXED_SIZE, Some(8));
+    assert_eq!(<[u32; 5]>::FIXED_SIZE, Some(20));
+    assert_eq!(Point::FIXED_SIZE, Some(11));
+    assert_eq!(Fingerprinted::<Point>::FIXED_SIZE, Some(19));
+    assert_eq!(Box::<[Point; 2]>::FIXED_SIZE, Some(22));
+    assert_eq!(Checksummed::<Point, XxHash64>::FIXED_SIZE, Some(23));
+    assert_eq!(Option::<u8>::FIXED_SIZE, None);
+    assert_eq!(String::FIXED_SIZE, None);
+    assert_eq!(Shape::FIXED_SIZE, None);
+    assert_eq!(Header::FIXED_SIZE, None);
+    assert_eq!(Extra::FIXED_SIZE, None);
+}
+
+#[test]
+fn test_skip_checks_lengths_not_contents() {
+    // A huge count of fixed-size elements is rejected from its length alone.
+    let huge = [0xffu8, 0xff, 0xff, 0xff, 0, 0];
+    assert!(matches!(
+        Vec::<u64>::skip(&huge, Endianness::Little),
+        Err(DeserializeError::Invalid)
+    ));
+
+    let invalid_utf8 = [2u8, 0, 0, 0, 0xff, 0xfe];
+    assert!(String::deserialize(&invalid_utf8, Endianness::Little).is_err());
+    assert_eq!(String::skip(&invalid_utf8, Endianness::Little).unwrap(), 6);
+
+    assert!(Option::<u8>::skip(&[2, 0], Endianness::Little).is_err());
+    assert_eq!(Shape::skip(&[200], Endianness::Little).unwrap(), 1);
+    assert!(matches!(
+        Fingerprinted::<Point>::skip(&[0; 19], Endianness::Little),
+        Err(DeserializeError::SchemaMismatch { .. })
+    ));
+    assert!(matches!(
+        Point::skip(&[], Endianness::Little),
+        Err(DeserializeError::Invalid)
+    ));
+}
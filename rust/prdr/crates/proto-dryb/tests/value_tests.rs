@@ -0,0 +1,295 @@
+use std::collections::BTreeMap;
+
+use proto_dryb::{
+    Deserialize, DeserializeError, Endianness, Schema, Serialize, SerializeError, Value,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(endian = "big")]
+struct Header {
+    length: u16,
+    #[dryb(endian = "little")]
+    flags: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(length_prefixed)]
+enum Status {
+    Ok,
+    Moved(String),
+    Failed {
+        code: i32,
+        retry: Option<f64>,
+    },
+    #[dryb(other)]
+    Unknown(u8),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(version = 2)]
+struct Record {
+    header: Header,
+    statuses: Vec<Status>,
+    #[dryb(since = 2)]
+    labels: BTreeMap<String, bool>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(tagged)]
+struct Sample {
+    #[dryb(id = 4)]
+    values: [i16; 3],
+    #[dryb(id = 1)]
+    id: u64,
+}
+
+fn record() -> Record {
+    let mut labels = BTreeMap::new();
+    labels.insert("retried".to_string(), true);
+    labels.insert("cached".to_string(), false);
+
+    Record {
+        header: Header {
+            length: 300,
+            flags: 0xdeadbeef,
+        },
+        statuses: vec![
+            Status::Ok,
+            Status::Moved("/elsewhere".to_string()),
+            Status::Failed {
+                code: -2,
+                retry: Some(1.5),
+            },
+            Status::Unknown(200),
+        ],
+        labels,
+    }
+}
+
+fn assert_roundtrip<T: Schema + Serialize>(value: &T, endian: Endianness) -> Value {
+    let mut buffer = [0u8; 512];
+    let size = value.serialize(&mut buffer, endian).unwrap();
+
+    let (decoded, read) = Value::decode(&T::schema(), &buffer[..size], endian).unwrap();
+    assert_eq!(read, size);
+
+    let mut encoded = [0u8; 512];
+    let written = decoded.encode(&T::schema(), &mut encoded, endian).unwrap();
+    assert_eq!(&encoded[..written], &buffer[..size]);
+    decoded
+}
+
+#[test]
+fn test_value_roundtrip() {
+    for endian in [Endianness::Little, Endianness::Big] {
+        assert_roundtrip(&record(), endian);
+        assert_roundtrip(
+            &Sample {
+                values: [1, -2, 3],
+                id: u64::MAX,
+            },
+            endian,
+        );
+        assert_roundtrip(&vec![Some(1u8), None], endian);
+    }
+}
+
+#[test]
+fn test_value_tree() {
+    let value = assert_roundtrip(&record(), Endianness::Little);
+
+    assert_eq!(
+        value.field("header"),
+        Some(&Value::Struct(vec![
+            ("length".to_string(), Value::U16(300)),
+            ("flags".to_string(), Value::U32(0xdeadbeef)),
+        ]))
+    );
+    let Some(Value::Seq(statuses)) = value.field("statuses") else {
+        panic!("expected a sequence of statuses");
+    };
+    assert_eq!(
+        statuses[1],
+        Value::Variant {
+            name: "Moved".to_string(),
+            tag: 1,
+            fields: vec![("0".to_string(), Value::String("/elsewhere".to_string()))],
+            rest: Vec::new(),
+        }
+    );
+    assert_eq!(
+        statuses[2].field("retry"),
+        Some(&Value::Option(Some(Box::new(Value::F64(1.5)))))
+    );
+    assert_eq!(statuses[3].field("0"), Some(&Value::U8(200)));
+    assert_eq!(
+        value.field("labels"),
+        Some(&Value::Map(vec![
+            (Value::String("cached".to_string()), Value::Bool(false)),
+            (Value::String("retried".to_string()), Value::Bool(true)),
+        ]))
+    );
+}
+
+#[test]
+fn test_value_keeps_unknown_tagged_fields() {
+    #[derive(Serialize, Deserialize)]
+    #[dryb(tagged)]
+    struct Old {
+        #[dryb(id = 1)]
+        id: u64,
+    }
+
+    let sample = Sample {
+        values: [7, 8, 9],
+        id: 42,
+    };
+    let mut buffer = [0u8; 64];
+    let size = sample.serialize(&mut buffer, Endianness::Big).unwrap();
+
+    let value = assert_reencodes(&Old::schema(), &buffer[..size], Endianness::Big);
+    assert_eq!(
+        value,
+        Value::Tagged {
+            fields: vec![("id".to_string(), Value::U64(42))],
+            entries: vec![(4, Some(vec![0, 7, 0, 8, 0, 9])), (1, None)],
+        }
+    );
+
+    // A field set after reading goes after the entries that were read.
+    let value = Value::Tagged {
+        fields: vec![
+            ("id".to_string(), Value::U64(1)),
+            ("values".to_string(), Value::Seq(vec![Value::I16(-1); 3])),
+        ],
+        entries: vec![(1, None)],
+    };
+    let size = value
+        .encode(&Sample::schema(), &mut buffer, Endianness::Big)
+        .unwrap();
+    assert_eq!(
+        &buffer[..size],
+        [
+            0, 0, 0, 26, 0, 1, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 1, 0, 4, 0, 0, 0, 6, 255, 255, 255,
+            255, 255, 255
+        ]
+    );
+}
+
+/// Decodes `bytes` as `ty` and checks the value encodes back to them.
+fn assert_reencodes(ty: &proto_dryb::schema::Type, bytes: &[u8], endian: Endianness) -> Value {
+    let (value, read) = Value::decode(ty, bytes, endian).unwrap();
+    assert_eq!(read, bytes.len());
+
+    let mut encoded = [0u8; 512];
+    let written = value.encode(ty, &mut encoded, endian).unwrap();
+    assert_eq!(&encoded[..written], bytes);
+    value
+}
+
+#[test]
+fn test_value_keeps_payload_version() {
+    #[derive(Serialize, Deserialize)]
+    #[dryb(version = 1)]
+    struct RecordV1 {
+        header: Header,
+        statuses: Vec<Status>,
+    }
+
+    let old = RecordV1 {
+        header: Header {
+            length: 1,
+            flags: 2,
+        },
+        statuses: vec![Status::Ok],
+    };
+    let mut buffer = [0u8; 512];
+    let size = old.serialize(&mut buffer, Endianness::Little).unwrap();
+    let value = assert_reencodes(&Record::schema(), &buffer[..size], Endianness::Little);
+    assert!(matches!(value, Value::Versioned { version: 1, ref rest, .. } if rest.is_empty()));
+    assert_eq!(value.field("labels"), None);
+
+    // A newer payload keeps the fields the older schema doesn't know as raw bytes.
+    let size = record().serialize(&mut buffer, Endianness::Little).unwrap();
+    let value = assert_reencodes(&RecordV1::schema(), &buffer[..size], Endianness::Little);
+    assert!(matches!(value, Value::Versioned { version: 2, ref rest, .. } if !rest.is_empty()));
+}
+
+#[test]
+fn test_value_keeps_unknown_variants() {
+    #[derive(Serialize, Deserialize)]
+    #[dryb(length_prefixed)]
+    enum StatusV2 {
+        Ok,
+        Moved(String),
+        Failed { code: i32, retry: Option<f64> },
+        Unknown(u8),
+        Redirected { to: String, permanent: bool },
+    }
+
+    #[derive(Serialize, Deserialize)]
+    enum Plan {
+        Free,
+        #[dryb(other)]
+        Other,
+    }
+
+    let mut buffer = [0u8; 64];
+    let size = StatusV2::Redirected {
+        to: "/x".to_string(),
+        permanent: true,
+    }
+    .serialize(&mut buffer, Endianness::Big)
+    .unwrap();
+    let value = assert_reencodes(&Status::schema(), &buffer[..size], Endianness::Big);
+    let Value::Variant {
+        name, tag, rest, ..
+    } = &value
+    else {
+        panic!("expected a variant");
+    };
+    assert_eq!((name.as_str(), *tag), ("Unknown", 4));
+    assert_eq!(rest, &buffer[5..size]);
+
+    let value = assert_reencodes(&Plan::schema(), &[9], Endianness::Little);
+    assert!(matches!(value, Value::Variant { tag: 9, .. }));
+
+    // A catch-all can't take a tag that decodes as a known variant.
+    let Value::Variant { name, fields, .. } = value else {
+        unreachable!()
+    };
+    let value = Value::Variant {
+        name,
+        tag: 0,
+        fields,
+        rest: Vec::new(),
+    };
+    assert!(matches!(
+        value.encode(&Plan::schema(), &mut buffer, Endianness::Little),
+        Err(SerializeError::KnownTag(0))
+    ));
+}
+
+#[test]
+fn test_value_rejects_mismatched_shapes() {
+    let mut buffer = [0u8; 64];
+    assert!(matches!(
+        Value::U32(1).encode(&u16::schema(), &mut buffer, Endianness::Little),
+        Err(SerializeError::ValueMismatch)
+    ));
+    assert!(matches!(
+        Value::Seq(vec![Value::U8(1)]).encode(
+            &<[u8; 2]>::schema(),
+            &mut buffer,
+            Endianness::Little
+        ),
+        Err(SerializeError::ValueMismatch)
+    ));
+    assert!(matches!(
+        Value::Struct(vec![]).encode(&Header::schema(), &mut buffer, Endianness::Little),
+        Err(SerializeError::ValueMismatch)
+    ));
+
+    assert!(Value::decode(&Record::schema(), &[2, 0, 9, 0], Endianness::Little).is_err());
+    assert!(Value::decode(&String::schema(), &[1, 0, 0, 0, 0xff], Endianness::Little).is_err());
+}
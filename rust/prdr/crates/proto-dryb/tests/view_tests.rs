@@ -0,0 +1,98 @@
+use proto_dryb::{Deserialize, DeserializeError, Endianness, Serialize, SerializeError};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Status {
+    Ok,
+    NotFound,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(view)]
+pub struct Log {
+    timestamp: i64,
+    server_name: String,
+    tags: Vec<String>,
+    body: Vec<u8>,
+    #[dryb(endian = "big")]
+    zone_id: u32,
+    status: Status,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[dryb(view, endian = "big")]
+struct Pair {
+    left: u16,
+    right: Option<u16>,
+}
+
+fn log() -> Log {
+    Log {
+        timestamp: -7,
+        server_name: "edge-1".to_string(),
+        tags: vec!["a".to_string(), "bc".to_string()],
+        body: vec![1, 2, 3],
+        zone_id: 0x01020304,
+        status: Status::NotFound,
+    }
+}
+
+fn to_bytes<T: Serialize>(value: &T, endian: Endianness) -> Vec<u8> {
+    let mut buffer = vec![0u8; 1024];
+    let size = value.serialize(&mut buffer, endian).unwrap();
+    buffer.truncate(size);
+    buffer
+}
+
+#[test]
+fn test_view_accessors() {
+    for endian in [Endianness::Little, Endianness::Big] {
+        let bytes = to_bytes(&log(), endian);
+        let view = LogView::new(&bytes, endian);
+
+        assert_eq!(view.status().unwrap(), Status::NotFound);
+        assert_eq!(view.zone_id().unwrap(), 0x01020304);
+        assert_eq!(view.timestamp().unwrap(), -7);
+        assert_eq!(view.tags().unwrap(), ["a", "bc"]);
+        assert_eq!(view.decode().unwrap(), log());
+
+        // Strings and bytes are borrowed from the buffer.
+        let server_name: &str = view.server_name().unwrap();
+        assert_eq!(server_name, "edge-1");
+        assert!(bytes.as_ptr_range().contains(&server_name.as_ptr()));
+        let body: &[u8] = view.body().unwrap();
+        assert_eq!(body, [1, 2, 3]);
+    }
+
+    // The struct's own byte order wins over the one the view is given.
+    let bytes = to_bytes(
+        &Pair {
+            left: 1,
+            right: Some(2),
+        },
+        Endianness::Little,
+    );
+    assert_eq!(bytes, [0, 1, 1, 0, 2]);
+    let view = PairView::new(&bytes, Endianness::Little);
+    assert_eq!((view.left().unwrap(), view.right().unwrap()), (1, Some(2)));
+}
+
+#[test]
+fn test_view_errors() {
+    let bytes = to_bytes(&log(), Endianness::Little);
+
+    // Fields before the damage still decode; the rest report it.
+    let view = LogView::new(&bytes[..12], Endianness::Little);
+    assert_eq!(view.timestamp().unwrap(), -7);
+    assert!(matches!(view.tags(), Err(DeserializeError::Invalid)));
+    assert!(matches!(view.status(), Err(DeserializeError::Invalid)));
+    assert!(view.decode().is_err());
+
+    let mut bytes = bytes;
+    let last = bytes.len() - 1;
+    bytes[last] = 9;
+    let view = LogView::new(&bytes, Endianness::Little);
+    assert_eq!(view.server_name().unwrap(), "edge-1");
+    assert!(matches!(view.status(), Err(DeserializeError::Invalid)));
+
+    assert_eq!(Status::skip(&[1, 0xff], Endianness::Little).unwrap(), 1);
+}